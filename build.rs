@@ -0,0 +1,34 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::process::Command;
+
+fn main() {
+	println!("cargo:rerun-if-changed=.git/HEAD");
+
+	// git commit is optional, eg. building from a source archive
+	let commit = Command::new("git")
+		.args(&["rev-parse", "--short", "HEAD"])
+		.output()
+		.ok()
+		.filter(|x| x.status.success())
+		.and_then(|x| String::from_utf8(x.stdout).ok());
+
+	if let Some(commit) = commit {
+		println!("cargo:rustc-env=GIT_COMMIT={}", commit.trim());
+	}
+}
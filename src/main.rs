@@ -17,7 +17,7 @@
 
 use structopt::StructOpt;
 
-use crate::config::{get_config, VersionInfo};
+use crate::config::{get_conf_file, get_config, VersionInfo};
 
 mod config;
 mod errors;
@@ -43,9 +43,11 @@ fn run() -> errors::Result<()> {
 		author: env!("CARGO_PKG_AUTHORS"),
 	};
 
-	let config = get_config(&opt, &version_info)?;
+	let conf_file = get_conf_file(&opt, &version_info);
 
-	rpc::run(&opt, &config)?;
+	let config = get_config(&conf_file)?;
+
+	rpc::run(&opt, &config, &conf_file)?;
 
 	Ok(())
 }
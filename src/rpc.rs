@@ -16,24 +16,58 @@
 // along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 
 use futures::future::Future;
+use jsonrpc_core::{MetaIoHandler, Middleware, RemoteProcedure};
 use log::info;
+use tokio::runtime::Runtime;
 
 use crate::config::Config;
 use crate::opt::{Opt, DEFAULT_RPC_PORT, DEFAULT_WS_PORT};
+use crate::rpc::batch_limit::BatchLimiter;
+use crate::rpc::cache::Caches;
 use crate::rpc::chain::{Chain, ChainApi};
+use crate::rpc::client::RpcClient;
+use crate::rpc::front::Front;
+use crate::rpc::metadata::Metadata;
+use crate::rpc::method_filter::MethodFilter;
+use crate::rpc::panel::{Admin, AdminApi, LiveConfig, Panel, PanelApi};
+use crate::rpc::request_id::RequestIds;
+use crate::rpc::request_log::RequestLogger;
+use crate::rpc::response_size::ResponseSizes;
 use crate::rpc::server::{start_http, start_ws};
+use crate::rpc::subscription::{ActiveSubscriptions, Subscription, SubscriptionApi};
+use crate::rpc::timeout::Timeouts;
 
+mod batch_limit;
+mod cache;
 mod chain;
+mod circuit_breaker;
 pub mod client;
+mod compression;
+mod concurrency;
 pub mod errors;
+mod front;
 mod metadata;
+mod method_filter;
+mod metrics;
+mod panel;
+mod prefetch;
+mod projection;
+mod proxy;
+mod rate_limit;
+mod request_id;
+mod request_log;
+mod response_size;
 mod serde;
 mod server;
+mod subscription;
+mod timeout;
 mod types;
 
-pub fn run(opt: &Opt, config: &Config) -> errors::Result<()> {
+pub fn run(opt: &Opt, config: &Config, conf_file: &Path) -> errors::Result<()> {
 	let rpc_interface: &str = if opt.rpc_external {
 		"0.0.0.0"
 	} else {
@@ -56,15 +90,128 @@ pub fn run(opt: &Opt, config: &Config) -> errors::Result<()> {
 
 	let (signal, exit) = exit_future::signal();
 
+	let rpc_client = Arc::new(RpcClient::new(config.clone())?);
+
+	let caches = Caches::new(config);
+
+	let _prefetch = match &config.prefetch {
+		Some(prefetch) => {
+			let shard_count = config.shards.len() as u16;
+			let runtime =
+				prefetch::start(prefetch, shard_count, rpc_client.clone(), caches.clone())?;
+			info!("Prefetch every {} ms", prefetch.interval_ms);
+			Some(runtime)
+		}
+		None => None,
+	};
+
+	// drive the subscriptions
+	let subscription_runtime = Runtime::new()?;
+
+	// the subscriptions of all the handlers, listed by `panel_subscriptions`
+	let active_subscriptions = Arc::new(ActiveSubscriptions::new());
+
+	// shared by the handlers, swapped by `panel_reloadConfig`
+	let shared_config = Arc::new(RwLock::new(config.clone()));
+
+	let timeouts = Timeouts::new(shared_config.clone());
+
+	let request_ids = Arc::new(RequestIds::new(config));
+
+	let request_logger = RequestLogger::new(config);
+
+	let response_sizes = ResponseSizes::new(config);
+
+	let method_filter = MethodFilter::new(config);
+
+	let live_config = Arc::new(LiveConfig::new(conf_file.to_owned(), shared_config.clone()));
+
 	let handler = || {
-		let chain = Chain::new(config.clone());
+		let methods = Arc::new(RwLock::new(Vec::new()));
 
-		let mut io = pubsub::PubSubHandler::default();
-		io.extend_with(chain.to_delegate());
+		let chain = Chain::new(shared_config.clone(), rpc_client.clone(), caches.clone());
+		let panel = Panel::new(
+			shared_config.clone(),
+			rpc_client.clone(),
+			caches.clone(),
+			methods.clone(),
+		);
+		let subscription = Subscription::new(
+			shared_config.clone(),
+			rpc_client.clone(),
+			subscription_runtime.executor(),
+			active_subscriptions.clone(),
+		);
+
+		let mut io = pubsub::PubSubHandler::new(MetaIoHandler::with_middleware(BatchLimiter::new(
+			config.max_batch_size,
+		)));
+		extend_with(
+			&mut io,
+			&methods,
+			&method_filter,
+			&timeouts,
+			&request_logger,
+			&request_ids,
+			&response_sizes,
+			chain.to_delegate(),
+		);
+		extend_with(
+			&mut io,
+			&methods,
+			&method_filter,
+			&timeouts,
+			&request_logger,
+			&request_ids,
+			&response_sizes,
+			panel.to_delegate(),
+		);
+		extend_with(
+			&mut io,
+			&methods,
+			&method_filter,
+			&timeouts,
+			&request_logger,
+			&request_ids,
+			&response_sizes,
+			subscription.to_delegate(),
+		);
+		if config.admin_rpc {
+			let admin = Admin::new(
+				caches.clone(),
+				rpc_client.clone(),
+				live_config.clone(),
+				active_subscriptions.clone(),
+			);
+			extend_with(
+				&mut io,
+				&methods,
+				&method_filter,
+				&timeouts,
+				&request_logger,
+				&request_ids,
+				&response_sizes,
+				admin.to_delegate(),
+			);
+		}
 		io
 	};
 
-	let _server = start_http(&rpc_address_http, handler())?;
+	let front = Front::new(config)?;
+
+	let keep_alive = config.http.as_ref().map(|x| x.keep_alive).unwrap_or(true);
+
+	// the method names are known once registered
+	let http_io = handler();
+	method_filter.check()?;
+
+	let _server = start_http(
+		&rpc_address_http,
+		http_io,
+		front,
+		request_ids.clone(),
+		keep_alive,
+	)?;
 
 	info!("Switch rpc http listen on: {}", rpc_address_http);
 
@@ -79,6 +226,42 @@ pub fn run(opt: &Opt, config: &Config) -> errors::Result<()> {
 	Ok(())
 }
 
+/// Register the exposed methods bounded by the timeouts and logged, and record the method names
+#[allow(clippy::too_many_arguments)]
+fn extend_with<S, D>(
+	io: &mut pubsub::PubSubHandler<Metadata, S>,
+	methods: &RwLock<Vec<String>>,
+	method_filter: &MethodFilter,
+	timeouts: &Timeouts,
+	request_logger: &RequestLogger,
+	request_ids: &RequestIds,
+	response_sizes: &ResponseSizes,
+	delegate: D,
+) where
+	S: Middleware<Metadata>,
+	D: IntoIterator<Item = (String, RemoteProcedure<Metadata>)>,
+{
+	let delegate = delegate
+		.into_iter()
+		.filter(|(name, _)| method_filter.is_allowed(name))
+		.map(|(name, procedure)| {
+			// the timed out calls are logged, with the request id
+			let procedure = response_sizes.apply(procedure);
+			let procedure = timeouts.apply(&name, procedure);
+			let procedure = request_logger.apply(&name, procedure);
+			let procedure = request_ids.apply(procedure);
+			(name, procedure)
+		})
+		.collect::<Vec<_>>();
+
+	methods
+		.write()
+		.expect("qed")
+		.extend(delegate.iter().map(|(name, _)| name.clone()));
+
+	io.extend_with(delegate);
+}
+
 fn parse_address(address: &str, port: Option<u16>) -> errors::Result<SocketAddr> {
 	let mut address: SocketAddr = address
 		.parse()
@@ -89,3 +272,103 @@ fn parse_address(address: &str, port: Option<u16>) -> errors::Result<SocketAddr>
 
 	Ok(address)
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, RwLock};
+
+	use crate::config::Config;
+	use crate::rpc::cache::Caches;
+	use crate::rpc::chain::{Chain, ChainApi};
+	use crate::rpc::client::RpcClient;
+	use crate::rpc::method_filter::MethodFilter;
+	use crate::rpc::panel::{Panel, PanelApi};
+	use crate::rpc::request_id::RequestIds;
+	use crate::rpc::request_log::RequestLogger;
+	use crate::rpc::response_size::ResponseSizes;
+	use crate::rpc::timeout::Timeouts;
+
+	use super::extend_with;
+
+	#[test]
+	fn test_extend_with() {
+		let config: Config = toml::from_str("[shards]").unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let methods = Arc::new(RwLock::new(Vec::new()));
+		let request_ids = RequestIds::new(&config);
+		let request_logger = RequestLogger::new(&config);
+		let response_sizes = ResponseSizes::new(&config);
+		let method_filter = MethodFilter::new(&config);
+		let config = Arc::new(RwLock::new(config));
+		let timeouts = Timeouts::new(config.clone());
+
+		let chain = Chain::new(config.clone(), rpc_client.clone(), caches.clone());
+		let panel = Panel::new(config, rpc_client, caches, methods.clone());
+
+		let mut io = pubsub::PubSubHandler::default();
+		extend_with(
+			&mut io,
+			&methods,
+			&method_filter,
+			&timeouts,
+			&request_logger,
+			&request_ids,
+			&response_sizes,
+			chain.to_delegate(),
+		);
+		extend_with(
+			&mut io,
+			&methods,
+			&method_filter,
+			&timeouts,
+			&request_logger,
+			&request_ids,
+			&response_sizes,
+			panel.to_delegate(),
+		);
+
+		let methods = methods.read().unwrap();
+		assert!(methods.contains(&"chain_getBestNumber".to_string()));
+		assert!(methods.contains(&"rpc_methods".to_string()));
+	}
+
+	#[test]
+	fn test_extend_with_method_filter() {
+		let config: Config = toml::from_str(
+			r#"
+			[methods]
+			deny = ["chain_getBestNumber"]
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let methods = Arc::new(RwLock::new(Vec::new()));
+		let request_ids = RequestIds::new(&config);
+		let request_logger = RequestLogger::new(&config);
+		let response_sizes = ResponseSizes::new(&config);
+		let method_filter = MethodFilter::new(&config);
+		let config = Arc::new(RwLock::new(config));
+		let timeouts = Timeouts::new(config.clone());
+
+		let chain = Chain::new(config.clone(), rpc_client, caches);
+
+		let mut io = pubsub::PubSubHandler::default();
+		extend_with(
+			&mut io,
+			&methods,
+			&method_filter,
+			&timeouts,
+			&request_logger,
+			&request_ids,
+			&response_sizes,
+			chain.to_delegate(),
+		);
+
+		let methods = methods.read().unwrap();
+		assert!(!methods.contains(&"chain_getBestNumber".to_string()));
+		assert!(methods.contains(&"chain_getFinalizedNumber".to_string()));
+	}
+}
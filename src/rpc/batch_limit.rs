@@ -0,0 +1,114 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use futures::future::{self, Either};
+use futures::Future;
+use jsonrpc_core::{Middleware, Request, Response};
+use log::debug;
+
+use crate::rpc::errors;
+
+type FutureResponse = Box<dyn Future<Item = Option<Response>, Error = ()> + Send>;
+
+/// Reject the batch requests of more calls than `max` before dispatch
+pub struct BatchLimiter {
+	max: usize,
+}
+
+impl BatchLimiter {
+	/// 0 to disable
+	pub fn new(max: usize) -> Self {
+		BatchLimiter { max }
+	}
+
+	fn check(&self, request: &Request) -> errors::Result<()> {
+		match request {
+			Request::Batch(calls) if self.max > 0 && calls.len() > self.max => {
+				Err(errors::ErrorKind::BatchTooLarge(calls.len(), self.max).into())
+			}
+			_ => Ok(()),
+		}
+	}
+}
+
+impl<M: jsonrpc_core::Metadata> Middleware<M> for BatchLimiter {
+	type Future = FutureResponse;
+
+	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X>
+	where
+		F: FnOnce(Request, M) -> X + Send,
+		X: Future<Item = Option<Response>, Error = ()> + Send + 'static,
+	{
+		if let Err(e) = self.check(&request) {
+			debug!("Batch rejected: {}", e);
+
+			let error: jsonrpc_core::Error = e.into();
+			let output = jsonrpc_core::Output::Failure(jsonrpc_core::Failure {
+				jsonrpc: Some(jsonrpc_core::Version::V2),
+				error,
+				id: jsonrpc_core::Id::Null,
+			});
+			return Either::A(Box::new(future::ok(Some(Response::Single(output)))));
+		}
+
+		Either::B(next(request, meta))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use jsonrpc_core::{MetaIoHandler, Value};
+
+	use super::BatchLimiter;
+
+	fn call(id: usize) -> String {
+		format!(
+			r#"{{"jsonrpc":"2.0","method":"ping","params":[],"id":{}}}"#,
+			id
+		)
+	}
+
+	fn batch(count: usize) -> String {
+		let calls = (0..count).map(call).collect::<Vec<_>>();
+		format!("[{}]", calls.join(","))
+	}
+
+	#[test]
+	fn test_batch_limiter() {
+		let mut io = MetaIoHandler::<(), _>::with_middleware(BatchLimiter::new(3));
+		io.add_method("ping", |_| Ok(Value::String("pong".to_string())));
+
+		// at the limit
+		let response = io.handle_request_sync(&batch(3), ()).unwrap();
+		let response: Value = serde_json::from_str(&response).unwrap();
+		let response = response.as_array().unwrap();
+		assert_eq!(response.len(), 3);
+		assert!(response.iter().all(|x| x["result"] == "pong"));
+
+		// over the limit
+		let response = io.handle_request_sync(&batch(4), ()).unwrap();
+		let response: Value = serde_json::from_str(&response).unwrap();
+		assert_eq!(response["error"]["message"], "Batch too large");
+		assert_eq!(response["error"]["data"]["size"], 4);
+		assert_eq!(response["error"]["data"]["max"], 3);
+
+		// single calls are not limited
+		let response = io.handle_request_sync(&call(0), ()).unwrap();
+		let response: Value = serde_json::from_str(&response).unwrap();
+		assert_eq!(response["result"], "pong");
+	}
+}
@@ -0,0 +1,453 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use lru_cache::LruCache;
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::rpc::metrics::{CacheCounters, CacheMetrics};
+use crate::rpc::types::{BlockNumber, ResultBlock};
+
+/// Node version rarely changes
+const NODE_VERSION_TTL: Duration = Duration::from_secs(60);
+
+/// The finalized number of a shard is refreshed by the block misses at most once in this interval
+const FINALIZED_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Caches shared by the rpc handlers
+#[derive(Clone)]
+pub struct Caches {
+	pub block: Arc<BlockCache>,
+	pub node_version: Arc<TtlCache<u16, Value>>,
+	pub head: Arc<HeadCache>,
+}
+
+impl Caches {
+	pub fn new(config: &Config) -> Self {
+		Caches {
+			block: Arc::new(BlockCache::new(config.block_cache_size)),
+			node_version: Arc::new(TtlCache::new(NODE_VERSION_TTL)),
+			head: Arc::new(HeadCache::new()),
+		}
+	}
+}
+
+/// Cache of finalized blocks with extrinsic results, finalized blocks are immutable
+pub struct BlockCache {
+	blocks: Mutex<LruCache<(u16, Vec<u8>), ResultBlock>>,
+	/// Hashes of the finalized block numbers
+	hashes: Mutex<LruCache<(u16, BlockNumber), Vec<u8>>>,
+	finalized_numbers: Mutex<HashMap<u16, BlockNumber>>,
+	finalized_refreshed: Mutex<HashMap<u16, Instant>>,
+	block_metrics: CacheMetrics,
+	hash_metrics: CacheMetrics,
+	finalized_metrics: CacheMetrics,
+}
+
+impl BlockCache {
+	pub fn new(capacity: usize) -> Self {
+		BlockCache {
+			blocks: Mutex::new(LruCache::new(capacity)),
+			hashes: Mutex::new(LruCache::new(capacity)),
+			finalized_numbers: Mutex::new(HashMap::new()),
+			finalized_refreshed: Mutex::new(HashMap::new()),
+			block_metrics: CacheMetrics::new(),
+			hash_metrics: CacheMetrics::new(),
+			finalized_metrics: CacheMetrics::new(),
+		}
+	}
+
+	pub fn get(&self, shard_num: u16, block_hash: &[u8]) -> Option<ResultBlock> {
+		let mut blocks = self.blocks.lock().expect("qed");
+		let result = blocks
+			.get_mut(&(shard_num, block_hash.to_vec()))
+			.map(|x| x.clone());
+		self.block_metrics.record(result.is_some());
+		result
+	}
+
+	/// Insert the block, ignored if the block is not known to be finalized
+	pub fn insert(&self, shard_num: u16, block: ResultBlock) {
+		if !self.is_finalized(shard_num, block.header.number) {
+			return;
+		}
+		let block_hash = match &block.header.block_hash {
+			Some(block_hash) => block_hash.0.clone(),
+			None => return,
+		};
+		self.insert_hash(shard_num, block.header.number, block_hash.clone());
+		let mut blocks = self.blocks.lock().expect("qed");
+		blocks.insert((shard_num, block_hash), block);
+	}
+
+	pub fn get_hash(&self, shard_num: u16, number: BlockNumber) -> Option<Vec<u8>> {
+		let mut hashes = self.hashes.lock().expect("qed");
+		let result = hashes.get_mut(&(shard_num, number)).map(|x| x.clone());
+		self.hash_metrics.record(result.is_some());
+		result
+	}
+
+	/// Insert the block hash, ignored if the block is not known to be finalized
+	pub fn insert_hash(&self, shard_num: u16, number: BlockNumber, block_hash: Vec<u8>) {
+		if !self.is_finalized(shard_num, number) {
+			return;
+		}
+		let mut hashes = self.hashes.lock().expect("qed");
+		hashes.insert((shard_num, number), block_hash);
+	}
+
+	pub fn is_finalized(&self, shard_num: u16, number: BlockNumber) -> bool {
+		let finalized_numbers = self.finalized_numbers.lock().expect("qed");
+		finalized_numbers
+			.get(&shard_num)
+			.map(|finalized| number <= *finalized)
+			.unwrap_or(false)
+	}
+
+	pub fn finalized_number(&self, shard_num: u16) -> Option<BlockNumber> {
+		let finalized_numbers = self.finalized_numbers.lock().expect("qed");
+		let result = finalized_numbers.get(&shard_num).cloned();
+		self.finalized_metrics.record(result.is_some());
+		result
+	}
+
+	/// Finalized number never goes back
+	pub fn set_finalized_number(&self, shard_num: u16, number: BlockNumber) {
+		let mut finalized_numbers = self.finalized_numbers.lock().expect("qed");
+		let finalized = finalized_numbers.entry(shard_num).or_insert(number);
+		if number > *finalized {
+			*finalized = number;
+		}
+	}
+
+	/// Whether a block miss refreshes the finalized number of the shard, at most once in the interval
+	pub fn should_refresh_finalized(&self, shard_num: u16) -> bool {
+		self.should_refresh_finalized_at(shard_num, Instant::now())
+	}
+
+	fn should_refresh_finalized_at(&self, shard_num: u16, now: Instant) -> bool {
+		let mut finalized_refreshed = self.finalized_refreshed.lock().expect("qed");
+		match finalized_refreshed.get(&shard_num) {
+			Some(refreshed) if now.duration_since(*refreshed) < FINALIZED_REFRESH_INTERVAL => false,
+			_ => {
+				finalized_refreshed.insert(shard_num, now);
+				true
+			}
+		}
+	}
+
+	pub fn block_counters(&self) -> CacheCounters {
+		self.block_metrics.counters()
+	}
+
+	pub fn hash_counters(&self) -> CacheCounters {
+		self.hash_metrics.counters()
+	}
+
+	pub fn finalized_counters(&self) -> CacheCounters {
+		self.finalized_metrics.counters()
+	}
+
+	/// Evict the blocks and the finalized numbers, return the count of the evicted blocks
+	pub fn clear(&self) -> usize {
+		self.finalized_numbers.lock().expect("qed").clear();
+		self.hashes.lock().expect("qed").clear();
+		let mut blocks = self.blocks.lock().expect("qed");
+		let count = blocks.len();
+		blocks.clear();
+		count
+	}
+}
+
+/// Best and finalized number of a shard
+#[derive(Clone, Debug, PartialEq)]
+pub struct Head {
+	pub best: Option<BlockNumber>,
+	pub finalized: Option<BlockNumber>,
+	/// Unix timestamp in milliseconds of the refresh
+	pub updated_at: u64,
+}
+
+impl Head {
+	pub fn new(best: Option<BlockNumber>, finalized: Option<BlockNumber>) -> Self {
+		Head {
+			best,
+			finalized,
+			updated_at: now_millis(),
+		}
+	}
+}
+
+fn now_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|x| x.as_secs() * 1000 + x.subsec_millis() as u64)
+		.unwrap_or(0)
+}
+
+/// Cache of the heads refreshed by the prefetch
+pub struct HeadCache {
+	heads: RwLock<HashMap<u16, Head>>,
+	metrics: CacheMetrics,
+}
+
+impl HeadCache {
+	pub fn new() -> Self {
+		HeadCache {
+			heads: RwLock::new(HashMap::new()),
+			metrics: CacheMetrics::new(),
+		}
+	}
+
+	pub fn get(&self, shard_num: u16) -> Option<Head> {
+		let heads = self.heads.read().expect("qed");
+		let result = heads.get(&shard_num).cloned();
+		self.metrics.record(result.is_some());
+		result
+	}
+
+	/// The head refreshed within `max_age_ms`
+	pub fn get_fresh(&self, shard_num: u16, max_age_ms: u64) -> Option<Head> {
+		let now = now_millis();
+		let heads = self.heads.read().expect("qed");
+		let result = heads
+			.get(&shard_num)
+			.filter(|x| now.saturating_sub(x.updated_at) < max_age_ms)
+			.cloned();
+		self.metrics.record(result.is_some());
+		result
+	}
+
+	pub fn counters(&self) -> CacheCounters {
+		self.metrics.counters()
+	}
+
+	pub fn insert(&self, shard_num: u16, head: Head) {
+		let mut heads = self.heads.write().expect("qed");
+		heads.insert(shard_num, head);
+	}
+
+	/// Refresh the best number, keeping the finalized number
+	pub fn insert_best(&self, shard_num: u16, best: BlockNumber) {
+		let mut heads = self.heads.write().expect("qed");
+		let finalized = heads.get(&shard_num).and_then(|x| x.finalized);
+		heads.insert(shard_num, Head::new(Some(best), finalized));
+	}
+
+	pub fn clear(&self) -> usize {
+		let mut heads = self.heads.write().expect("qed");
+		let count = heads.len();
+		heads.clear();
+		count
+	}
+}
+
+/// Cache of which the entries expire after the ttl
+pub struct TtlCache<K, V> {
+	ttl: Duration,
+	entries: Mutex<HashMap<K, (Instant, V)>>,
+	metrics: CacheMetrics,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+	pub fn new(ttl: Duration) -> Self {
+		TtlCache {
+			ttl,
+			entries: Mutex::new(HashMap::new()),
+			metrics: CacheMetrics::new(),
+		}
+	}
+
+	pub fn get(&self, key: &K) -> Option<V> {
+		let result = self.get_at(key, Instant::now());
+		self.metrics.record(result.is_some());
+		result
+	}
+
+	pub fn counters(&self) -> CacheCounters {
+		self.metrics.counters()
+	}
+
+	pub fn insert(&self, key: K, value: V) {
+		let mut entries = self.entries.lock().expect("qed");
+		entries.insert(key, (Instant::now(), value));
+	}
+
+	pub fn clear(&self) -> usize {
+		let mut entries = self.entries.lock().expect("qed");
+		let count = entries.len();
+		entries.clear();
+		count
+	}
+
+	fn get_at(&self, key: &K, now: Instant) -> Option<V> {
+		let entries = self.entries.lock().expect("qed");
+		match entries.get(key) {
+			Some((time, value)) if now.duration_since(*time) < self.ttl => Some(value.clone()),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::rpc::metrics::CacheCounters;
+	use crate::rpc::serde::Hex;
+	use crate::rpc::types::{BlockNumber, ResultBlock, ResultHeader};
+
+	use std::time::{Duration, Instant};
+
+	use super::{BlockCache, Head, HeadCache, TtlCache};
+
+	fn block(number: BlockNumber, hash: u8) -> ResultBlock {
+		ResultBlock {
+			header: ResultHeader {
+				block_hash: Some(Hex(vec![hash; 32])),
+				extrinsics_root: vec![0u8; 32],
+				parent_hash: vec![0u8; 32],
+				state_root: vec![0u8; 32],
+				number,
+			},
+			extrinsics: vec![],
+		}
+	}
+
+	#[test]
+	fn test_block_cache() {
+		let cache = BlockCache::new(16);
+
+		// finalized number unknown
+		cache.insert(0, block(5, 5));
+		assert!(cache.get(0, &[5u8; 32]).is_none());
+
+		cache.set_finalized_number(0, 10);
+
+		// finalized
+		cache.insert(0, block(5, 5));
+		assert_eq!(cache.get(0, &[5u8; 32]).map(|x| x.header.number), Some(5));
+
+		// cached by shard
+		assert!(cache.get(1, &[5u8; 32]).is_none());
+
+		// not finalized
+		cache.insert(0, block(11, 11));
+		assert!(cache.get(0, &[11u8; 32]).is_none());
+
+		// finalized number never goes back
+		cache.set_finalized_number(0, 3);
+		assert_eq!(cache.finalized_number(0), Some(10));
+	}
+
+	#[test]
+	fn test_should_refresh_finalized() {
+		let cache = BlockCache::new(16);
+		let now = Instant::now();
+
+		assert!(cache.should_refresh_finalized_at(0, now));
+		assert!(!cache.should_refresh_finalized_at(0, now + Duration::from_millis(500)));
+
+		// by shard
+		assert!(cache.should_refresh_finalized_at(1, now));
+
+		assert!(cache.should_refresh_finalized_at(0, now + Duration::from_millis(1000)));
+	}
+
+	#[test]
+	fn test_block_cache_counters() {
+		let cache = BlockCache::new(16);
+		cache.set_finalized_number(0, 10);
+		cache.insert(0, block(5, 5));
+
+		assert!(cache.get(0, &[6u8; 32]).is_none());
+		assert_eq!(cache.block_counters(), CacheCounters { hits: 0, misses: 1 });
+
+		// read and re-read
+		assert!(cache.get(0, &[5u8; 32]).is_some());
+		assert!(cache.get(0, &[5u8; 32]).is_some());
+		assert_eq!(cache.block_counters(), CacheCounters { hits: 2, misses: 1 });
+
+		assert!(cache.get_hash(0, 5).is_some());
+		assert_eq!(cache.hash_counters(), CacheCounters { hits: 1, misses: 0 });
+
+		// the finalized number lookups, not the checks of the inserts
+		assert!(cache.finalized_number(1).is_none());
+		assert_eq!(cache.finalized_number(0), Some(10));
+		assert_eq!(
+			cache.finalized_counters(),
+			CacheCounters { hits: 1, misses: 1 }
+		);
+	}
+
+	#[test]
+	fn test_block_cache_hash() {
+		let cache = BlockCache::new(16);
+		cache.set_finalized_number(0, 10);
+
+		// by the inserted blocks
+		cache.insert(0, block(5, 5));
+		assert_eq!(cache.get_hash(0, 5), Some(vec![5u8; 32]));
+
+		cache.insert_hash(0, 6, vec![6u8; 32]);
+		assert_eq!(cache.get_hash(0, 6), Some(vec![6u8; 32]));
+		assert!(cache.get_hash(1, 6).is_none());
+
+		// not finalized
+		cache.insert_hash(0, 11, vec![11u8; 32]);
+		assert!(cache.get_hash(0, 11).is_none());
+
+		cache.clear();
+		assert!(cache.get_hash(0, 6).is_none());
+	}
+
+	#[test]
+	fn test_head_cache_insert_best() {
+		let cache = HeadCache::new();
+
+		// not refreshed yet
+		assert_eq!(cache.get_fresh(0, 1000), None);
+
+		cache.insert(0, Head::new(Some(10), Some(8)));
+		cache.insert_best(0, 11);
+
+		// the finalized number is kept
+		let head = cache.get_fresh(0, 1000).unwrap();
+		assert_eq!((head.best, head.finalized), (Some(11), Some(8)));
+
+		// not fresh with no max age
+		assert_eq!(cache.get_fresh(0, 0), None);
+	}
+
+	#[test]
+	fn test_ttl_cache() {
+		let cache = TtlCache::new(Duration::from_secs(1));
+
+		cache.insert(0u16, "v1.0.0".to_string());
+
+		let now = Instant::now();
+		assert_eq!(cache.get_at(&0, now), Some("v1.0.0".to_string()));
+		assert_eq!(cache.get_at(&1, now), None);
+
+		// expired
+		let now = now + Duration::from_secs(2);
+		assert_eq!(cache.get_at(&0, now), None);
+	}
+}
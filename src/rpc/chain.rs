@@ -15,37 +15,69 @@
 // You should have received a copy of the GNU General Public License
 // along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use futures::future;
 use futures::future::{Future, Loop};
+use futures::stream::{self, Stream};
 use jsonrpc_core::BoxFuture;
 use jsonrpc_derive::rpc;
-use parity_codec::Decode;
-use serde_json::Value;
+use log::warn;
+use parity_codec::{Compact, Decode, Encode};
+use runtime_primitives::generic::DigestItem;
+use serde_json::{json, Value};
 use srml_system::{EventRecord, Phase};
 use substrate_primitives::blake2_256;
+use substrate_primitives::crypto::Ss58Codec;
+use substrate_primitives::sr25519;
+use yee_consensus_pow_primitives::YEE_POW_ENGINE_ID;
 use yee_primitives::Address;
 use yee_primitives::AddressCodec;
 use yee_primitives::Hrp;
-use yee_runtime::Event;
+use yee_runtime::{AuthorityId, Event, Hash};
 use yee_sharding_primitives::utils::shard_num_for_bytes;
 use yee_signer::tx::call::relay;
 use yee_signer::tx::types::Call;
 use yee_signer::tx::types::Transaction;
+use yee_srml_balances::BalanceLock;
 
 use crate::config::{HRP, SHARD_COUNT};
 use crate::config::Config;
+use crate::rpc::cache::{BlockCache, Caches, Head};
 use crate::rpc::client::{self, RpcClient};
 use crate::rpc::errors;
+use crate::rpc::projection;
 use crate::rpc::serde::Hex;
 use crate::rpc::types::{
-	Balance, BlockNumber, get_map_storage_key, get_value_storage_key, Nonce, ResultBlock,
-	ResultHeader, ResultTransaction,
+	get_map_storage_key, get_value_storage_key, Balance, BlockNumber, BlockResponse, Encoding,
+	Header, Nonce, NumberOrHex, ResultBlock, ResultHeader, ResultTransaction,
 };
 
+/// Count of the recent finalized blocks to derive the finalization rate
+const FINALIZATION_RATE_BLOCKS: u64 = 10;
+
+/// The known calls of the extrinsics: (module, method, module name, method name, param names)
+const CALLS: &[(u8, u8, &str, &str, &[&str])] = &[
+	(0, 0, "timestamp", "set", &["now"]),
+	(4, 0, "balances", "transfer", &["dest", "value"]),
+	(
+		9,
+		0,
+		"relay",
+		"transfer",
+		&["relay_type", "tx", "number", "hash", "parent"],
+	),
+];
+
+/// The public key of the example of chain_getShardLayout
+const EXAMPLE_PUBLIC_KEY: [u8; 32] = [
+	0x10, 0x33, 0xe0, 0x57, 0x68, 0x22, 0xa6, 0xa8, 0x36, 0xf6, 0x12, 0xa1, 0x93, 0x03, 0x60, 0x42,
+	0x05, 0x0e, 0x28, 0x6d, 0xa4, 0x56, 0x1f, 0x5c, 0xc5, 0xd8, 0xee, 0x56, 0x0c, 0x64, 0xdc, 0x54,
+];
+
 #[rpc]
 pub trait ChainApi {
 	#[rpc(name = "chain_getBestNumber")]
@@ -54,39 +86,212 @@ pub trait ChainApi {
 	#[rpc(name = "chain_getFinalizedNumber")]
 	fn get_finalized_number(&self, shard_num: u16) -> BoxFuture<Option<BlockNumber>>;
 
+	#[rpc(name = "chain_getBestNumberDefault")]
+	fn get_best_number_default(&self) -> BoxFuture<Option<BlockNumber>>;
+
+	#[rpc(name = "chain_getFinalizedNumberDefault")]
+	fn get_finalized_number_default(&self) -> BoxFuture<Option<BlockNumber>>;
+
+	#[rpc(name = "chain_getBlockHashBatch")]
+	fn get_block_hash_batch(&self, shard_num: u16, numbers: Vec<BlockNumber>) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getFinalizedHash")]
+	fn get_finalized_hash(&self, shard_num: u16) -> BoxFuture<Option<Hex<Vec<u8>>>>;
+
+	#[rpc(name = "chain_getFinalizedTimestamp")]
+	fn get_finalized_timestamp(&self, shard_num: u16) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getFinalizationEstimate")]
+	fn get_finalization_estimate(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getBlockTimeStats")]
+	fn get_block_time_stats(&self, shard_num: u16, sample: BlockNumber) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getFinalizedNumberAll")]
+	fn get_finalized_number_all(&self) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getFinalityLagAll")]
+	fn get_finality_lag_all(&self) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getBestHeaderAll")]
+	fn get_best_header_all(&self) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getShardSummary")]
+	fn get_shard_summary(&self, shard_num: u16) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getShardLiveness")]
+	fn get_shard_liveness(&self) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getOverview")]
+	fn get_overview(&self) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getPeerCount")]
+	fn get_peer_count(&self, shard_num: u16) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getNodeVersion")]
+	fn get_node_version(&self, shard_num: u16) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getProperties")]
+	fn get_properties(&self) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getShardLayout")]
+	fn get_shard_layout(&self) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getAddressFormats")]
+	fn get_address_formats(&self) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getPublicKey")]
+	fn get_public_key(&self, address: String) -> BoxFuture<Value>;
+
 	#[rpc(name = "chain_getHeaderByNumber")]
 	fn get_header_by_number(
 		&self,
 		shard_num: u16,
-		number: BlockNumber,
-	) -> BoxFuture<Option<ResultHeader>>;
+		number: NumberOrHex,
+		encoding: Option<Encoding>,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getHeadersByNumberRange")]
+	fn get_headers_by_number_range(
+		&self,
+		shard_num: u16,
+		from: NumberOrHex,
+		to: NumberOrHex,
+	) -> BoxFuture<Value>;
 
 	#[rpc(name = "chain_getHeaderByHash")]
 	fn get_header_by_hash(
 		&self,
 		shard_num: u16,
 		hash: Hex<Vec<u8>>,
-	) -> BoxFuture<Option<ResultHeader>>;
+		encoding: Option<Encoding>,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getParentHeader")]
+	fn get_parent_header(&self, shard_num: u16, hash: Hex<Vec<u8>>) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getHeaderDigest")]
+	fn get_header_digest(&self, shard_num: u16, hash: Hex<Vec<u8>>) -> BoxFuture<Value>;
 
 	#[rpc(name = "chain_getBlockByNumber")]
-	fn get_block_by_number(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Option<Value>>;
+	fn get_block_by_number(
+		&self,
+		shard_num: u16,
+		number: NumberOrHex,
+		with_success: Option<bool>,
+		with_raw: Option<bool>,
+		encoding: Option<Encoding>,
+		fields: Option<Vec<String>>,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getBlockByNumberDefault")]
+	fn get_block_by_number_default(&self, number: NumberOrHex) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getBlockByNumberSigned")]
+	fn get_block_by_number_signed(
+		&self,
+		shard_num: u16,
+		number: NumberOrHex,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getBlockWithEvents")]
+	fn get_block_with_events(
+		&self,
+		shard_num: u16,
+		number: BlockNumber,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getEventsRaw")]
+	fn get_events_raw(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getFinalizedBlockByNumber")]
+	fn get_finalized_block_by_number(
+		&self,
+		shard_num: u16,
+		number: BlockNumber,
+	) -> BoxFuture<Option<Value>>;
 
 	#[rpc(name = "chain_getBlockByHash")]
-	fn get_block_by_hash(&self, shard_num: u16, hash: Hex<Vec<u8>>) -> BoxFuture<Option<Value>>;
+	fn get_block_by_hash(
+		&self,
+		shard_num: u16,
+		hash: Hex<Vec<u8>>,
+		with_success: Option<bool>,
+		with_raw: Option<bool>,
+		encoding: Option<Encoding>,
+		fields: Option<Vec<String>>,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getBlockByNumberOrHash")]
+	fn get_block(&self, shard_num: u16, at: Value) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getBlockByHashList")]
+	fn get_block_by_hash_list(&self, shard_num: u16, hashes: Vec<Hex<Vec<u8>>>)
+		-> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getBlockAuthor")]
+	fn get_block_author(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_verifyExtrinsicsRoot")]
+	fn verify_extrinsics_root(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Value>;
 
 	#[rpc(name = "chain_getExtrinsicByHash")]
 	fn get_extrinsic_by_hash(
 		&self,
 		shard_num: u16,
+		block_number: NumberOrHex,
+		hash: Hex<Vec<u8>>,
+		with_raw: Option<bool>,
+		fields: Option<Vec<String>>,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getExtrinsicByHashInRange")]
+	fn get_extrinsic_by_hash_in_range(
+		&self,
+		shard_num: u16,
+		from: BlockNumber,
+		to: BlockNumber,
+		hash: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getExtrinsicByHashAllShards")]
+	fn get_extrinsic_by_hash_all_shards(
+		&self,
 		block_number: BlockNumber,
 		hash: Hex<Vec<u8>>,
 	) -> BoxFuture<Option<Value>>;
 
+	#[rpc(name = "chain_getExtrinsicsBySenderInBlock")]
+	fn get_extrinsics_by_sender_in_block(
+		&self,
+		shard_num: u16,
+		number: BlockNumber,
+		address: String,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getExtrinsicCountByAddress")]
+	fn get_extrinsic_count_by_address(
+		&self,
+		shard_num: u16,
+		address: String,
+		from: BlockNumber,
+		to: BlockNumber,
+	) -> BoxFuture<Value>;
+
 	#[rpc(name = "chain_getExtrinsicByRaw")]
 	fn get_extrinsic_by_raw(
 		&self,
 		shard_num: u16,
-		block_number: BlockNumber,
+		block_number: NumberOrHex,
+		raw: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getExtrinsicByRawInRange")]
+	fn get_extrinsic_by_raw_in_range(
+		&self,
+		shard_num: u16,
+		from: BlockNumber,
+		to: BlockNumber,
 		raw: Hex<Vec<u8>>,
 	) -> BoxFuture<Option<Value>>;
 
@@ -94,900 +299,6938 @@ pub trait ChainApi {
 	fn get_extrinsic_by_origin_hash(
 		&self,
 		shard_num: u16,
-		from_block_number: BlockNumber,
-		to_block_number: BlockNumber,
+		from_block_number: NumberOrHex,
+		to_block_number: NumberOrHex,
+		origin_hash: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>>;
+
+	#[rpc(name = "chain_getExtrinsicByOriginHashAllShards")]
+	fn get_extrinsic_by_origin_hash_all_shards(
+		&self,
+		from_block_number: NumberOrHex,
+		to_block_number: NumberOrHex,
 		origin_hash: Hex<Vec<u8>>,
 	) -> BoxFuture<Option<Value>>;
 
+	#[rpc(name = "chain_getRelayTransferStatus")]
+	fn get_relay_transfer_status(
+		&self,
+		origin_hash: Hex<Vec<u8>>,
+		from_block_number: NumberOrHex,
+		to_block_number: NumberOrHex,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getRelayOriginHash")]
+	fn get_relay_origin_hash(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_decodeHeader")]
+	fn decode_header(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_decodeEvents")]
+	fn decode_events(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_describeCall")]
+	fn describe_call(&self, module: u8, method: u8) -> BoxFuture<Value>;
+
 	#[rpc(name = "state_getNonce")]
-	fn get_nonce(&self, address: String, block_number: Option<BlockNumber>) -> BoxFuture<Nonce>;
+	fn get_nonce(&self, address: String, block_number: Option<NumberOrHex>) -> BoxFuture<Value>;
+
+	#[rpc(name = "state_getNonceWithPending")]
+	fn get_nonce_with_pending(&self, address: String) -> BoxFuture<Value>;
 
 	#[rpc(name = "state_getBalance")]
-	fn get_balance(&self, address: String, block_number: Option<BlockNumber>) -> BoxFuture<Value>;
+	fn get_balance(&self, address: String, block_number: Option<NumberOrHex>) -> BoxFuture<Value>;
+
+	#[rpc(name = "state_getAccountExists")]
+	fn get_account_exists(
+		&self,
+		address: String,
+		block_number: Option<NumberOrHex>,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "state_getBalanceLocks")]
+	fn get_balance_locks(
+		&self,
+		address: String,
+		block_number: Option<NumberOrHex>,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getFullAccount")]
+	fn get_full_account(
+		&self,
+		address: String,
+		block_number: Option<NumberOrHex>,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getAccountInfoBatch")]
+	fn get_account_info_batch(
+		&self,
+		addresses: Vec<String>,
+		block_number: Option<NumberOrHex>,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "chain_getAddressBalanceHistory")]
+	fn get_address_balance_history(
+		&self,
+		address: String,
+		from_block: NumberOrHex,
+		to_block: NumberOrHex,
+		step: NumberOrHex,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "state_getStorageBatch")]
+	fn get_storage_batch(
+		&self,
+		shard_num: u16,
+		keys: Vec<Hex<Vec<u8>>>,
+		block_number: Option<BlockNumber>,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "state_getStorageChanges")]
+	fn get_storage_changes(
+		&self,
+		shard_num: u16,
+		key: Hex<Vec<u8>>,
+		from: BlockNumber,
+		to: BlockNumber,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "state_getMapStorageKey")]
+	fn get_map_storage_key(
+		&self,
+		module_prefix: String,
+		public_key: Hex<Vec<u8>>,
+	) -> BoxFuture<Value>;
+
+	#[rpc(name = "state_getValueStorageKey")]
+	fn get_value_storage_key(&self, prefix: String) -> BoxFuture<Value>;
 
 	#[rpc(name = "author_submitExtrinsic")]
 	fn submit_extrinsic(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Hex<Vec<u8>>>;
+
+	#[rpc(name = "author_dryRunExtrinsic")]
+	fn dry_run_extrinsic(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value>;
+
+	#[rpc(name = "author_getExtrinsicShard")]
+	fn get_extrinsic_shard(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value>;
 }
 
 pub struct Chain {
-	config: Config,
+	/// Swapped on reload
+	config: Arc<RwLock<Config>>,
 	rpc_client: Arc<RpcClient>,
+	caches: Caches,
 }
 
 impl Chain {
 	/// Create new State API RPC handler.
-	pub fn new(config: Config) -> Self {
+	pub fn new(config: Arc<RwLock<Config>>, rpc_client: Arc<RpcClient>, caches: Caches) -> Self {
 		Self {
-			config: config.clone(),
-			rpc_client: Arc::new(RpcClient::new(config)),
+			config,
+			rpc_client,
+			caches,
 		}
 	}
+
+	fn config(&self) -> RwLockReadGuard<Config> {
+		self.config.read().expect("qed")
+	}
+
+	/// The block number must be within `max_block_number_margin` above the cached best number,
+	/// not checked if the best number is not cached
+	fn check_block_number(&self, shard_num: u16, number: BlockNumber) -> errors::Result<()> {
+		let margin = match self.config().max_block_number_margin {
+			Some(margin) => margin,
+			None => return Ok(()),
+		};
+		let best = self.caches.head.get(shard_num).and_then(|x| x.best);
+		check_block_number_margin(number, best, margin)
+	}
+
+	/// The block hash of the state to read by `get_state_block_hash_future`,
+	/// the block number must pass `check_block_number`
+	fn get_state_block_hash(
+		&self,
+		shard_num: u16,
+		block_number: Option<BlockNumber>,
+	) -> BoxFuture<Option<Hex<Vec<u8>>>> {
+		if let Some(number) = block_number {
+			match self.check_block_number(shard_num, number) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+		}
+
+		get_state_block_hash_future(
+			self.rpc_client.clone(),
+			block_number,
+			shard_num,
+			self.config().finalized_state,
+		)
+	}
 }
 
 impl ChainApi for Chain {
 	fn get_best_number(&self, shard_num: u16) -> BoxFuture<Option<BlockNumber>> {
-		match check_shard_num(shard_num, &self.config) {
+		match check_shard_num(shard_num, &self.config()) {
 			Err(e) => return Box::new(future::err(e.into())),
 			_ => (),
 		}
 
+		let ttl_ms = self.config().best_number_ttl_ms;
+		if ttl_ms > 0 {
+			if let Some(number) = self
+				.caches
+				.head
+				.get_fresh(shard_num, ttl_ms)
+				.and_then(|x| x.best)
+			{
+				return Box::new(future::ok(Some(number)));
+			}
+		}
+
 		let result = client::get_header_future(self.rpc_client.clone(), &None, shard_num);
 
-		let result = result.map(|x| x.map(|x| x.number));
+		let head_cache = self.caches.head.clone();
+		let result = result.map(move |x| {
+			let number = x.map(|x| x.number);
+			if let Some(number) = number {
+				head_cache.insert_best(shard_num, number);
+			}
+			number
+		});
 
 		Box::new(result)
 	}
 
 	fn get_finalized_number(&self, shard_num: u16) -> BoxFuture<Option<BlockNumber>> {
-		match check_shard_num(shard_num, &self.config) {
+		match check_shard_num(shard_num, &self.config()) {
 			Err(e) => return Box::new(future::err(e.into())),
 			_ => (),
 		}
 
-		let result = client::get_finalized_hash_future(self.rpc_client.clone(), shard_num);
+		let result = get_finalized_number_future(self.rpc_client.clone(), shard_num);
 
-		let rpc_client = self.rpc_client.clone();
-		let result = result.and_then(move |hash| {
-			let header = client::get_header_future(rpc_client, &hash, shard_num);
-			header
+		let block_cache = self.caches.block.clone();
+		let result = result.map(move |x| {
+			if let Some(number) = x {
+				block_cache.set_finalized_number(shard_num, number);
+			}
+			x
 		});
 
-		let result = result.map(|x| x.map(|x| x.number));
-
 		Box::new(result)
 	}
 
-	fn get_header_by_number(
-		&self,
-		shard_num: u16,
-		number: BlockNumber,
-	) -> BoxFuture<Option<ResultHeader>> {
-		match check_shard_num(shard_num, &self.config) {
+	fn get_best_number_default(&self) -> BoxFuture<Option<BlockNumber>> {
+		let shard_num = get_default_shard(&self.config());
+		match shard_num {
+			Ok(shard_num) => self.get_best_number(shard_num),
+			Err(e) => Box::new(future::err(e.into())),
+		}
+	}
+
+	fn get_finalized_number_default(&self) -> BoxFuture<Option<BlockNumber>> {
+		let shard_num = get_default_shard(&self.config());
+		match shard_num {
+			Ok(shard_num) => self.get_finalized_number(shard_num),
+			Err(e) => Box::new(future::err(e.into())),
+		}
+	}
+
+	fn get_block_hash_batch(&self, shard_num: u16, numbers: Vec<BlockNumber>) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
 			Err(e) => return Box::new(future::err(e.into())),
 			_ => (),
 		}
 
-		let result = client::get_block_hash_future(self.rpc_client.clone(), number, shard_num);
+		match check_block_numbers(numbers.len(), self.config().max_block_hash_batch) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-		let rpc_client = self.rpc_client.clone();
-		let result = result.and_then(move |hash| {
-			let header = client::get_header_future(rpc_client, &hash, shard_num);
-			header.map(|header| match (header, hash) {
-				(Some(header), Some(hash)) => {
-					let mut header: ResultHeader = header.into();
-					header.block_hash = Some(hash);
-					Some(header)
-				}
-				_ => None,
+		if let Some(number) = numbers.iter().max() {
+			match self.check_block_number(shard_num, *number) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+		}
+
+		let futures = numbers
+			.into_iter()
+			.map(|number| {
+				let block_cache = self.caches.block.clone();
+				let result: BoxFuture<Option<Hex<Vec<u8>>>> =
+					match block_cache.get_hash(shard_num, number) {
+						Some(hash) => Box::new(future::ok(Some(Hex(hash)))),
+						None => {
+							let result = client::get_block_hash_future(
+								self.rpc_client.clone(),
+								number,
+								shard_num,
+							);
+							let result = result.map(move |x| {
+								if let Some(hash) = &x {
+									block_cache.insert_hash(shard_num, number, hash.0.clone());
+								}
+								x
+							});
+							Box::new(result)
+						}
+					};
+				result.map(move |x| (number, x))
 			})
+			.collect::<Vec<_>>();
+
+		let result = join_limited(futures, self.config().max_range_concurrency);
+
+		// the numbers not yet produced are null
+		let result = result.map(|x| {
+			let result = x
+				.into_iter()
+				.map(|(number, hash)| (number.to_string(), json!(hash)))
+				.collect::<serde_json::Map<_, _>>();
+			Value::Object(result)
 		});
 
 		Box::new(result)
 	}
 
-	fn get_header_by_hash(
-		&self,
-		shard_num: u16,
-		hash: Hex<Vec<u8>>,
-	) -> BoxFuture<Option<ResultHeader>> {
-		match check_shard_num(shard_num, &self.config) {
+	fn get_finalized_hash(&self, shard_num: u16) -> BoxFuture<Option<Hex<Vec<u8>>>> {
+		match check_shard_num(shard_num, &self.config()) {
 			Err(e) => return Box::new(future::err(e.into())),
 			_ => (),
 		}
 
-		let result = future::ok(Some(hash));
-
-		let rpc_client = self.rpc_client.clone();
-		let result = result.and_then(move |hash| {
-			let header = client::get_header_future(rpc_client, &hash, shard_num);
-			header.map(|header| match (header, hash) {
-				(Some(header), Some(hash)) => {
-					let mut header: ResultHeader = header.into();
-					header.block_hash = Some(hash);
-					Some(header)
-				}
-				_ => None,
-			})
-		});
-
-		Box::new(result)
+		client::get_finalized_hash_future(self.rpc_client.clone(), shard_num)
 	}
 
-	fn get_block_by_number(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Option<Value>> {
-		match check_shard_num(shard_num, &self.config) {
+	fn get_finalized_timestamp(&self, shard_num: u16) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
 			Err(e) => return Box::new(future::err(e.into())),
 			_ => (),
 		}
 
-		// get block hash
+		// get the finalized block
+		let rpc_client = self.rpc_client.clone();
 		let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
-			let result = client::get_block_hash_future(self.rpc_client.clone(), number, shard_num);
+			let result = client::get_finalized_hash_future(rpc_client.clone(), shard_num);
 			let result = result.map(|x| Ok(x));
 			Box::new(result)
 		};
 		let result = get_block_hash();
 
-		let result = get_block_future(self.rpc_client.clone(), shard_num, false, result);
+		let result = get_block_future(
+			self.rpc_client.clone(),
+			self.caches.block.clone(),
+			shard_num,
+			false,
+			false,
+			result,
+		);
 
 		let result = get_option_value_future(result);
 
-		let result = get_value_with_address_future(result);
-
 		let result = result.and_then(|x| match x {
 			Ok(v) => future::ok(v),
 			Err(e) => future::err(e),
 		});
 
+		let result = result.map(|block| {
+			block
+				.and_then(|block| {
+					let timestamp = get_block_timestamp(&block)?;
+					Some(json!({
+						"number": block["header"]["number"],
+						"timestamp": timestamp,
+					}))
+				})
+				.unwrap_or(Value::Null)
+		});
+
 		Box::new(result)
 	}
 
-	fn get_block_by_hash(&self, shard_num: u16, hash: Hex<Vec<u8>>) -> BoxFuture<Option<Value>> {
-		match check_shard_num(shard_num, &self.config) {
+	fn get_finalization_estimate(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
 			Err(e) => return Box::new(future::err(e.into())),
 			_ => (),
 		}
 
-		let result = Box::new(future::ok(Ok(Some(hash))));
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-		let result = get_block_future(self.rpc_client.clone(), shard_num, false, result);
+		let rpc_client = self.rpc_client.clone();
+		let block_cache = self.caches.block.clone();
+		let result = get_finalized_number_future(rpc_client.clone(), shard_num);
 
-		let result = get_option_value_future(result);
+		let result = result.and_then(move |finalized| -> BoxFuture<Value> {
+			let finalized = match finalized {
+				Some(finalized) => finalized,
+				None => return Box::new(future::ok(Value::Null)),
+			};
+			let value = move |seconds: Option<u64>| {
+				json!({
+					"number": number,
+					"finalized_number": finalized,
+					"seconds": seconds,
+				})
+			};
+			if number <= finalized {
+				return Box::new(future::ok(value(Some(0))));
+			}
 
-		let result = get_value_with_address_future(result);
+			// the rate of the recent finalized blocks
+			let from = finalized.saturating_sub(FINALIZATION_RATE_BLOCKS);
+			let get_timestamp = |number: BlockNumber| {
+				let result = get_block_by_number_future(
+					rpc_client.clone(),
+					block_cache.clone(),
+					shard_num,
+					number,
+					false,
+					false,
+				);
+				result.map(move |x| x.and_then(|x| get_block_timestamp(&x)).map(|x| (number, x)))
+			};
 
-		let result = result.and_then(|x| match x {
-			Ok(v) => future::ok(v),
-			Err(e) => future::err(e),
+			let result = get_timestamp(from).join(get_timestamp(finalized));
+
+			let result = result.map(move |x| match x {
+				(Some(from), Some(to)) => {
+					value(estimate_finalization_seconds(number, finalized, from, to))
+				}
+				_ => value(None),
+			});
+
+			Box::new(result)
 		});
 
 		Box::new(result)
 	}
 
-	fn get_extrinsic_by_hash(
-		&self,
-		shard_num: u16,
-		block_number: BlockNumber,
-		hash: Hex<Vec<u8>>,
-	) -> BoxFuture<Option<Value>> {
-		match check_shard_num(shard_num, &self.config) {
+	fn get_block_time_stats(&self, shard_num: u16, sample: BlockNumber) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
 			Err(e) => return Box::new(future::err(e.into())),
 			_ => (),
 		}
 
-		// get block hash
-		let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
-			let result =
-				client::get_block_hash_future(self.rpc_client.clone(), block_number, shard_num);
-			let result = result.map(|x| Ok(x));
-			Box::new(result)
-		};
-		let result = get_block_hash();
+		match check_samples(sample, self.config().max_block_time_samples) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-		let result = get_block_future(self.rpc_client.clone(), shard_num, false, result);
+		let rpc_client = self.rpc_client.clone();
+		let block_cache = self.caches.block.clone();
+		let max_range_concurrency = self.config().max_range_concurrency;
+		let result = self.get_best_number(shard_num);
 
-		// filter
-		let filter = move || -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
-			let result = result.map(move |x| match x {
-				Ok(Some(block)) => {
-					let extrinsic = block
-						.extrinsics
-						.into_iter()
-						.filter_map(|tx| {
-							if tx.hash.as_ref() == Some(&hash) {
-								Some(tx)
-							} else {
-								None
-							}
-						})
-						.next();
-					Ok(extrinsic)
-				}
-				Ok(None) => Ok(None),
-				Err(e) => Err(e),
-			});
-			Box::new(result)
-		};
-		let result = filter();
+		let result = result.and_then(move |best| -> BoxFuture<Value> {
+			let best = match best {
+				Some(best) => best,
+				None => return Box::new(future::ok(Value::Null)),
+			};
 
-		let result = get_option_value_future(result);
+			// the last `sample` blocks up to the best
+			let from = (best + 1).saturating_sub(sample);
+			let futures = (from..=best)
+				.map(|number| {
+					let result = get_block_by_number_future(
+						rpc_client.clone(),
+						block_cache.clone(),
+						shard_num,
+						number,
+						false,
+						false,
+					);
+					result
+						.map(move |x| x.and_then(|x| get_block_timestamp(&x)).map(|x| (number, x)))
+				})
+				.collect::<Vec<_>>();
 
-		let result = get_value_with_address_future(result);
+			let result = join_limited(futures, max_range_concurrency);
 
-		let result = result.and_then(|x| match x {
-			Ok(v) => future::ok(v),
-			Err(e) => future::err(e),
+			let result = result.map(move |x| {
+				let timestamps = x.into_iter().filter_map(|x| x).collect::<Vec<_>>();
+				let mut stats = get_block_time_stats(&timestamps);
+				stats["from"] = json!(from);
+				stats["to"] = json!(best);
+				stats
+			});
+
+			Box::new(result)
 		});
 
 		Box::new(result)
 	}
 
-	fn get_extrinsic_by_raw(
-		&self,
-		shard_num: u16,
-		block_number: BlockNumber,
-		raw: Hex<Vec<u8>>,
-	) -> BoxFuture<Option<Value>> {
-		match check_shard_num(shard_num, &self.config) {
-			Err(e) => return Box::new(future::err(e.into())),
-			_ => (),
-		}
+	fn get_finalized_number_all(&self) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
 
-		// get block hash
-		let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
-			let result =
-				client::get_block_hash_future(self.rpc_client.clone(), block_number, shard_num);
-			let result = result.map(|x| Ok(x));
-			Box::new(result)
-		};
-		let result = get_block_hash();
+		let futures = (0..shard_count)
+			.map(|shard_num| {
+				// served from the prefetch if enabled
+				let head = match self.caches.head.get(shard_num) {
+					Some(head) => Box::new(future::ok(head)) as BoxFuture<Head>,
+					None => get_head_future(self.rpc_client.clone(), shard_num),
+				};
+				head.map(move |head| {
+					json!({
+						"shard_num": shard_num,
+						"best": head.best,
+						"finalized": head.finalized,
+						"updated_at": head.updated_at,
+					})
+				})
+			})
+			.collect::<Vec<_>>();
 
-		let result = get_block_future(self.rpc_client.clone(), shard_num, true, result);
+		let result = future::join_all(futures).map(Value::Array);
 
-		// filter
-		let filter = move || -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
-			let result = result.map(move |x| match x {
-				Ok(Some(block)) => {
-					let extrinsic = block
-						.extrinsics
-						.into_iter()
-						.filter_map(|tx| {
-							if tx.raw.as_ref() == Some(&raw) {
-								Some(tx)
-							} else {
-								None
-							}
-						})
-						.next();
-					let extrinsic = extrinsic.map(|mut x| {
-						x.raw = None;
-						x
-					});
-					Ok(extrinsic)
-				}
-				Ok(None) => Ok(None),
-				Err(e) => Err(e),
-			});
-			Box::new(result)
-		};
-		let result = filter();
+		Box::new(result)
+	}
 
-		let result = get_option_value_future(result);
+	fn get_finality_lag_all(&self) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
 
-		let result = get_value_with_address_future(result);
+		let futures = (0..shard_count)
+			.map(|shard_num| {
+				// served from the prefetch if enabled
+				let head = match self.caches.head.get(shard_num) {
+					Some(head) => Box::new(future::ok(head)) as BoxFuture<Head>,
+					None => get_head_future(self.rpc_client.clone(), shard_num),
+				};
+				head.map(move |head| (shard_num, head))
+			})
+			.collect::<Vec<_>>();
 
-		let result = result.and_then(|x| match x {
-			Ok(v) => future::ok(v),
-			Err(e) => future::err(e),
+		let result = future::join_all(futures).map(|heads| {
+			// the lag is null if either number is unknown
+			let shards = heads
+				.into_iter()
+				.map(|(shard_num, head)| {
+					let lag = match (head.best, head.finalized) {
+						(Some(best), Some(finalized)) => Some(best.saturating_sub(finalized)),
+						_ => None,
+					};
+					(shard_num, head, lag)
+				})
+				.collect::<Vec<_>>();
+			let max_lag = shards.iter().filter_map(|(_, _, lag)| *lag).max();
+			let shards = shards
+				.into_iter()
+				.map(|(shard_num, head, lag)| {
+					json!({
+						"shard_num": shard_num,
+						"best": head.best,
+						"finalized": head.finalized,
+						"lag": lag,
+					})
+				})
+				.collect::<Vec<_>>();
+			json!({
+				"shards": shards,
+				"max_lag": max_lag,
+			})
 		});
 
 		Box::new(result)
 	}
 
-	fn get_extrinsic_by_origin_hash(
-		&self,
-		shard_num: u16,
-		from_block_number: BlockNumber,
-		to_block_number: BlockNumber,
-		origin_hash: Hex<Vec<u8>>,
-	) -> BoxFuture<Option<Value>> {
-		match check_shard_num(shard_num, &self.config) {
-			Err(e) => return Box::new(future::err(e.into())),
-			_ => (),
-		}
+	fn get_best_header_all(&self) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
 
-		let rpc_client = self.rpc_client.clone();
-		let result = future::loop_fn(from_block_number, move |block_number| {
-			get_extrinsic_by_origin_hash_future(
-				rpc_client.clone(),
-				shard_num,
-				block_number,
-				origin_hash.clone(),
-			)
-			.and_then(move |x| {
-				x.map(|x| match x {
-					Some(tx) => {
-						let tx: jsonrpc_core::Result<Option<ResultTransaction>> = Ok(Some(tx));
-						Loop::Break(tx)
-					}
-					None => {
-						if block_number >= to_block_number {
-							Loop::Break(Ok(None))
-						} else {
-							Loop::Continue(block_number + 1)
-						}
-					}
+		let futures = (0..shard_count)
+			.map(|shard_num| {
+				let rpc_client = self.rpc_client.clone();
+				let result = client::get_best_hash_future(rpc_client.clone(), shard_num);
+				let result = result
+					.and_then(move |hash| get_result_header_future(rpc_client, shard_num, hash));
+
+				// an unreachable shard degrades to null
+				result.then(move |x| -> jsonrpc_core::Result<(String, Value)> {
+					let header = x.ok().and_then(|x| x).map(|x| json!(x));
+					Ok((shard_num.to_string(), header.unwrap_or(Value::Null)))
 				})
 			})
-		});
-		let result = Box::new(result) as BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>>;
+			.collect::<Vec<_>>();
 
-		let result = get_option_value_future(result);
+		let result = future::join_all(futures).map(|x| Value::Object(x.into_iter().collect()));
 
-		let result = get_value_with_tx_decoded_future(result);
+		Box::new(result)
+	}
 
-		let result = result.and_then(|x| match x {
-			Ok(v) => future::ok(v),
-			Err(e) => future::err(e),
+	fn get_shard_summary(&self, shard_num: u16) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		// a failed fetch degrades to null instead of failing the whole summary
+		let best = self
+			.get_best_number(shard_num)
+			.then(|x| -> jsonrpc_core::Result<Option<BlockNumber>> { Ok(x.ok().and_then(|x| x)) });
+		let finalized = self
+			.get_finalized_number(shard_num)
+			.then(|x| -> jsonrpc_core::Result<Option<BlockNumber>> { Ok(x.ok().and_then(|x| x)) });
+
+		let result = best.join(finalized).map(|(best, finalized)| {
+			let lag = match (best, finalized) {
+				(Some(best), Some(finalized)) => Some(best.saturating_sub(finalized)),
+				_ => None,
+			};
+			json!({
+				"best": best,
+				"finalized": finalized,
+				"lag": lag,
+			})
 		});
 
 		Box::new(result)
 	}
 
-	fn get_nonce(&self, address: String, block_number: Option<BlockNumber>) -> BoxFuture<Nonce> {
-		let address = Address(address);
+	fn get_shard_liveness(&self) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
+		let max_block_age = self.config().max_block_age;
 
-		let (public_key, _hrp) = match <[u8; 32]>::from_address(&address)
-			.map_err(|_| errors::Error::from(errors::ErrorKind::InvalidAddress).into())
-		{
-			Ok(v) => v,
-			Err(e) => return Box::new(future::err(e)),
-		};
+		let futures = (0..shard_count)
+			.map(|shard_num| {
+				let result = get_best_block_future(
+					self.rpc_client.clone(),
+					self.caches.block.clone(),
+					shard_num,
+				);
 
-		let shard_count = self.config.shards.len() as u16;
+				// a failed fetch is unhealthy
+				result.map(move |block| {
+					let number = block.as_ref().and_then(|x| x["header"]["number"].as_u64());
+					let timestamp = block.as_ref().and_then(get_block_timestamp);
+					get_liveness(shard_num, number, timestamp, now(), max_block_age)
+				})
+			})
+			.collect::<Vec<_>>();
 
-		let shard_num = match shard_num_for_bytes(&public_key, shard_count) {
-			Some(shard_num) => shard_num,
-			None => {
-				return Box::new(future::err(
-					errors::Error::from(errors::ErrorKind::InvalidShard).into(),
-				));
-			}
-		};
-		let storage_key = get_map_storage_key(&public_key, b"System AccountNonce");
-		let storage_key = Hex(storage_key.0);
+		let result = future::join_all(futures).map(Value::Array);
 
-		// get block hash
-		let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
-			match block_number {
-				Some(block_number) => {
-					let result = client::get_block_hash_future(
-						self.rpc_client.clone(),
-						block_number,
-						shard_num,
-					);
-					let result = result.map(|x| Ok(x));
-					Box::new(result)
-				}
-				None => Box::new(future::ok(Ok(None))),
-			}
-		};
-		let result = get_block_hash();
+		Box::new(result)
+	}
 
-		// get nonce
-		let rpc_client = self.rpc_client.clone();
-		let get_nonce = move || -> BoxFuture<jsonrpc_core::Result<Nonce>> {
-			let result = result.and_then(move |x| match x {
-				Ok(block_hash) => {
-					let result = client::get_storage_future(
-						rpc_client,
-						&storage_key,
-						&block_hash,
-						shard_num,
-					);
-					let result = result.map(|x| -> jsonrpc_core::Result<Nonce> {
-						match x {
-							Some(x) => Ok(u64_from_slice(&x.0)?),
-							None => Ok(0),
-						}
-					});
-					Box::new(result) as BoxFuture<jsonrpc_core::Result<Nonce>>
-				}
-				Err(e) => Box::new(future::err(e)),
-			});
-			Box::new(result)
-		};
-		let result = get_nonce();
+	fn get_overview(&self) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
 
-		let result = result.and_then(|x| match x {
-			Ok(v) => future::ok(v),
-			Err(e) => future::err(e),
-		});
+		// each field degrades to null on its own
+		let futures = (0..shard_count)
+			.map(|shard_num| {
+				let head = match self.caches.head.get(shard_num) {
+					Some(head) => Box::new(future::ok(head)) as BoxFuture<Head>,
+					None => get_head_future(self.rpc_client.clone(), shard_num),
+				};
+				let peer_count = client::get_peers_future(self.rpc_client.clone(), shard_num).then(
+					|x| -> jsonrpc_core::Result<Option<usize>> { Ok(x.ok().map(|x| x.len())) },
+				);
+				let block = get_best_block_future(
+					self.rpc_client.clone(),
+					self.caches.block.clone(),
+					shard_num,
+				);
+
+				head.join3(peer_count, block)
+					.map(move |(head, peer_count, block)| {
+						let block_age = block
+							.as_ref()
+							.and_then(get_block_timestamp)
+							.map(|x| now().saturating_sub(x));
+						json!({
+							"shard_num": shard_num,
+							"best": head.best,
+							"finalized": head.finalized,
+							"peer_count": peer_count,
+							"block_age": block_age,
+						})
+					})
+			})
+			.collect::<Vec<_>>();
+
+		let result = future::join_all(futures).map(Value::Array);
 
 		Box::new(result)
 	}
 
-	fn get_balance(&self, address: String, block_number: Option<BlockNumber>) -> BoxFuture<Value> {
-		let address = Address(address);
+	fn get_peer_count(&self, shard_num: u16) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-		let (public_key, _hrp) = match <[u8; 32]>::from_address(&address)
-			.map_err(|_| errors::Error::from(errors::ErrorKind::InvalidAddress).into())
-		{
-			Ok(v) => v,
-			Err(e) => return Box::new(future::err(e)),
-		};
+		let result = client::get_peers_future(self.rpc_client.clone(), shard_num);
 
-		let shard_count = self.config.shards.len() as u16;
+		let result = result.map(|x| Value::Number(x.len().into()));
 
-		let shard_num = match shard_num_for_bytes(&public_key, shard_count) {
-			Some(shard_num) => shard_num,
-			None => {
-				return Box::new(future::err(
-					errors::Error::from(errors::ErrorKind::InvalidShard).into(),
-				));
-			}
-		};
-		let storage_key = get_map_storage_key(&public_key, b"Balances FreeBalance");
-		let storage_key = Hex(storage_key.0);
+		Box::new(result)
+	}
 
-		// get block hash
-		let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
-			match block_number {
-				Some(block_number) => {
-					let result = client::get_block_hash_future(
-						self.rpc_client.clone(),
-						block_number,
-						shard_num,
-					);
-					let result = result.map(|x| Ok(x));
-					Box::new(result)
-				}
-				None => Box::new(future::ok(Ok(None))),
-			}
-		};
-		let result = get_block_hash();
+	fn get_node_version(&self, shard_num: u16) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-		// get balance
-		let rpc_client = self.rpc_client.clone();
-		let get_balance = move || -> BoxFuture<jsonrpc_core::Result<Balance>> {
-			let result = result.and_then(move |x| match x {
-				Ok(block_hash) => {
-					let result = client::get_storage_future(
-						rpc_client,
-						&storage_key,
-						&block_hash,
-						shard_num,
-					);
-					let result = result.map(|x| -> jsonrpc_core::Result<Balance> {
-						match x {
-							Some(x) => Ok(Balance(u128_from_slice(&x.0)?)),
-							None => Ok(Balance(0)),
-						}
-					});
-					Box::new(result) as BoxFuture<jsonrpc_core::Result<Balance>>
-				}
-				Err(e) => Box::new(future::err(e)),
-			});
-			Box::new(result)
-		};
-		let result = get_balance();
+		if let Some(node_version) = self.caches.node_version.get(&shard_num) {
+			return Box::new(future::ok(node_version));
+		}
 
-		let result = get_value_future(result);
+		let result = client::get_node_version_future(self.rpc_client.clone(), shard_num);
 
-		let result = result.and_then(|x| match x {
-			Ok(v) => future::ok(v),
-			Err(e) => future::err(e),
+		let node_version_cache = self.caches.node_version.clone();
+		let result = result.map(move |(name, version)| {
+			let node_version = json!({
+				"name": name,
+				"version": version,
+			});
+			node_version_cache.insert(shard_num, node_version.clone());
+			node_version
 		});
 
 		Box::new(result)
 	}
 
-	fn submit_extrinsic(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Hex<Vec<u8>>> {
-		let tx: Transaction = match Decode::decode(&mut &raw.0[..]) {
-			Some(v) => v,
-			None => {
-				return Box::new(future::err(
-					errors::Error::from(errors::ErrorKind::InvalidExtrinsic).into(),
-				));
-			}
-		};
+	fn get_properties(&self) -> BoxFuture<Value> {
+		// reported by the nodes and checked against the config on startup
+		let shard_count = SHARD_COUNT.read().map(|x| *x).ok();
 
-		let get_public = || -> errors::Result<[u8; 32]> {
-			match tx.signature {
-				Some((address, _, _, _)) => {
-					let mut public = [0u8; 32];
-					(&mut public[..]).copy_from_slice(&address.0[1..]);
-					Ok(public)
-				}
-				None => Err(errors::ErrorKind::InvalidExtrinsic.into()),
-			}
-		};
+		let result = json!({
+			"shard_count": shard_count,
+			"configured_shard_count": self.config().shards.len(),
+		});
 
-		let public = match get_public() {
-			Ok(v) => v,
-			Err(e) => return Box::new(future::err(e.into())),
-		};
+		Box::new(future::ok(result))
+	}
 
-		let shard_count = self.config.shards.len() as u16;
+	fn get_shard_layout(&self) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
+		let hrp = HRP.read().expect("qed").clone();
 
-		let shard_num = match shard_num_for_bytes(&public, shard_count) {
-			Some(shard_num) => shard_num,
-			None => {
-				return Box::new(future::err(
-					errors::Error::from(errors::ErrorKind::InvalidShard).into(),
-				));
-			}
-		};
+		Box::new(future::ok(get_shard_layout(shard_count, hrp)))
+	}
 
-		let result = client::submit_extrinsic_future(self.rpc_client.clone(), &raw, shard_num);
+	fn get_address_formats(&self) -> BoxFuture<Value> {
+		let hrp = HRP.read().expect("qed").clone();
 
-		result
+		let result = json!({
+			"hrp": get_hrp_str(hrp),
+			"ss58_prefix": self.config().ss58_prefix,
+		});
+
+		Box::new(future::ok(result))
 	}
-}
 
-fn check_shard_num(shard_num: u16, config: &Config) -> errors::Result<()> {
-	if shard_num > config.shards.len() as u16 {
-		return Err(errors::ErrorKind::InvalidShard.into());
+	fn get_public_key(&self, address: String) -> BoxFuture<Value> {
+		let result = decode_address(&address)
+			.map(|public_key| json!(Hex(public_key.to_vec())))
+			.map_err(|e| e.into());
+
+		Box::new(future::result(result))
 	}
-	Ok(())
-}
 
-fn get_block_extrinsics_result(
-	events: Option<Hex<Vec<u8>>>,
-) -> errors::Result<HashMap<u32, (bool, Vec<String>)>> {
-	let mut result = HashMap::new();
+	fn get_header_by_number(
+		&self,
+		shard_num: u16,
+		number: NumberOrHex,
+		encoding: Option<Encoding>,
+	) -> BoxFuture<Option<Value>> {
+		let number = number.number();
+		let encoding = encoding.unwrap_or_default();
 
-	let events = match events {
-		Some(events) => events,
-		None => return Ok(result),
-	};
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-	let events: Vec<EventRecord<Event>> =
-		Decode::decode(&mut &events.0[..]).ok_or(errors::ErrorKind::ParseError)?;
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-	for event in events.into_iter() {
-		match event.phase {
-			Phase::ApplyExtrinsic(index) => match &event.event {
-				Event::system(system_event) => {
-					let success = match system_event {
-						srml_system::Event::ExtrinsicSuccess => true,
-						srml_system::Event::ExtrinsicFailed => false,
-					};
-					match result.entry(index) {
-						Entry::Vacant(entry) => {
-							entry.insert((success, vec![]));
-						}
-						Entry::Occupied(mut entry) => {
-							let entry = entry.get_mut();
-							entry.0 = success;
-						}
-					}
-				}
-				_ => {
-					let event_str = format!("{:?}", event.event);
-					match result.entry(index) {
-						Entry::Vacant(entry) => {
-							entry.insert((false, vec![event_str]));
-						}
-						Entry::Occupied(mut entry) => {
-							let entry = entry.get_mut();
-							entry.1.push(event_str);
-						}
-					}
-				}
-			},
-			_ => {}
+		if encoding == Encoding::Scale {
+			let rpc_client = self.rpc_client.clone();
+			let result = get_produced_block_hash_future(rpc_client.clone(), shard_num, number);
+			let result =
+				result.and_then(move |hash| get_scale_header_future(rpc_client, shard_num, hash));
+			return Box::new(result);
 		}
-	}
 
-	Ok(result)
-}
+		let rpc_client = self.rpc_client.clone();
+		let result = get_produced_block_hash_future(rpc_client.clone(), shard_num, number);
 
-fn get_block_future(
-	rpc_client: Arc<RpcClient>,
-	shard_num: u16,
-	with_raw: bool,
-	hash_future: BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>>,
-) -> BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>> {
-	// get block
-	let tmp_rpc_client = rpc_client.clone();
-	let get_block = move || -> BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>> {
-		let result = hash_future.and_then(move |x| match x {
-			Ok(Some(hash)) => {
-				let result =
-					client::get_block_future(tmp_rpc_client, &Some(hash.clone()), shard_num);
-				let result = result.map(|x| -> jsonrpc_core::Result<Option<ResultBlock>> {
-					let block = match x {
-						Some(block) => block,
-						None => return Ok(None),
-					};
-					let mut block: ResultBlock = block.block.try_into()?;
-					block.header.block_hash = Some(hash);
-					Ok(Some(block))
-				});
-				Box::new(result) as BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>>
-			}
-			Ok(None) => Box::new(future::ok(Ok(None))),
-			Err(e) => Box::new(future::err(e)),
-		});
-		Box::new(result)
-	};
-	let result = get_block();
+		let result =
+			result.and_then(move |hash| get_result_header_future(rpc_client, shard_num, hash));
 
-	// get block with extrinsic result
-	let tmp_rpc_client = rpc_client.clone();
-	let get_block_with_extrinsic_result =
-		move || -> BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>> {
-			let result = result.and_then(move |x| match x {
-				Ok(Some(mut block)) => {
-					let events_storage_key = get_value_storage_key(b"System Events");
-					let events_storage_key = &Hex(events_storage_key.0);
-					let block_hash = &Some(block.header.block_hash.as_ref().expect("qed").clone());
-					let events = client::get_storage_future(
-						tmp_rpc_client,
-						events_storage_key,
-						block_hash,
-						shard_num,
-					);
-					let result =
-						events.map(move |x| -> jsonrpc_core::Result<Option<ResultBlock>> {
-							let result = get_block_extrinsics_result(x)?;
-							for (index, tx) in &mut block.extrinsics.iter_mut().enumerate() {
-								if !with_raw {
-									tx.raw = None;
-								}
-								tx.success = result.get(&(index as u32)).as_ref().map(|x| x.0);
-							}
-							Ok(Some(block))
-						});
-					Box::new(result) as BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>>
-				}
-				Ok(None) => Box::new(future::ok(Ok(None))),
-				Err(e) => Box::new(future::err(e)),
-			});
-			Box::new(result)
-		};
-	let result = get_block_with_extrinsic_result();
-
-	Box::new(result)
-}
+		let result = result.map(|x| x.map(|header| json!(header)));
 
-fn get_option_value_future<T>(
-	future: BoxFuture<jsonrpc_core::Result<Option<T>>>,
-) -> BoxFuture<jsonrpc_core::Result<Option<Value>>>
-where
-	T: TryInto<Value> + 'static,
-{
-	// convert to value to avoid jsonrpc u128 serialize problem
-	let convert_to_value = || -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
-		let result = future.map(|x| match x {
-			Ok(Some(o)) => {
-				let result: Value = o
-					.try_into()
-					.map_err(|_| errors::Error::from(errors::ErrorKind::ParseError))?;
-				Ok(Some(result))
-			}
-			Ok(None) => Ok(None),
-			Err(e) => Err(e),
-		});
 		Box::new(result)
-	};
-	let result = convert_to_value();
+	}
 
-	Box::new(result)
-}
+	fn get_headers_by_number_range(
+		&self,
+		shard_num: u16,
+		from: NumberOrHex,
+		to: NumberOrHex,
+	) -> BoxFuture<Value> {
+		let from = from.number();
+		let to = to.number();
 
-fn get_value_future<T>(
-	future: BoxFuture<jsonrpc_core::Result<T>>,
-) -> BoxFuture<jsonrpc_core::Result<Value>>
-where
-	T: TryInto<Value> + 'static,
-{
-	// convert to value to avoid jsonrpc u128 serialize problem
-	let convert_to_value = || -> BoxFuture<jsonrpc_core::Result<Value>> {
-		let result = future.map(|x| match x {
-			Ok(o) => {
-				let result: Value = o
-					.try_into()
-					.map_err(|_| errors::Error::from(errors::ErrorKind::ParseError))?;
-				Ok(result)
-			}
-			Err(e) => Err(e),
-		});
-		Box::new(result)
-	};
-	let result = convert_to_value();
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-	Box::new(result)
-}
+		match check_range(from, to, self.config().max_block_range) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-fn get_value_with_address_future(
-	future: BoxFuture<jsonrpc_core::Result<Option<Value>>>,
-) -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
-	let hrp = HRP.read().expect("qed").clone();
-	let shard_count = SHARD_COUNT.read().expect("qed").clone();
+		match self.check_block_number(shard_num, to) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-	let provide_address = move || -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
-		let result = future.map(move |x| match x {
-			Ok(Some(mut value)) => {
-				// process block
-				match value.get_mut("extrinsics") {
-					Some(extrinsics) => match extrinsics.as_array_mut() {
-						Some(extrinsics) => {
-							for extrinsic in extrinsics {
-								extrinsic_append_address(extrinsic, hrp.clone(), shard_count)
-							}
-						}
-						None => (),
-					},
-					None => (),
-				}
+		let futures = (from..=to)
+			.map(|number| get_header_by_number_future(self.rpc_client.clone(), shard_num, number))
+			.collect::<Vec<_>>();
 
-				// process extrinsic
-				match value.get("call") {
-					Some(_) => extrinsic_append_address(&mut value, hrp.clone(), shard_count),
-					None => (),
-				}
+		let result = join_limited(futures, self.config().max_range_concurrency);
 
-				Ok(Some(value))
-			}
-			Ok(None) => Ok(None),
-			Err(e) => Err(e),
+		let result = result.and_then(|x| -> jsonrpc_core::Result<Value> {
+			let result = serde_json::to_value(x)
+				.map_err(|_| errors::Error::from(errors::ErrorKind::ParseError))?;
+			Ok(result)
 		});
+
 		Box::new(result)
-	};
-	let result = provide_address();
+	}
 
-	Box::new(result)
-}
+	fn get_header_by_hash(
+		&self,
+		shard_num: u16,
+		hash: Hex<Vec<u8>>,
+		encoding: Option<Encoding>,
+	) -> BoxFuture<Option<Value>> {
+		let encoding = encoding.unwrap_or_default();
 
-fn extrinsic_append_address(extrinsic: &mut Value, hrp: Hrp, shard_count: u16) {
-	let call = &mut extrinsic["call"];
-	let module = call.get("module").and_then(|x| x.as_u64());
-	let method = call.get("method").and_then(|x| x.as_u64());
-	match (module, method) {
-		(Some(4), Some(0)) => {
-			let params = &mut call["params"];
-			if let Some(dest) = params["dest"].as_str() {
-				let dest = dest.trim_start_matches("0x");
-				match hex::decode(dest) {
-					Ok(dest) => {
-						if dest[0] == 0xFF {
-							let public = dest[1..].to_vec();
-							let shard_num = shard_num_for_bytes(&public, shard_count).expect("qed");
-							let address = public.to_address(hrp).expect("qed");
-							params["dest_address"] = Value::String(address.0);
-							params["dest_shard_num"] = Value::Number(shard_num.into());
-						}
-					}
-					Err(_) => (),
-				}
-			}
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
 		}
-		_ => (),
-	}
-}
 
-fn get_extrinsic_by_origin_hash_future(
-	rpc_client: Arc<RpcClient>,
-	shard_num: u16,
-	block_number: BlockNumber,
-	origin_hash: Hex<Vec<u8>>,
-) -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
-	// get block hash
-	let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
-		let result = client::get_block_hash_future(rpc_client.clone(), block_number, shard_num);
-		let result = result.map(|x| Ok(x));
-		Box::new(result)
-	};
-	let result = get_block_hash();
+		if encoding == Encoding::Scale {
+			return get_scale_header_future(self.rpc_client.clone(), shard_num, Some(hash));
+		}
 
-	let result = get_block_future(rpc_client.clone(), shard_num, false, result);
+		let result = future::ok(Some(hash));
 
-	// filter
-	let filter = move || -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
-		let result = result.map(move |x| match x {
-			Ok(Some(block)) => {
-				let extrinsic = block
-					.extrinsics
-					.into_iter()
-					.filter_map(|mut tx| match &tx.call {
-						Call::Relay(call) => match call {
-							relay::Call::Transfer(transfer) => {
-								let origin_tx = &transfer.tx;
-								let origin_tx_hash = blake2_256(&origin_tx.0).to_vec();
-								if &origin_tx_hash == &origin_hash.0 {
-									tx.block_number = Some(block_number);
-									Some(tx)
-								} else {
-									None
-								}
-							}
-						},
-						_ => None,
-					})
-					.next();
-				Ok(extrinsic)
-			}
-			Ok(None) => Ok(None),
-			Err(e) => Err(e),
+		let rpc_client = self.rpc_client.clone();
+		let result = result.and_then(move |hash| {
+			let header = client::get_header_future(rpc_client, &hash, shard_num);
+			header.map(|header| match (header, hash) {
+				(Some(header), Some(hash)) => {
+					let mut header: ResultHeader = header.into();
+					header.block_hash = Some(hash);
+					Some(json!(header))
+				}
+				_ => None,
+			})
 		});
+
 		Box::new(result)
-	};
-	let result = filter();
+	}
 
-	Box::new(result)
-}
+	fn get_parent_header(&self, shard_num: u16, hash: Hex<Vec<u8>>) -> BoxFuture<Option<Value>> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-fn get_value_with_tx_decoded_future(
-	future: BoxFuture<jsonrpc_core::Result<Option<Value>>>,
-) -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
-	let hrp = HRP.read().expect("qed").clone();
-	let shard_count = SHARD_COUNT.read().expect("qed").clone();
+		let rpc_client = self.rpc_client.clone();
+		let result = client::get_header_future(rpc_client.clone(), &Some(hash), shard_num);
 
-	let provide_tx_decoded = move || -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
-		let result = future.map(move |x| match x {
-			Ok(Some(mut value)) => {
-				// process extrinsic
-				match value.get("call") {
-					Some(_) => extrinsic_append_tx_decoded(&mut value, hrp.clone(), shard_count),
-					None => (),
+		// the genesis has no parent
+		let result = result.and_then(move |header| -> BoxFuture<Option<ResultHeader>> {
+			match header {
+				Some(header) if header.number > 0 => {
+					let parent_hash = Some(Hex(header.parent_hash));
+					get_result_header_future(rpc_client, shard_num, parent_hash)
 				}
-
-				Ok(Some(value))
+				_ => Box::new(future::ok(None)),
 			}
-			Ok(None) => Ok(None),
-			Err(e) => Err(e),
 		});
+
+		let result = result.map(|x| x.map(|x| json!(x)));
+
 		Box::new(result)
-	};
-	let result = provide_tx_decoded();
+	}
 
-	Box::new(result)
-}
+	fn get_header_digest(&self, shard_num: u16, hash: Hex<Vec<u8>>) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
 
-fn extrinsic_append_tx_decoded(extrinsic: &mut Value, hrp: Hrp, shard_count: u16) {
-	let call = &mut extrinsic["call"];
-	let module = call.get("module").and_then(|x| x.as_u64());
-	let method = call.get("method").and_then(|x| x.as_u64());
-	match (module, method) {
-		(Some(9), Some(0)) => {
-			let params = &mut call["params"];
-			if let Some(tx) = params["tx"].as_str() {
-				let tx = tx.trim_start_matches("0x");
-				match hex::decode(tx) {
-					Ok(tx) => {
-						let tx: Transaction = Decode::decode(&mut &tx[..]).expect("qed");
-						let tx: ResultTransaction = tx.into();
-						let mut tx: Value = tx.try_into().expect("qed");
-						extrinsic_append_address(&mut tx, hrp, shard_count);
-						params["tx_decoded"] = tx;
-					}
-					Err(_) => (),
-				}
+		let result = client::get_header_future(self.rpc_client.clone(), &Some(hash), shard_num);
+
+		let result = result.map(|header| match header {
+			Some(header) => {
+				let logs = header
+					.digest
+					.logs
+					.iter()
+					.map(|log| get_digest_log(&log.0))
+					.collect::<Vec<_>>();
+				Value::Array(logs)
 			}
-		}
-		_ => (),
-	}
-}
+			None => Value::Null,
+		});
 
-fn u64_from_slice(bytes: &[u8]) -> errors::Result<u64> {
-	const LEN: usize = 8;
-	if bytes.len() != LEN {
-		return Err(errors::ErrorKind::ParseError.into());
+		Box::new(result)
 	}
-	let mut array = [0; LEN];
-	let bytes = &bytes[..];
-	array.copy_from_slice(bytes);
-	Ok(u64::from_le_bytes(array))
-}
 
-fn u128_from_slice(bytes: &[u8]) -> errors::Result<u128> {
-	const LEN: usize = 16;
-	if bytes.len() != LEN {
-		return Err(errors::ErrorKind::ParseError.into());
+	fn get_block_by_number(
+		&self,
+		shard_num: u16,
+		number: NumberOrHex,
+		with_success: Option<bool>,
+		with_raw: Option<bool>,
+		encoding: Option<Encoding>,
+		fields: Option<Vec<String>>,
+	) -> BoxFuture<Option<Value>> {
+		let number = number.number();
+		let with_success = with_success.unwrap_or(true);
+		let with_raw = with_raw.unwrap_or(false);
+		let encoding = encoding.unwrap_or_default();
+
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		if encoding == Encoding::Scale {
+			let rpc_client = self.rpc_client.clone();
+			let result = get_produced_block_hash_future(rpc_client.clone(), shard_num, number);
+			let result =
+				result.and_then(move |hash| get_scale_block_future(rpc_client, shard_num, hash));
+			return Box::new(result);
+		}
+
+		let result = get_block_by_number_future(
+			self.rpc_client.clone(),
+			self.caches.block.clone(),
+			shard_num,
+			number,
+			with_success,
+			with_raw,
+		);
+
+		get_projected_future(result, fields)
+	}
+
+	fn get_block_by_number_default(&self, number: NumberOrHex) -> BoxFuture<Option<Value>> {
+		let shard_num = get_default_shard(&self.config());
+		match shard_num {
+			Ok(shard_num) => self.get_block_by_number(shard_num, number, None, None, None, None),
+			Err(e) => Box::new(future::err(e.into())),
+		}
+	}
+
+	fn get_block_by_number_signed(
+		&self,
+		shard_num: u16,
+		number: NumberOrHex,
+	) -> BoxFuture<Option<Value>> {
+		let number = number.number();
+
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let rpc_client = self.rpc_client.clone();
+		let result = get_produced_block_hash_future(rpc_client.clone(), shard_num, number);
+		let result = result.map(|x| Ok(x));
+
+		let result = get_block_future(
+			rpc_client,
+			self.caches.block.clone(),
+			shard_num,
+			false,
+			true,
+			Box::new(result),
+		);
+
+		let result = get_option_value_future(result);
+
+		let result = get_value_with_address_future(result);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_block_with_events(
+		&self,
+		shard_num: u16,
+		number: BlockNumber,
+	) -> BoxFuture<Option<Value>> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let rpc_client = self.rpc_client.clone();
+		let block_cache = self.caches.block.clone();
+		let result = get_produced_block_hash_future(rpc_client.clone(), shard_num, number);
+
+		// the cached blocks carry no events, read from the node once
+		let result = result.and_then(
+			move |hash| -> BoxFuture<jsonrpc_core::Result<Option<(ResultBlock, Value)>>> {
+				let hash = match hash {
+					Some(hash) => hash,
+					None => return Box::new(future::ok(Ok(None))),
+				};
+				let result =
+					get_cached_block_with_events_future(rpc_client, block_cache, shard_num, hash);
+				let result = result.map(|x| {
+					x.and_then(|x| match x {
+						Some((block, events)) => {
+							let events = match &events {
+								Some(events) => decode_events(&events.0)?,
+								None => Value::Array(vec![]),
+							};
+							Ok(Some((block, events)))
+						}
+						None => Ok(None),
+					})
+				});
+				Box::new(result)
+			},
+		);
+
+		let hrp = HRP.read().expect("qed").clone();
+		let shard_count = SHARD_COUNT.read().expect("qed").clone();
+
+		let result = result.map(move |x| -> jsonrpc_core::Result<Option<Value>> {
+			let (mut block, events) = match x? {
+				Some(x) => x,
+				None => return Ok(None),
+			};
+
+			// strip raw
+			for tx in block.extrinsics.iter_mut() {
+				tx.raw = None;
+			}
+
+			let mut block: Value = block
+				.try_into()
+				.map_err(|_| errors::Error::from(errors::ErrorKind::ParseError))?;
+			if let Some(extrinsics) = block["extrinsics"].as_array_mut() {
+				for extrinsic in extrinsics {
+					extrinsic_append_address(extrinsic, hrp.clone(), shard_count)
+				}
+			}
+
+			let result = json!({
+				"block": block,
+				"events": events,
+			});
+			Ok(Some(result))
+		});
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_events_raw(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Option<Value>> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let rpc_client = self.rpc_client.clone();
+		let result = get_produced_block_hash_future(rpc_client.clone(), shard_num, number);
+
+		let result = result.and_then(move |hash| -> BoxFuture<Option<Hex<Vec<u8>>>> {
+			match hash {
+				Some(hash) => client::get_storage_future(
+					rpc_client,
+					&get_events_storage_key(),
+					&Some(hash),
+					shard_num,
+				),
+				None => Box::new(future::ok(None)),
+			}
+		});
+
+		let result = result.map(|x| x.map(|x| json!(x)));
+
+		Box::new(result)
+	}
+
+	fn get_finalized_block_by_number(
+		&self,
+		shard_num: u16,
+		number: BlockNumber,
+	) -> BoxFuture<Option<Value>> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let rpc_client = self.rpc_client.clone();
+		let block_cache = self.caches.block.clone();
+
+		// the cached finalized number only grows, refresh it if not enough
+		let finalized: BoxFuture<Option<BlockNumber>> =
+			match block_cache.finalized_number(shard_num) {
+				Some(finalized) if number <= finalized => Box::new(future::ok(Some(finalized))),
+				_ => {
+					let block_cache = block_cache.clone();
+					let result = get_finalized_number_future(rpc_client.clone(), shard_num);
+					let result = result.map(move |x| {
+						if let Some(finalized) = x {
+							block_cache.set_finalized_number(shard_num, finalized);
+						}
+						x
+					});
+					Box::new(result)
+				}
+			};
+
+		let result = finalized.and_then(move |finalized| -> BoxFuture<Option<Value>> {
+			match check_finalized(number, finalized) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+			get_block_by_number_future(rpc_client, block_cache, shard_num, number, true, false)
+		});
+
+		Box::new(result)
+	}
+
+	fn get_block_by_hash(
+		&self,
+		shard_num: u16,
+		hash: Hex<Vec<u8>>,
+		with_success: Option<bool>,
+		with_raw: Option<bool>,
+		encoding: Option<Encoding>,
+		fields: Option<Vec<String>>,
+	) -> BoxFuture<Option<Value>> {
+		let with_success = with_success.unwrap_or(true);
+		let with_raw = with_raw.unwrap_or(false);
+		let encoding = encoding.unwrap_or_default();
+
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		if encoding == Encoding::Scale {
+			return get_scale_block_future(self.rpc_client.clone(), shard_num, Some(hash));
+		}
+
+		let result = Box::new(future::ok(Ok(Some(hash))));
+
+		let result = get_block_future(
+			self.rpc_client.clone(),
+			self.caches.block.clone(),
+			shard_num,
+			with_raw,
+			with_success,
+			result,
+		);
+
+		let result = get_option_value_future(result);
+
+		let result = get_value_with_address_future(result);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		get_projected_future(Box::new(result), fields)
+	}
+
+	fn get_block(&self, shard_num: u16, at: Value) -> BoxFuture<Option<Value>> {
+		match parse_block_id(&at) {
+			Ok(BlockId::Number(number)) => self.get_block_by_number(
+				shard_num,
+				NumberOrHex::Number(number),
+				None,
+				None,
+				None,
+				None,
+			),
+			Ok(BlockId::Hash(hash)) => {
+				self.get_block_by_hash(shard_num, hash, None, None, None, None)
+			}
+			Err(e) => Box::new(future::err(e.into())),
+		}
+	}
+
+	fn get_block_by_hash_list(
+		&self,
+		shard_num: u16,
+		hashes: Vec<Hex<Vec<u8>>>,
+	) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match check_hash_list(hashes.len(), self.config().max_hash_list) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let futures = hashes
+			.into_iter()
+			.map(|hash| self.get_block_by_hash(shard_num, hash, None, None, None, None))
+			.collect::<Vec<_>>();
+
+		// in the order of the hashes
+		let result = join_limited(futures, self.config().max_range_concurrency);
+
+		let result = result.and_then(|x| -> jsonrpc_core::Result<Value> {
+			let result = Value::Array(x.into_iter().map(|x| x.unwrap_or(Value::Null)).collect());
+			Ok(result)
+		});
+
+		Box::new(result)
+	}
+
+	fn get_block_author(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let rpc_client = self.rpc_client.clone();
+		let result = client::get_block_hash_future(rpc_client.clone(), number, shard_num);
+
+		let result = result.and_then(move |hash| -> BoxFuture<Option<Header>> {
+			match hash {
+				Some(hash) => client::get_header_future(rpc_client, &Some(hash), shard_num),
+				None => Box::new(future::ok(None)),
+			}
+		});
+
+		let result = result.map(|header| {
+			let hrp = HRP.read().expect("qed").clone();
+			match header.and_then(|header| get_block_author(&header, hrp)) {
+				Some(author) => Value::String(author),
+				None => Value::Null,
+			}
+		});
+
+		Box::new(result)
+	}
+
+	fn verify_extrinsics_root(&self, shard_num: u16, number: BlockNumber) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		// the raw extrinsics of the node block
+		let rpc_client = self.rpc_client.clone();
+		let result = get_produced_block_hash_future(rpc_client.clone(), shard_num, number);
+
+		let result = result.and_then(move |hash| -> BoxFuture<Option<BlockResponse>> {
+			match hash {
+				Some(hash) => client::get_block_future(rpc_client, &Some(hash), shard_num),
+				None => Box::new(future::ok(None)),
+			}
+		});
+
+		let result = result.and_then(|x| -> jsonrpc_core::Result<Value> {
+			let block = match x {
+				Some(x) => x.block,
+				None => return Ok(Value::Null),
+			};
+			let computed = block.compute_extrinsics_root()?;
+			let expected = block.header.extrinsics_root;
+			Ok(json!({
+				"valid": computed == expected,
+				"computed": Hex(computed),
+				"expected": Hex(expected),
+			}))
+		});
+
+		Box::new(result)
+	}
+
+	fn get_extrinsic_by_hash(
+		&self,
+		shard_num: u16,
+		block_number: NumberOrHex,
+		hash: Hex<Vec<u8>>,
+		with_raw: Option<bool>,
+		fields: Option<Vec<String>>,
+	) -> BoxFuture<Option<Value>> {
+		let block_number = block_number.number();
+		let with_raw = with_raw.unwrap_or(false);
+
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let result = get_extrinsic_by_hash_future(
+			self.rpc_client.clone(),
+			self.caches.block.clone(),
+			shard_num,
+			block_number,
+			hash,
+			with_raw,
+		);
+
+		let result = get_option_value_future(result);
+
+		let result = get_value_with_address_future(result);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		get_projected_future(Box::new(result), fields)
+	}
+
+	fn get_extrinsic_by_hash_in_range(
+		&self,
+		shard_num: u16,
+		from: BlockNumber,
+		to: BlockNumber,
+		hash: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match check_range(from, to, self.config().max_block_range) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, to) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		// scan the blocks concurrently
+		let futures = (from..=to)
+			.map(|number| {
+				let result = get_extrinsic_by_hash_future(
+					self.rpc_client.clone(),
+					self.caches.block.clone(),
+					shard_num,
+					number,
+					hash.clone(),
+					false,
+				);
+				result.map(move |x| {
+					x.map(|x| {
+						x.map(|mut tx| {
+							tx.block_number = Some(number);
+							tx
+						})
+					})
+				})
+			})
+			.collect::<Vec<_>>();
+
+		// the first in the range
+		let result = join_limited(futures, self.config().max_range_concurrency).map(
+			|x| -> jsonrpc_core::Result<Option<ResultTransaction>> {
+				for tx in x {
+					if let Some(tx) = tx? {
+						return Ok(Some(tx));
+					}
+				}
+				Ok(None)
+			},
+		);
+		let result: BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> = Box::new(result);
+
+		let result = get_option_value_future(result);
+
+		let result = get_value_with_address_future(result);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_extrinsic_by_hash_all_shards(
+		&self,
+		block_number: BlockNumber,
+		hash: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>> {
+		let shard_count = self.config().shards.len() as u16;
+
+		// search the shards concurrently
+		let futures = (0..shard_count)
+			.map(|shard_num| {
+				let result = get_extrinsic_by_hash_future(
+					self.rpc_client.clone(),
+					self.caches.block.clone(),
+					shard_num,
+					block_number,
+					hash.clone(),
+					false,
+				);
+
+				let result = get_option_value_future(result);
+
+				let result = get_value_with_address_future(result);
+
+				result.map(move |x| {
+					x.map(|x| {
+						x.map(|mut tx| {
+							tx["shard_num"] = json!(shard_num);
+							tx
+						})
+					})
+				})
+			})
+			.collect::<Vec<_>>();
+
+		// the first match, the failed shards matter only if none matches
+		let result = future::join_all(futures).and_then(|x| {
+			let mut error = None;
+			for tx in x {
+				match tx {
+					Ok(Some(tx)) => return future::ok(Some(tx)),
+					Ok(None) => (),
+					Err(e) => {
+						error.get_or_insert(e);
+					}
+				}
+			}
+			match error {
+				Some(e) => future::err(e),
+				None => future::ok(None),
+			}
+		});
+
+		Box::new(result)
+	}
+
+	fn get_extrinsics_by_sender_in_block(
+		&self,
+		shard_num: u16,
+		number: BlockNumber,
+		address: String,
+	) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
+
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let (public_key, _) = match get_address_shard_num(address, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		// the raw to extract the signers
+		let result = get_block_by_number_future(
+			self.rpc_client.clone(),
+			self.caches.block.clone(),
+			shard_num,
+			number,
+			true,
+			true,
+		);
+
+		let result = result.map(move |block| match block {
+			Some(block) => {
+				let extrinsics = block["extrinsics"]
+					.as_array()
+					.map(|x| {
+						x.iter()
+							.filter(|x| extrinsic_signed_by(x, &public_key, shard_count))
+							.cloned()
+							.collect::<Vec<_>>()
+					})
+					.unwrap_or_default();
+				Value::Array(extrinsics)
+			}
+			None => Value::Null,
+		});
+
+		Box::new(result)
+	}
+
+	fn get_extrinsic_count_by_address(
+		&self,
+		shard_num: u16,
+		address: String,
+		from: BlockNumber,
+		to: BlockNumber,
+	) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
+
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match check_range(from, to, self.config().max_block_range) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, to) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let (public_key, _) = match get_address_shard_num(address, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		// scan the blocks concurrently, each block is dropped once counted
+		let futures = (from..=to)
+			.map(|number| {
+				let result = get_block_by_number_future(
+					self.rpc_client.clone(),
+					self.caches.block.clone(),
+					shard_num,
+					number,
+					false,
+					true,
+				);
+				result.map(move |block| {
+					block
+						.as_ref()
+						.and_then(|x| x["extrinsics"].as_array())
+						.map(|x| {
+							x.iter()
+								.filter(|x| extrinsic_signed_by(x, &public_key, shard_count))
+								.count()
+						})
+						.unwrap_or_default()
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let result = join_limited(futures, self.config().max_range_concurrency)
+			.map(|x| Value::from(x.iter().sum::<usize>()));
+
+		Box::new(result)
+	}
+
+	fn get_extrinsic_by_raw(
+		&self,
+		shard_num: u16,
+		block_number: NumberOrHex,
+		raw: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>> {
+		let block_number = block_number.number();
+
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, block_number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let result = get_extrinsic_by_raw_future(
+			self.rpc_client.clone(),
+			self.caches.block.clone(),
+			shard_num,
+			block_number,
+			raw,
+		);
+
+		let result = get_option_value_future(result);
+
+		let result = get_value_with_address_future(result);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_extrinsic_by_raw_in_range(
+		&self,
+		shard_num: u16,
+		from: BlockNumber,
+		to: BlockNumber,
+		raw: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match check_range(from, to, self.config().max_block_range) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, to) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		// scan the blocks concurrently
+		let futures = (from..=to)
+			.map(|number| {
+				let result = get_extrinsic_by_raw_future(
+					self.rpc_client.clone(),
+					self.caches.block.clone(),
+					shard_num,
+					number,
+					raw.clone(),
+				);
+				result.map(move |x| {
+					x.map(|x| {
+						x.map(|mut tx| {
+							tx.block_number = Some(number);
+							tx
+						})
+					})
+				})
+			})
+			.collect::<Vec<_>>();
+
+		// the first in the range
+		let result = join_limited(futures, self.config().max_range_concurrency).map(
+			|x| -> jsonrpc_core::Result<Option<ResultTransaction>> {
+				for tx in x {
+					if let Some(tx) = tx? {
+						return Ok(Some(tx));
+					}
+				}
+				Ok(None)
+			},
+		);
+		let result: BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> = Box::new(result);
+
+		let result = get_option_value_future(result);
+
+		let result = get_value_with_address_future(result);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_extrinsic_by_origin_hash(
+		&self,
+		shard_num: u16,
+		from_block_number: NumberOrHex,
+		to_block_number: NumberOrHex,
+		origin_hash: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>> {
+		let from_block_number = from_block_number.number();
+		let to_block_number = to_block_number.number();
+
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match check_range(
+			from_block_number,
+			to_block_number,
+			self.config().max_block_range,
+		) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, to_block_number) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let result = get_extrinsic_by_origin_hash_in_range_future(
+			self.rpc_client.clone(),
+			self.caches.block.clone(),
+			shard_num,
+			from_block_number,
+			to_block_number,
+			origin_hash,
+		);
+
+		let result = get_option_value_future(result);
+
+		let result = get_value_with_tx_decoded_future(result, self.config().max_decode_depth);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_extrinsic_by_origin_hash_all_shards(
+		&self,
+		from_block_number: NumberOrHex,
+		to_block_number: NumberOrHex,
+		origin_hash: Hex<Vec<u8>>,
+	) -> BoxFuture<Option<Value>> {
+		let from_block_number = from_block_number.number();
+		let to_block_number = to_block_number.number();
+
+		match check_range(
+			from_block_number,
+			to_block_number,
+			self.config().max_block_range,
+		) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let shard_count = self.config().shards.len() as u16;
+
+		for shard_num in 0..shard_count {
+			match self.check_block_number(shard_num, to_block_number) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+		}
+
+		// search every shard concurrently, attach the shard num to the match
+		let futures = (0..shard_count)
+			.map(|shard_num| {
+				let result = get_extrinsic_by_origin_hash_in_range_future(
+					self.rpc_client.clone(),
+					self.caches.block.clone(),
+					shard_num,
+					from_block_number,
+					to_block_number,
+					origin_hash.clone(),
+				);
+				let result = get_option_value_future(result);
+				result.map(move |x| {
+					x.map(|x| {
+						x.map(|mut value| {
+							value["shard_num"] = json!(shard_num);
+							value
+						})
+					})
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let result = future::join_all(futures);
+
+		let result = result.map(|x| -> jsonrpc_core::Result<Option<Value>> {
+			for item in x {
+				if let Some(value) = item? {
+					return Ok(Some(value));
+				}
+			}
+			Ok(None)
+		});
+
+		let result =
+			get_value_with_tx_decoded_future(Box::new(result), self.config().max_decode_depth);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_relay_transfer_status(
+		&self,
+		origin_hash: Hex<Vec<u8>>,
+		from_block_number: NumberOrHex,
+		to_block_number: NumberOrHex,
+	) -> BoxFuture<Value> {
+		let from_block_number = from_block_number.number();
+		let to_block_number = to_block_number.number();
+
+		match check_range(
+			from_block_number,
+			to_block_number,
+			self.config().max_block_range,
+		) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let shard_count = self.config().shards.len() as u16;
+
+		for shard_num in 0..shard_count {
+			match self.check_block_number(shard_num, to_block_number) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+		}
+
+		// search every shard, the relay extrinsic lands on the destination shard
+		let futures = (0..shard_count)
+			.map(|shard_num| {
+				let result = get_extrinsic_by_origin_hash_in_range_future(
+					self.rpc_client.clone(),
+					self.caches.block.clone(),
+					shard_num,
+					from_block_number,
+					to_block_number,
+					origin_hash.clone(),
+				);
+				result.then(move |x| -> jsonrpc_core::Result<jsonrpc_core::Result<_>> {
+					Ok(x.and_then(|x| x).map(|x| x.map(|tx| (shard_num, tx))))
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let result = future::join_all(futures);
+
+		// a failed shard is reported, the other shards are still searched
+		let result = result.map(|x| {
+			let mut landed = None;
+			let mut failed_shards = Vec::new();
+			for (shard_num, item) in x.into_iter().enumerate() {
+				match item {
+					Ok(Some(item)) if landed.is_none() => landed = Some(item),
+					Ok(_) => (),
+					Err(e) => {
+						warn!("Failed to search shard {}: {:?}", shard_num, e);
+						failed_shards.push(shard_num as u16);
+					}
+				}
+			}
+			match landed {
+				Some((shard_num, tx)) => json!({
+					"status": "landed",
+					"shard_num": shard_num,
+					"block_number": tx.block_number,
+					"hash": tx.hash,
+				}),
+				None if failed_shards.is_empty() => json!({
+					"status": "pending",
+				}),
+				None => json!({
+					"status": "unknown",
+					"failed_shards": failed_shards,
+				}),
+			}
+		});
+
+		Box::new(result)
+	}
+
+	fn get_relay_origin_hash(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value> {
+		let result = || -> errors::Result<Value> {
+			let tx: Transaction = Decode::decode(&mut &raw.0[..])
+				.ok_or(errors::ErrorKind::DecodeFailed("transaction"))?;
+			let origin_hash =
+				get_relay_origin_hash(&tx.call).ok_or(errors::ErrorKind::NotRelayTransfer)?;
+			Ok(json!(Hex(origin_hash)))
+		};
+
+		Box::new(future::result(result().map_err(jsonrpc_core::Error::from)))
+	}
+
+	fn decode_header(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value> {
+		let result = decode_header(&raw.0).map_err(jsonrpc_core::Error::from);
+
+		Box::new(future::result(result))
+	}
+
+	fn decode_events(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value> {
+		let result = decode_events(&raw.0).map_err(jsonrpc_core::Error::from);
+
+		Box::new(future::result(result))
+	}
+
+	fn describe_call(&self, module: u8, method: u8) -> BoxFuture<Value> {
+		Box::new(future::ok(describe_call(module, method)))
+	}
+
+	fn get_nonce(&self, address: String, block_number: Option<NumberOrHex>) -> BoxFuture<Value> {
+		let block_number = block_number.map(|x| x.number());
+
+		let public_key = match decode_address(&address) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let shard_count = self.config().shards.len() as u16;
+
+		let shard_num = match shard_num_for_bytes(&public_key, shard_count) {
+			Some(shard_num) => shard_num,
+			None => {
+				return Box::new(future::err(
+					errors::Error::from(errors::ErrorKind::InvalidShard).into(),
+				));
+			}
+		};
+		let storage_key = get_map_storage_key(&public_key, b"System AccountNonce");
+		let storage_key = Hex(storage_key.0);
+
+		// get block hash
+		let result = self.get_state_block_hash(shard_num, block_number);
+		let result = result.map(|x| Ok(x));
+
+		// get nonce
+		let rpc_client = self.rpc_client.clone();
+		let get_nonce = move || -> BoxFuture<jsonrpc_core::Result<Nonce>> {
+			let result = result.and_then(move |x| match x {
+				Ok(block_hash) => {
+					let result = client::get_storage_future(
+						rpc_client,
+						&storage_key,
+						&block_hash,
+						shard_num,
+					);
+					let result = result.map(|x| -> jsonrpc_core::Result<Nonce> {
+						match x {
+							Some(x) => Ok(Nonce::from(u64_from_slice(&x.0)?)),
+							None => Ok(Nonce::from(0)),
+						}
+					});
+					Box::new(result) as BoxFuture<jsonrpc_core::Result<Nonce>>
+				}
+				Err(e) => Box::new(future::err(e)),
+			});
+			Box::new(result)
+		};
+		let result = get_nonce();
+
+		let result = get_value_future(result);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_nonce_with_pending(&self, address: String) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
+
+		let (public_key, shard_num) = match get_address_shard_num(address, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let storage_key = get_map_storage_key(&public_key, b"System AccountNonce");
+		let storage_key = Hex(storage_key.0);
+
+		let nonce =
+			client::get_storage_future(self.rpc_client.clone(), &storage_key, &None, shard_num);
+		let nonce = nonce.and_then(|x| -> jsonrpc_core::Result<u64> {
+			match x {
+				Some(x) => Ok(u64_from_slice(&x.0)?),
+				None => Ok(0),
+			}
+		});
+
+		let pending = client::get_pending_extrinsics_future(self.rpc_client.clone(), shard_num);
+
+		let result =
+			nonce
+				.join(pending)
+				.and_then(move |(nonce, pending)| -> jsonrpc_core::Result<Value> {
+					let nonce = get_next_nonce(nonce, &pending, &public_key);
+					let nonce: Value = Nonce::from(nonce).try_into()?;
+					Ok(nonce)
+				});
+
+		Box::new(result)
+	}
+
+	fn get_balance(&self, address: String, block_number: Option<NumberOrHex>) -> BoxFuture<Value> {
+		let block_number = block_number.map(|x| x.number());
+
+		let shard_count = self.config().shards.len() as u16;
+
+		let (public_key, shard_num) = match get_address_shard_num(address, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		if let Some(number) = block_number {
+			match self.check_block_number(shard_num, number) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+		}
+
+		let result = get_balance_future(
+			self.rpc_client.clone(),
+			&public_key,
+			block_number,
+			shard_num,
+			self.config().finalized_state,
+		);
+
+		let result = Box::new(result.map(|x| Ok(x)));
+
+		let result = get_value_future(result);
+
+		let result = result.and_then(|x| match x {
+			Ok(v) => future::ok(v),
+			Err(e) => future::err(e),
+		});
+
+		Box::new(result)
+	}
+
+	fn get_account_exists(
+		&self,
+		address: String,
+		block_number: Option<NumberOrHex>,
+	) -> BoxFuture<Value> {
+		let block_number = block_number.map(|x| x.number());
+
+		let shard_count = self.config().shards.len() as u16;
+
+		let (public_key, shard_num) = match get_address_shard_num(address, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let rpc_client = self.rpc_client.clone();
+		let result = self.get_state_block_hash(shard_num, block_number);
+
+		// an account never used has neither key, a zero balance still has the key
+		let result = result.and_then(move |block_hash| {
+			let get_storage = |prefix: &[u8]| {
+				let storage_key = get_map_storage_key(&public_key, prefix);
+				let storage_key = Hex(storage_key.0);
+				client::get_storage_future(rpc_client.clone(), &storage_key, &block_hash, shard_num)
+			};
+			let nonce = get_storage(b"System AccountNonce");
+			let balance = get_storage(b"Balances FreeBalance");
+			nonce
+				.join(balance)
+				.map(|(nonce, balance)| Value::Bool(nonce.is_some() || balance.is_some()))
+		});
+
+		Box::new(result)
+	}
+
+	fn get_balance_locks(
+		&self,
+		address: String,
+		block_number: Option<NumberOrHex>,
+	) -> BoxFuture<Value> {
+		let block_number = block_number.map(|x| x.number());
+
+		let shard_count = self.config().shards.len() as u16;
+
+		let (public_key, shard_num) = match get_address_shard_num(address, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let storage_key = get_map_storage_key(&public_key, b"Balances Locks");
+		let storage_key = Hex(storage_key.0);
+
+		let rpc_client = self.rpc_client.clone();
+		let result = self.get_state_block_hash(shard_num, block_number);
+
+		let result = result.and_then(move |block_hash| {
+			client::get_storage_future(rpc_client, &storage_key, &block_hash, shard_num)
+		});
+
+		// no locks stored
+		let result =
+			result.and_then(|x| -> jsonrpc_core::Result<Value> { Ok(decode_balance_locks(x)?) });
+
+		Box::new(result)
+	}
+
+	fn get_full_account(
+		&self,
+		address: String,
+		block_number: Option<NumberOrHex>,
+	) -> BoxFuture<Value> {
+		let block_number = block_number.map(|x| x.number());
+
+		let shard_count = self.config().shards.len() as u16;
+
+		let (public_key, shard_num) = match get_address_shard_num(address, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let rpc_client = self.rpc_client.clone();
+		let result = self.get_state_block_hash(shard_num, block_number);
+
+		let result = result.and_then(move |block_hash| {
+			get_full_account_future(rpc_client, &public_key, block_hash, shard_num)
+		});
+
+		Box::new(result)
+	}
+
+	fn get_account_info_batch(
+		&self,
+		addresses: Vec<String>,
+		block_number: Option<NumberOrHex>,
+	) -> BoxFuture<Value> {
+		let block_number = block_number.map(|x| x.number());
+
+		match check_batch_addresses(addresses.len(), self.config().max_batch_addresses) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		let shard_count = self.config().shards.len() as u16;
+
+		// (index, address, public key) of each shard, the invalid addresses fail alone
+		let mut entries = Vec::new();
+		let mut shards: HashMap<u16, Vec<(usize, String, [u8; 32])>> = HashMap::new();
+		for (index, address) in addresses.into_iter().enumerate() {
+			match get_address_shard_num(address.clone(), shard_count) {
+				Ok((public_key, shard_num)) => shards
+					.entry(shard_num)
+					.or_insert_with(Vec::new)
+					.push((index, address, public_key)),
+				Err(e) => {
+					let error: jsonrpc_core::Error = e.into();
+					entries.push((index, json!({ "address": address, "error": error })));
+				}
+			}
+		}
+
+		let futures = shards
+			.into_iter()
+			.map(|(shard_num, accounts)| {
+				// the block hash is read once for the shard, not yet produced above the best number
+				let rpc_client = self.rpc_client.clone();
+				let result = self.get_state_block_hash(shard_num, block_number);
+
+				result.then(move |block_hash| {
+					let futures = accounts
+						.into_iter()
+						.map(move |(index, address, public_key)| {
+							let result: BoxFuture<Value> = match &block_hash {
+								Ok(block_hash) => get_account_info_future(
+									rpc_client.clone(),
+									&public_key,
+									block_hash.clone(),
+									shard_num,
+								),
+								Err(e) => Box::new(future::err(e.clone())),
+							};
+							result.then(move |x| -> jsonrpc_core::Result<(usize, Value)> {
+								let entry = match x {
+									Ok(mut info) => {
+										info["address"] = Value::String(address);
+										info
+									}
+									Err(error) => json!({ "address": address, "error": error }),
+								};
+								Ok((index, entry))
+							})
+						});
+					future::join_all(futures.collect::<Vec<_>>())
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let result = future::join_all(futures).map(move |x| {
+			entries.extend(x.into_iter().flatten());
+			entries.sort_by_key(|(index, _)| *index);
+			Value::Array(entries.into_iter().map(|(_, entry)| entry).collect())
+		});
+
+		Box::new(result)
+	}
+
+	fn get_address_balance_history(
+		&self,
+		address: String,
+		from_block: NumberOrHex,
+		to_block: NumberOrHex,
+		step: NumberOrHex,
+	) -> BoxFuture<Value> {
+		let from_block = from_block.number();
+		let to_block = to_block.number();
+		let step = step.number();
+
+		let shard_count = self.config().shards.len() as u16;
+
+		let (public_key, shard_num) = match get_address_shard_num(address, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let numbers = match get_sample_numbers(
+			from_block,
+			to_block,
+			step,
+			self.config().max_balance_samples,
+		) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		match self.check_block_number(shard_num, to_block) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		// the blocks above the best number are not yet produced
+		let rpc_client = self.rpc_client.clone();
+		let max_range_concurrency = self.config().max_range_concurrency;
+		let result = client::get_header_future(rpc_client.clone(), &None, shard_num);
+		let result = result.and_then(move |header| -> BoxFuture<Value> {
+			match check_produced(to_block, header.map(|x| x.number)) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+
+			let futures = numbers
+				.into_iter()
+				.map(|number| {
+					let result = get_balance_future(
+						rpc_client.clone(),
+						&public_key,
+						Some(number),
+						shard_num,
+						false,
+					);
+					result.and_then(move |x| -> jsonrpc_core::Result<Value> {
+						let balance: Value = x.try_into()?;
+						Ok(json!({
+							"block_number": number,
+							"balance": balance,
+						}))
+					})
+				})
+				.collect::<Vec<_>>();
+
+			Box::new(join_limited(futures, max_range_concurrency).map(Value::Array))
+		});
+
+		Box::new(result)
+	}
+
+	fn get_storage_batch(
+		&self,
+		shard_num: u16,
+		keys: Vec<Hex<Vec<u8>>>,
+		block_number: Option<BlockNumber>,
+	) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match check_storage_keys(keys.len(), self.config().max_storage_keys) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		if let Some(number) = block_number {
+			match self.check_block_number(shard_num, number) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+		}
+
+		// the block hash once for all the keys
+		let block_hash: BoxFuture<Option<Hex<Vec<u8>>>> = match block_number {
+			Some(block_number) => {
+				get_produced_block_hash_future(self.rpc_client.clone(), shard_num, block_number)
+			}
+			None => Box::new(future::ok(None)),
+		};
+
+		let rpc_client = self.rpc_client.clone();
+		let result = block_hash.and_then(move |block_hash| {
+			let futures = keys
+				.iter()
+				.map(|key| {
+					client::get_storage_future(rpc_client.clone(), key, &block_hash, shard_num)
+				})
+				.collect::<Vec<_>>();
+			future::join_all(futures).map(|x| json!(x))
+		});
+
+		Box::new(result)
+	}
+
+	fn get_storage_changes(
+		&self,
+		shard_num: u16,
+		key: Hex<Vec<u8>>,
+		from: BlockNumber,
+		to: BlockNumber,
+	) -> BoxFuture<Value> {
+		match check_shard_num(shard_num, &self.config()) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match check_range(from, to, self.config().max_block_range) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		match self.check_block_number(shard_num, to) {
+			Err(e) => return Box::new(future::err(e.into())),
+			_ => (),
+		}
+
+		// the blocks above the best number are not yet produced
+		let rpc_client = self.rpc_client.clone();
+		let max_range_concurrency = self.config().max_range_concurrency;
+		let result = client::get_header_future(rpc_client.clone(), &None, shard_num);
+		let result = result.and_then(move |header| -> BoxFuture<Value> {
+			match check_produced(to, header.map(|x| x.number)) {
+				Err(e) => return Box::new(future::err(e.into())),
+				_ => (),
+			}
+
+			// the value at each block of the range
+			let futures = (from..=to)
+				.map(|number| {
+					let rpc_client = rpc_client.clone();
+					let key = key.clone();
+					let result =
+						client::get_block_hash_future(rpc_client.clone(), number, shard_num);
+					result.and_then(move |hash| -> BoxFuture<Option<Hex<Vec<u8>>>> {
+						match hash {
+							Some(hash) => {
+								client::get_storage_future(rpc_client, &key, &Some(hash), shard_num)
+							}
+							None => Box::new(future::ok(None)),
+						}
+					})
+				})
+				.collect::<Vec<_>>();
+
+			let result = join_limited(futures, max_range_concurrency)
+				.map(move |values| get_storage_changes(from, values));
+
+			Box::new(result)
+		});
+
+		Box::new(result)
+	}
+
+	fn get_map_storage_key(
+		&self,
+		module_prefix: String,
+		public_key: Hex<Vec<u8>>,
+	) -> BoxFuture<Value> {
+		let storage_key = get_map_storage_key(&public_key.0, module_prefix.as_bytes());
+
+		Box::new(future::ok(json!(Hex(storage_key.0))))
+	}
+
+	fn get_value_storage_key(&self, prefix: String) -> BoxFuture<Value> {
+		let storage_key = get_value_storage_key(prefix.as_bytes());
+
+		Box::new(future::ok(json!(Hex(storage_key.0))))
+	}
+
+	fn submit_extrinsic(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Hex<Vec<u8>>> {
+		let shard_count = self.config().shards.len() as u16;
+
+		let shard_num = match get_extrinsic_shard_num(&raw.0, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let result = client::submit_extrinsic_future(self.rpc_client.clone(), &raw, shard_num);
+
+		result
+	}
+
+	fn dry_run_extrinsic(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
+
+		let shard_num = match get_extrinsic_shard_num(&raw.0, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let result = client::dry_run_extrinsic_future(self.rpc_client.clone(), &raw, shard_num);
+
+		let result =
+			result.and_then(|x| parse_dry_run_result(&x).map_err(jsonrpc_core::Error::from));
+
+		Box::new(result)
+	}
+
+	fn get_extrinsic_shard(&self, raw: Hex<Vec<u8>>) -> BoxFuture<Value> {
+		let shard_count = self.config().shards.len() as u16;
+
+		let (public, shard_num) = match get_extrinsic_signer(&raw.0, shard_count) {
+			Ok(v) => v,
+			Err(e) => return Box::new(future::err(e.into())),
+		};
+
+		let hrp = HRP.read().expect("qed").clone();
+		let signer = match public.to_vec().to_address(hrp) {
+			Ok(v) => v,
+			Err(_) => return Box::new(future::err(errors::ErrorKind::InvalidAddress.into())),
+		};
+
+		Box::new(future::ok(json!({
+			"shard_num": shard_num,
+			"signer": signer.0,
+		})))
+	}
+}
+
+/// Get the shard of the extrinsic by the signer
+fn get_extrinsic_shard_num(raw: &[u8], shard_count: u16) -> errors::Result<u16> {
+	let (_public, shard_num) = get_extrinsic_signer(raw, shard_count)?;
+
+	Ok(shard_num)
+}
+
+/// (public key, shard num) of the extrinsic signer
+fn get_extrinsic_signer(raw: &[u8], shard_count: u16) -> errors::Result<([u8; 32], u16)> {
+	let tx: Transaction =
+		Decode::decode(&mut &raw[..]).ok_or(errors::ErrorKind::DecodeFailed("transaction"))?;
+
+	let public = match tx.signature {
+		Some((address, _, _, _)) => {
+			let mut public = [0u8; 32];
+			(&mut public[..]).copy_from_slice(&address.0[1..]);
+			public
+		}
+		None => return Err(errors::ErrorKind::UnsignedExtrinsic.into()),
+	};
+
+	let shard_num =
+		shard_num_for_bytes(&public, shard_count).ok_or(errors::ErrorKind::InvalidShard)?;
+
+	Ok((public, shard_num))
+}
+
+/// The extrinsic with the raw is signed by the public key
+fn extrinsic_signed_by(extrinsic: &Value, public_key: &[u8; 32], shard_count: u16) -> bool {
+	let raw: Option<Hex<Vec<u8>>> = serde_json::from_value(extrinsic["raw"].clone()).ok();
+	match raw {
+		Some(raw) => match get_extrinsic_signer(&raw.0, shard_count) {
+			Ok((public, _)) => &public == public_key,
+			Err(_) => false,
+		},
+		None => false,
+	}
+}
+
+/// Parse the dry run result of the node, either the encoded `ApplyResult`,
+/// or an object of the encoded `ApplyResult` as `result` and the raw `events`
+fn parse_dry_run_result(value: &Value) -> errors::Result<Value> {
+	let parse_hex = |value: &Value| -> errors::Result<Vec<u8>> {
+		let value: Hex<Vec<u8>> =
+			serde_json::from_value(value.clone()).map_err(|_| errors::ErrorKind::ParseError)?;
+		Ok(value.0)
+	};
+
+	let (apply_result, events) = match value {
+		Value::Object(object) => {
+			let apply_result = object.get("result").ok_or(errors::ErrorKind::ParseError)?;
+			let events = match object.get("events") {
+				Some(Value::Null) | None => Value::Null,
+				Some(events) => decode_events(&parse_hex(events)?)?,
+			};
+			(parse_hex(apply_result)?, events)
+		}
+		value => (parse_hex(value)?, Value::Null),
+	};
+
+	// Result<ApplyOutcome, ApplyError>
+	let result = match &apply_result[..] {
+		[0, 0] => json!({ "applied": true, "success": true }),
+		[0, 1] => json!({ "applied": true, "success": false }),
+		[1, error] => {
+			let error = match error {
+				0 => "BadSignature",
+				1 => "Stale",
+				2 => "Future",
+				3 => "CantPay",
+				255 => "FullBlock",
+				_ => return Err(errors::ErrorKind::ParseError.into()),
+			};
+			json!({ "applied": false, "error": error })
+		}
+		_ => return Err(errors::ErrorKind::ParseError.into()),
+	};
+
+	Ok(json!({
+		"result": result,
+		"events": events,
+	}))
+}
+
+pub fn check_shard_num(shard_num: u16, config: &Config) -> errors::Result<()> {
+	if shard_num >= config.shards.len() as u16 {
+		return Err(errors::ErrorKind::InvalidShard.into());
+	}
+	Ok(())
+}
+
+/// The shard of the methods without the shard num
+fn get_default_shard(config: &Config) -> errors::Result<u16> {
+	config
+		.default_shard
+		.ok_or_else(|| errors::ErrorKind::DefaultShardUnset.into())
+}
+
+/// The best block without the extrinsic results, a failed fetch degrades to none
+fn get_best_block_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+) -> BoxFuture<Option<Value>> {
+	// get the best block
+	let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
+		let result = client::get_best_hash_future(rpc_client.clone(), shard_num);
+		let result = result.map(|x| Ok(x));
+		Box::new(result)
+	};
+	let result = get_block_hash();
+
+	let result = get_block_future(rpc_client, block_cache, shard_num, false, false, result);
+
+	let result = get_option_value_future(result);
+
+	let result = result.then(|x| -> jsonrpc_core::Result<Option<Value>> {
+		Ok(x.ok().and_then(|x| x.ok()).and_then(|x| x))
+	});
+
+	Box::new(result)
+}
+
+/// Seconds since the unix epoch
+fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|x| x.as_secs())
+		.unwrap_or(0)
+}
+
+/// Get the timestamp in seconds set by the timestamp inherent of the block
+fn get_block_timestamp(block: &Value) -> Option<u64> {
+	const TIMESTAMP_MODULE: u64 = 0;
+	const TIMESTAMP_SET_METHOD: u64 = 0;
+
+	block["extrinsics"]
+		.as_array()?
+		.iter()
+		.map(|x| &x["call"])
+		.find(|x| {
+			x["module"].as_u64() == Some(TIMESTAMP_MODULE)
+				&& x["method"].as_u64() == Some(TIMESTAMP_SET_METHOD)
+		})
+		.and_then(|x| x["params"]["now"].as_u64())
+}
+
+/// Seconds until the block is finalized at the rate from the (number, timestamp) `from` to `to`,
+/// none if the rate is unknown or the estimate overflows
+fn estimate_finalization_seconds(
+	number: BlockNumber,
+	finalized: BlockNumber,
+	from: (BlockNumber, u64),
+	to: (BlockNumber, u64),
+) -> Option<u64> {
+	if to.0 <= from.0 || to.1 <= from.1 {
+		return None;
+	}
+	let pending = number.saturating_sub(finalized);
+	pending
+		.checked_mul(to.1 - from.1)
+		.map(|x| x / (to.0 - from.0))
+}
+
+/// Min, max and average seconds between the consecutive blocks of the (number, timestamp)s
+/// ordered by number, the blocks without a timestamp are skipped
+fn get_block_time_stats(timestamps: &[(BlockNumber, u64)]) -> Value {
+	let intervals = timestamps
+		.windows(2)
+		.filter(|x| x[1].0 == x[0].0 + 1)
+		.map(|x| x[1].1.saturating_sub(x[0].1))
+		.collect::<Vec<_>>();
+
+	let average = match intervals.len() {
+		0 => None,
+		len => Some(intervals.iter().sum::<u64>() as f64 / len as f64),
+	};
+
+	json!({
+		"intervals": intervals.len(),
+		"min": intervals.iter().min(),
+		"max": intervals.iter().max(),
+		"average": average,
+	})
+}
+
+/// A shard is healthy if the age of the best block is within `max_block_age` seconds
+fn get_liveness(
+	shard_num: u16,
+	number: Option<BlockNumber>,
+	timestamp: Option<u64>,
+	now: u64,
+	max_block_age: u64,
+) -> Value {
+	let age = timestamp.map(|x| now.saturating_sub(x));
+	let healthy = age.map(|x| x <= max_block_age).unwrap_or(false);
+
+	json!({
+		"shard_num": shard_num,
+		"number": number,
+		"timestamp": timestamp,
+		"age": age,
+		"healthy": healthy,
+	})
+}
+
+/// Check the span of the inclusive range
+fn check_range(from: BlockNumber, to: BlockNumber, max: u64) -> errors::Result<()> {
+	let span = to.saturating_sub(from).saturating_add(1);
+	if from <= to && span > max {
+		return Err(errors::ErrorKind::RangeTooLarge(span, max).into());
+	}
+	Ok(())
+}
+
+/// (public key, shard num) of the address
+fn get_address_shard_num(address: String, shard_count: u16) -> errors::Result<([u8; 32], u16)> {
+	let public_key = decode_address(&address)?;
+
+	let shard_num =
+		shard_num_for_bytes(&public_key, shard_count).ok_or(errors::ErrorKind::InvalidShard)?;
+
+	Ok((public_key, shard_num))
+}
+
+/// The public key of the yee bech32 address, or else of the SS58 address
+fn decode_address(address: &str) -> errors::Result<[u8; 32]> {
+	if let Ok((public_key, _hrp)) = <[u8; 32]>::from_address(&Address(address.to_string())) {
+		return Ok(public_key);
+	}
+
+	let public = sr25519::Public::from_ss58check(address)
+		.map_err(|_| errors::Error::from(errors::ErrorKind::InvalidAddress))?;
+
+	Ok(public.0)
+}
+
+/// The free balance at the block, or at the best block if not set
+fn get_balance_future(
+	rpc_client: Arc<RpcClient>,
+	public_key: &[u8],
+	block_number: Option<BlockNumber>,
+	shard_num: u16,
+	finalized_state: bool,
+) -> BoxFuture<Balance> {
+	let storage_key = get_map_storage_key(public_key, b"Balances FreeBalance");
+	let storage_key = Hex(storage_key.0);
+
+	// get block hash
+	let result =
+		get_state_block_hash_future(rpc_client.clone(), block_number, shard_num, finalized_state);
+
+	// get balance
+	let result = result.and_then(move |block_hash| {
+		let result = client::get_storage_future(rpc_client, &storage_key, &block_hash, shard_num);
+		result.and_then(|x| -> jsonrpc_core::Result<Balance> {
+			match x {
+				Some(x) => Ok(Balance(u128_from_slice(&x.0)?)),
+				None => Ok(Balance(0)),
+			}
+		})
+	});
+
+	Box::new(result)
+}
+
+/// The nonce, the balances and the locks at the block read concurrently, a failed read is null
+fn get_full_account_future(
+	rpc_client: Arc<RpcClient>,
+	public_key: &[u8],
+	block_hash: Option<Hex<Vec<u8>>>,
+	shard_num: u16,
+) -> BoxFuture<Value> {
+	let get_storage =
+		|prefix: &'static [u8], f: fn(Option<Hex<Vec<u8>>>) -> errors::Result<Value>| {
+			let storage_key = get_map_storage_key(public_key, prefix);
+			let storage_key = Hex(storage_key.0);
+			client::get_storage_future(rpc_client.clone(), &storage_key, &block_hash, shard_num)
+				.then(move |x| -> jsonrpc_core::Result<Value> {
+					let value = match x {
+						Ok(x) => f(x).map_err(|e| e.to_string()),
+						Err(e) => Err(e.message),
+					};
+					Ok(value.unwrap_or_else(|e| {
+						let prefix = String::from_utf8_lossy(prefix);
+						warn!("Failed to read {} of shard {}: {}", prefix, shard_num, e);
+						Value::Null
+					}))
+				})
+		};
+
+	let nonce = get_storage(b"System AccountNonce", |x| decode_nonce(x)?.try_into());
+	let balance = |x| -> errors::Result<Value> { decode_balance(x)?.try_into() };
+	let free_balance = get_storage(b"Balances FreeBalance", balance);
+	let reserved_balance = get_storage(b"Balances ReservedBalance", balance);
+	let locks = get_storage(b"Balances Locks", decode_balance_locks);
+
+	let result = nonce.join4(free_balance, reserved_balance, locks).map(
+		move |(nonce, free_balance, reserved_balance, locks)| {
+			json!({
+				"shard_num": shard_num,
+				"nonce": nonce,
+				"free_balance": free_balance,
+				"reserved_balance": reserved_balance,
+				"locks": locks,
+			})
+		},
+	);
+
+	Box::new(result)
+}
+
+/// The stored nonce, 0 if not stored
+fn decode_nonce(value: Option<Hex<Vec<u8>>>) -> errors::Result<Nonce> {
+	match value {
+		Some(x) => Ok(Nonce::from(u64_from_slice(&x.0)?)),
+		None => Ok(Nonce::from(0)),
+	}
+}
+
+/// The stored free or reserved balance, 0 if not stored
+fn decode_balance(value: Option<Hex<Vec<u8>>>) -> errors::Result<Balance> {
+	match value {
+		Some(x) => Ok(Balance(u128_from_slice(&x.0)?)),
+		None => Ok(Balance(0)),
+	}
+}
+
+/// The stored locks, none if not stored
+fn decode_balance_locks(value: Option<Hex<Vec<u8>>>) -> errors::Result<Value> {
+	match value {
+		Some(x) => get_balance_locks(&x.0),
+		None => Ok(Value::Array(vec![])),
+	}
+}
+
+/// The encoded `Vec<BalanceLock>`, the reasons by the flags of the encoded mask
+fn get_balance_locks(raw: &[u8]) -> errors::Result<Value> {
+	let locks: Vec<BalanceLock<u128, u64>> =
+		Decode::decode(&mut &raw[..]).ok_or(errors::ErrorKind::DecodeFailed("locks"))?;
+
+	let locks = locks
+		.into_iter()
+		.map(|lock| {
+			let amount: Value = Balance(lock.amount).try_into()?;
+			let reasons = lock.reasons.encode().first().cloned().unwrap_or_default();
+			Ok(json!({
+				"id": String::from_utf8_lossy(&lock.id),
+				"amount": amount,
+				"until": lock.until,
+				"reasons": get_withdraw_reasons(reasons),
+			}))
+		})
+		.collect::<errors::Result<Vec<_>>>()?;
+
+	Ok(Value::Array(locks))
+}
+
+/// The names of the flags of `WithdrawReasons`
+fn get_withdraw_reasons(reasons: u8) -> Vec<&'static str> {
+	let flags = [
+		(0b0000_0001, "TransactionPayment"),
+		(0b0000_0010, "Transfer"),
+		(0b0000_0100, "Reserve"),
+		(0b0000_1000, "Fee"),
+	];
+
+	flags
+		.iter()
+		.filter(|(flag, _)| reasons & flag != 0)
+		.map(|(_, name)| *name)
+		.collect()
+}
+
+/// The block hash of the state to read, the best state (none) or the finalized one if not set,
+/// not yet produced above the best number
+fn get_state_block_hash_future(
+	rpc_client: Arc<RpcClient>,
+	block_number: Option<BlockNumber>,
+	shard_num: u16,
+	finalized_state: bool,
+) -> BoxFuture<Option<Hex<Vec<u8>>>> {
+	match block_number {
+		Some(block_number) => get_produced_block_hash_future(rpc_client, shard_num, block_number),
+		None if finalized_state => client::get_finalized_hash_future(rpc_client, shard_num),
+		None => Box::new(future::ok(None)),
+	}
+}
+
+/// The nonce and the balances at the block, or at the best block if not set
+fn get_account_info_future(
+	rpc_client: Arc<RpcClient>,
+	public_key: &[u8],
+	block_hash: Option<Hex<Vec<u8>>>,
+	shard_num: u16,
+) -> BoxFuture<Value> {
+	let get_storage = |prefix: &[u8]| {
+		let storage_key = get_map_storage_key(public_key, prefix);
+		let storage_key = Hex(storage_key.0);
+		client::get_storage_future(rpc_client.clone(), &storage_key, &block_hash, shard_num)
+	};
+
+	let nonce = get_storage(b"System AccountNonce")
+		.and_then(|x| -> jsonrpc_core::Result<Nonce> { Ok(decode_nonce(x)?) });
+	let balance = |x| -> jsonrpc_core::Result<Balance> { Ok(decode_balance(x)?) };
+	let free_balance = get_storage(b"Balances FreeBalance").and_then(balance);
+	let reserved_balance = get_storage(b"Balances ReservedBalance").and_then(balance);
+
+	let result = nonce.join3(free_balance, reserved_balance).and_then(
+		move |(nonce, free_balance, reserved_balance)| -> jsonrpc_core::Result<Value> {
+			let nonce: Value = nonce.try_into()?;
+			let free_balance: Value = free_balance.try_into()?;
+			let reserved_balance: Value = reserved_balance.try_into()?;
+			Ok(json!({
+				"shard_num": shard_num,
+				"nonce": nonce,
+				"free_balance": free_balance,
+				"reserved_balance": reserved_balance,
+			}))
+		},
+	);
+
+	Box::new(result)
+}
+
+/// The on-chain nonce moved past the pending extrinsics of the signer in a row,
+/// the pending extrinsics after a gap do not count
+fn get_next_nonce(nonce: u64, pending: &[Hex<Vec<u8>>], public_key: &[u8; 32]) -> u64 {
+	let pending_nonces = pending
+		.iter()
+		.filter_map(|raw| {
+			let tx: Transaction = Decode::decode(&mut &raw.0[..])?;
+			match tx.signature {
+				Some((address, _, nonce, _)) if address.0[1..] == public_key[..] => Some(nonce.0),
+				_ => None,
+			}
+		})
+		.collect::<HashSet<_>>();
+
+	let mut next = nonce;
+	while pending_nonces.contains(&next) {
+		next += 1;
+	}
+	next
+}
+
+/// Every `step`-th block number of the inclusive range
+fn get_sample_numbers(
+	from: BlockNumber,
+	to: BlockNumber,
+	step: BlockNumber,
+	max: usize,
+) -> errors::Result<Vec<BlockNumber>> {
+	if step == 0 {
+		return Err(errors::ErrorKind::ParseError.into());
+	}
+	if from > to {
+		return Ok(vec![]);
+	}
+
+	// the whole chain sampled by the step 1
+	let count = ((to - from) / step)
+		.checked_add(1)
+		.ok_or(errors::ErrorKind::TooManySamples(
+			u64::max_value(),
+			max as u64,
+		))?;
+	if count > max as u64 {
+		return Err(errors::ErrorKind::TooManySamples(count, max as u64).into());
+	}
+
+	let numbers = (0..count).map(|i| from + i * step).collect();
+
+	Ok(numbers)
+}
+
+#[derive(Debug, PartialEq)]
+enum BlockId {
+	Number(BlockNumber),
+	Hash(Hex<Vec<u8>>),
+}
+
+/// A number, or a hex string of a 32 bytes hash
+fn parse_block_id(at: &Value) -> errors::Result<BlockId> {
+	const HASH_LEN: usize = 32;
+
+	match at {
+		Value::Number(number) => {
+			let number = number.as_u64().ok_or(errors::ErrorKind::ParseError)?;
+			Ok(BlockId::Number(number))
+		}
+		Value::String(hash) if hash.starts_with("0x") => {
+			let hash = hex::decode(&hash[2..]).map_err(|_| errors::ErrorKind::ParseError)?;
+			if hash.len() != HASH_LEN {
+				return Err(errors::ErrorKind::ParseError.into());
+			}
+			Ok(BlockId::Hash(Hex(hash)))
+		}
+		_ => Err(errors::ErrorKind::ParseError.into()),
+	}
+}
+
+fn get_block_by_number_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+	number: BlockNumber,
+	with_success: bool,
+	with_raw: bool,
+) -> BoxFuture<Option<Value>> {
+	// get block hash
+	let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
+		let result = get_produced_block_hash_future(rpc_client.clone(), shard_num, number);
+		let result = result.map(|x| Ok(x));
+		Box::new(result)
+	};
+	let result = get_block_hash();
+
+	let result = get_block_future(
+		rpc_client,
+		block_cache,
+		shard_num,
+		with_raw,
+		with_success,
+		result,
+	);
+
+	let result = get_option_value_future(result);
+
+	let result = get_value_with_address_future(result);
+
+	let result = result.and_then(|x| match x {
+		Ok(v) => future::ok(v),
+		Err(e) => future::err(e),
+	});
+
+	Box::new(result)
+}
+
+/// The block hash by number, the numbers above the best number are not yet produced
+fn get_produced_block_hash_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+	number: BlockNumber,
+) -> BoxFuture<Option<Hex<Vec<u8>>>> {
+	let result = client::get_block_hash_future(rpc_client.clone(), number, shard_num);
+
+	let result = result.and_then(move |hash| -> BoxFuture<Option<Hex<Vec<u8>>>> {
+		match hash {
+			Some(hash) => Box::new(future::ok(Some(hash))),
+			None => {
+				let result = client::get_header_future(rpc_client, &None, shard_num);
+				let result = result.and_then(move |header| -> jsonrpc_core::Result<_> {
+					check_produced(number, header.map(|x| x.number))?;
+					Ok(None)
+				});
+				Box::new(result)
+			}
+		}
+	});
+
+	Box::new(result)
+}
+
+/// The block number must be at or below the best number if known
+fn check_produced(number: BlockNumber, best: Option<BlockNumber>) -> errors::Result<()> {
+	match best {
+		Some(best) if number > best => {
+			Err(errors::ErrorKind::BlockNotYetProduced(number, best).into())
+		}
+		_ => Ok(()),
+	}
+}
+
+/// The block number must be at most `margin` above the best number if known
+fn check_block_number_margin(
+	number: BlockNumber,
+	best: Option<BlockNumber>,
+	margin: u64,
+) -> errors::Result<()> {
+	match best {
+		Some(best) if number > best.saturating_add(margin) => {
+			Err(errors::ErrorKind::BlockNotYetProduced(number, best).into())
+		}
+		_ => Ok(()),
+	}
+}
+
+/// The block number must be at or below the finalized number
+fn check_finalized(number: BlockNumber, finalized: Option<BlockNumber>) -> errors::Result<()> {
+	match finalized {
+		Some(finalized) if number <= finalized => Ok(()),
+		_ => Err(errors::ErrorKind::NotFinalized(number, finalized).into()),
+	}
+}
+
+/// The blocks where the value differs from the previous block of the range
+fn get_storage_changes(from: BlockNumber, values: Vec<Option<Hex<Vec<u8>>>>) -> Value {
+	let changes = values
+		.windows(2)
+		.enumerate()
+		.filter(|(_, x)| x[0] != x[1])
+		.map(|(i, x)| {
+			json!({
+				"block_number": from + i as BlockNumber + 1,
+				"before": x[0],
+				"after": x[1],
+			})
+		})
+		.collect::<Vec<_>>();
+	Value::Array(changes)
+}
+
+fn check_storage_keys(count: usize, max: usize) -> errors::Result<()> {
+	if count > max {
+		return Err(errors::ErrorKind::TooManyStorageKeys(count, max).into());
+	}
+	Ok(())
+}
+
+fn check_batch_addresses(count: usize, max: usize) -> errors::Result<()> {
+	if count > max {
+		return Err(errors::ErrorKind::RangeTooLarge(count as u64, max as u64).into());
+	}
+	Ok(())
+}
+
+fn check_block_numbers(count: usize, max: usize) -> errors::Result<()> {
+	if count > max {
+		return Err(errors::ErrorKind::TooManyBlockNumbers(count, max).into());
+	}
+	Ok(())
+}
+
+fn check_samples(count: u64, max: usize) -> errors::Result<()> {
+	if count > max as u64 {
+		return Err(errors::ErrorKind::TooManySamples(count, max as u64).into());
+	}
+	Ok(())
+}
+
+fn check_hash_list(count: usize, max: usize) -> errors::Result<()> {
+	if count > max {
+		return Err(errors::ErrorKind::HashListTooLong(count, max).into());
+	}
+	Ok(())
+}
+
+fn get_header_by_number_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+	number: BlockNumber,
+) -> BoxFuture<Option<ResultHeader>> {
+	let result = client::get_block_hash_future(rpc_client.clone(), number, shard_num);
+
+	let result = result.and_then(move |hash| get_result_header_future(rpc_client, shard_num, hash));
+
+	Box::new(result)
+}
+
+/// Run at most `max` of the futures at a time, the results in the order of the futures, 0 to disable
+fn join_limited<F>(futures: Vec<F>, max: usize) -> BoxFuture<Vec<F::Item>>
+where
+	F: Future<Error = jsonrpc_core::Error> + Send + 'static,
+	F::Item: Send + 'static,
+{
+	if max == 0 {
+		return Box::new(future::join_all(futures));
+	}
+
+	let result = stream::iter_ok::<_, jsonrpc_core::Error>(futures)
+		.buffered(max)
+		.collect();
+
+	Box::new(result)
+}
+
+/// The header of the block hash, with the block hash set
+fn get_result_header_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+	hash: Option<Hex<Vec<u8>>>,
+) -> BoxFuture<Option<ResultHeader>> {
+	let header = client::get_header_future(rpc_client, &hash, shard_num);
+
+	let result = header.map(|header| match (header, hash) {
+		(Some(header), Some(hash)) => {
+			let mut header: ResultHeader = header.into();
+			header.block_hash = Some(hash);
+			Some(header)
+		}
+		_ => None,
+	});
+
+	Box::new(result)
+}
+
+/// The projection of the value to the fields if set
+fn get_projected_future(
+	result: BoxFuture<Option<Value>>,
+	fields: Option<Vec<String>>,
+) -> BoxFuture<Option<Value>> {
+	match fields {
+		Some(fields) => Box::new(result.map(move |x| x.map(|x| projection::project(&x, &fields)))),
+		None => result,
+	}
+}
+
+/// The hex of the SCALE encoded header from the node
+fn get_scale_header_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+	hash: Option<Hex<Vec<u8>>>,
+) -> BoxFuture<Option<Value>> {
+	let hash = match hash {
+		Some(hash) => hash,
+		None => return Box::new(future::ok(None)),
+	};
+
+	let result = client::get_header_future(rpc_client, &Some(hash), shard_num);
+
+	let result = result.map(|x| x.map(|header| json!(Hex(header.encode()))));
+
+	Box::new(result)
+}
+
+/// The routing of the addresses to the shards, as done by `shard_num_for_bytes`
+fn get_shard_layout(shard_count: u16, hrp: Hrp) -> Value {
+	// the shard count is a power of two
+	let shard_bits = match shard_count {
+		x if x.is_power_of_two() => Some(x.trailing_zeros()),
+		_ => None,
+	};
+
+	let public_key = EXAMPLE_PUBLIC_KEY;
+	let address = public_key.to_vec().to_address(hrp).ok().map(|x| x.0);
+	let shard_num = shard_bits.and_then(|_| shard_num_for_bytes(&public_key, shard_count));
+
+	json!({
+		"shard_count": shard_count,
+		"shard_bits": shard_bits,
+		"rule": "shard_num is the low shard_bits bits of the last two bytes of the public key",
+		"example": {
+			"address": address,
+			"public_key": Hex(public_key.to_vec()),
+			"shard_num": shard_num,
+		},
+	})
+}
+
+/// The bech32 hrp is the part of an address before the last separator `1`
+fn get_hrp_str(hrp: Hrp) -> Option<String> {
+	let address = EXAMPLE_PUBLIC_KEY.to_vec().to_address(hrp).ok()?.0;
+
+	address.rfind('1').map(|i| address[..i].to_string())
+}
+
+/// The address of the coinbase in the pow seal of the header digest, the seal data starts with the coinbase
+fn get_block_author(header: &Header, hrp: Hrp) -> Option<String> {
+	header
+		.digest
+		.logs
+		.iter()
+		.filter_map(|log| {
+			let item: Option<DigestItem<Hash, AuthorityId, ()>> = Decode::decode(&mut &log.0[..]);
+			match item {
+				Some(DigestItem::Consensus(engine_id, data)) if engine_id == YEE_POW_ENGINE_ID => {
+					let coinbase: Option<[u8; 32]> = Decode::decode(&mut &data[..]);
+					coinbase
+				}
+				_ => None,
+			}
+		})
+		.next()
+		.and_then(|coinbase| coinbase.to_vec().to_address(hrp).ok())
+		.map(|address| address.0)
+}
+
+/// The item type of the pre-runtime digest, not known by the digest item of the runtime
+const PRE_RUNTIME_DIGEST_TYPE: u8 = 6;
+
+/// The type and the payload of the SCALE encoded digest log, `unknown` if not decoded
+fn get_digest_log(log: &[u8]) -> Value {
+	let payload = Hex(log.get(1..).unwrap_or_default().to_vec());
+
+	if log.first() == Some(&PRE_RUNTIME_DIGEST_TYPE) {
+		let pre_runtime: Option<([u8; 4], Vec<u8>)> = Decode::decode(&mut &log[1..]);
+		return match pre_runtime {
+			Some((engine_id, data)) => json!({
+				"type": "pre_runtime",
+				"engine_id": String::from_utf8_lossy(&engine_id),
+				"data": Hex(data),
+			}),
+			None => json!({
+				"type": "unknown",
+				"data": payload,
+			}),
+		};
+	}
+
+	let item: Option<DigestItem<Hash, AuthorityId, ()>> = Decode::decode(&mut &log[..]);
+	match item {
+		Some(DigestItem::ChangesTrieRoot(root)) => json!({
+			"type": "changes_trie_root",
+			"data": Hex(root.encode()),
+		}),
+		Some(DigestItem::AuthoritiesChange(authorities)) => json!({
+			"type": "authorities_change",
+			"authorities": authorities.iter().map(|x| Hex(x.encode())).collect::<Vec<_>>(),
+			"data": payload,
+		}),
+		Some(DigestItem::Consensus(engine_id, data)) => json!({
+			"type": "consensus",
+			"engine_id": String::from_utf8_lossy(&engine_id),
+			"data": Hex(data),
+		}),
+		Some(DigestItem::Seal(..)) => json!({
+			"type": "seal",
+			"data": payload,
+		}),
+		Some(DigestItem::Other(data)) => json!({
+			"type": "other",
+			"data": Hex(data),
+		}),
+		_ => json!({
+			"type": "unknown",
+			"data": payload,
+		}),
+	}
+}
+
+/// The hex of the SCALE encoded block from the node
+fn get_scale_block_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+	hash: Option<Hex<Vec<u8>>>,
+) -> BoxFuture<Option<Value>> {
+	let hash = match hash {
+		Some(hash) => hash,
+		None => return Box::new(future::ok(None)),
+	};
+
+	let result = client::get_block_future(rpc_client, &Some(hash), shard_num);
+
+	let result = result.and_then(|x| -> jsonrpc_core::Result<Option<Value>> {
+		match x {
+			Some(x) => Ok(Some(json!(Hex(x.block.encode_scale()?)))),
+			None => Ok(None),
+		}
+	});
+
+	Box::new(result)
+}
+
+/// Result of an extrinsic collected from the block events
+#[derive(Default)]
+struct ExtrinsicResult {
+	success: bool,
+	events: Vec<String>,
+	fee: Option<u128>,
+}
+
+fn get_block_extrinsics_result(
+	events: Option<Hex<Vec<u8>>>,
+) -> errors::Result<HashMap<u32, ExtrinsicResult>> {
+	let mut result: HashMap<u32, ExtrinsicResult> = HashMap::new();
+
+	let events = match events {
+		Some(events) => events,
+		None => return Ok(result),
+	};
+
+	let events: Vec<EventRecord<Event>> =
+		Decode::decode(&mut &events.0[..]).ok_or(errors::ErrorKind::DecodeFailed("events"))?;
+
+	for event in events.into_iter() {
+		match event.phase {
+			Phase::ApplyExtrinsic(index) => {
+				let entry = result.entry(index).or_insert_with(Default::default);
+				match &event.event {
+					Event::system(system_event) => {
+						entry.success = match system_event {
+							srml_system::Event::ExtrinsicSuccess => true,
+							srml_system::Event::ExtrinsicFailed => false,
+						};
+					}
+					// the transfer event carries the fee charged
+					Event::balances(yee_srml_balances::RawEvent::Transfer(_, _, _, fee)) => {
+						entry.fee = Some(*fee);
+						entry.events.push(format!("{:?}", event.event));
+					}
+					_ => {
+						entry.events.push(format!("{:?}", event.event));
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	Ok(result)
+}
+
+/// Decode the SCALE header of the node, the block hash is the hash of the raw
+fn decode_header(raw: &[u8]) -> errors::Result<Value> {
+	let decode_failed = || errors::Error::from(errors::ErrorKind::DecodeFailed("header"));
+
+	let mut input = &raw[..];
+	let parent_hash: Hash = Decode::decode(&mut input).ok_or_else(decode_failed)?;
+	let number: Compact<BlockNumber> = Decode::decode(&mut input).ok_or_else(decode_failed)?;
+	let state_root: Hash = Decode::decode(&mut input).ok_or_else(decode_failed)?;
+	let extrinsics_root: Hash = Decode::decode(&mut input).ok_or_else(decode_failed)?;
+	let _logs: Vec<DigestItem<Hash, AuthorityId, ()>> =
+		Decode::decode(&mut input).ok_or_else(decode_failed)?;
+	if !input.is_empty() {
+		return Err(decode_failed());
+	}
+
+	let header = ResultHeader {
+		block_hash: Some(Hex(blake2_256(raw).to_vec())),
+		extrinsics_root: extrinsics_root.as_ref().to_vec(),
+		parent_hash: parent_hash.as_ref().to_vec(),
+		state_root: state_root.as_ref().to_vec(),
+		number: number.0,
+	};
+
+	Ok(json!(header))
+}
+
+/// Decode the raw `System Events` into the structured events
+fn decode_events(raw: &[u8]) -> errors::Result<Value> {
+	let events: Vec<EventRecord<Event>> =
+		Decode::decode(&mut &raw[..]).ok_or(errors::ErrorKind::DecodeFailed("events"))?;
+
+	let events = events
+		.into_iter()
+		.map(|event| {
+			let phase = match event.phase {
+				Phase::ApplyExtrinsic(index) => json!({ "applyExtrinsic": index }),
+				Phase::Finalization => json!("finalization"),
+			};
+			json!({
+				"phase": phase,
+				"event": format!("{:?}", event.event),
+			})
+		})
+		.collect::<Vec<_>>();
+
+	Ok(Value::Array(events))
+}
+
+/// Without `with_success` the `System Events` read is skipped and the block is not cached
+fn get_block_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+	with_raw: bool,
+	with_success: bool,
+	hash_future: BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>> {
+	// get block from cache or node
+	let get_block = move || -> BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>> {
+		let result = hash_future.and_then(move |x| match x {
+			Ok(Some(hash)) => match block_cache.get(shard_num, &hash.0) {
+				Some(block) => Box::new(future::ok(Ok(Some(block))))
+					as BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>>,
+				None if with_success => {
+					let result =
+						get_block_with_extrinsic_result_future(rpc_client.clone(), shard_num, hash);
+					cache_block_future(rpc_client, block_cache, shard_num, result)
+				}
+				None => get_block_without_extrinsic_result_future(rpc_client, shard_num, hash),
+			},
+			Ok(None) => Box::new(future::ok(Ok(None))),
+			Err(e) => Box::new(future::err(e)),
+		});
+		Box::new(result)
+	};
+	let result = get_block();
+
+	// strip raw
+	let result = result.map(move |x| {
+		x.map(|x| {
+			x.map(|mut block| {
+				if !with_raw {
+					for tx in block.extrinsics.iter_mut() {
+						tx.raw = None;
+					}
+				}
+				block
+			})
+		})
+	});
+
+	Box::new(result)
+}
+
+fn get_block_without_extrinsic_result_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+	hash: Hex<Vec<u8>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>> {
+	let result = client::get_block_future(rpc_client, &Some(hash.clone()), shard_num);
+	let result = result.map(|x| -> jsonrpc_core::Result<Option<ResultBlock>> {
+		let block = match x {
+			Some(block) => block,
+			None => return Ok(None),
+		};
+		let mut block: ResultBlock = block.block.try_into()?;
+		block.header.block_hash = Some(hash);
+		Ok(Some(block))
+	});
+
+	Box::new(result)
+}
+
+fn get_block_with_extrinsic_result_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+	hash: Hex<Vec<u8>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>> {
+	let result = get_block_with_events_future(rpc_client, shard_num, hash);
+
+	let result = result.map(|x| x.map(|x| x.map(|(block, _events)| block)));
+
+	Box::new(result)
+}
+
+fn get_events_storage_key() -> Hex<Vec<u8>> {
+	let events_storage_key = get_value_storage_key(b"System Events");
+	Hex(events_storage_key.0)
+}
+
+/// The block with the extrinsic results and the raw `System Events` they are read from
+type BlockWithEvents = (ResultBlock, Option<Hex<Vec<u8>>>);
+
+fn get_block_with_events_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+	hash: Hex<Vec<u8>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<BlockWithEvents>>> {
+	// get block
+	let result = get_block_without_extrinsic_result_future(rpc_client.clone(), shard_num, hash);
+
+	// get block with extrinsic result
+	let get_block_with_extrinsic_result =
+		move || -> BoxFuture<jsonrpc_core::Result<Option<BlockWithEvents>>> {
+			let result = result.and_then(move |x| match x {
+				Ok(Some(mut block)) => {
+					let events_storage_key = &get_events_storage_key();
+					let block_hash = &Some(block.header.block_hash.as_ref().expect("qed").clone());
+					let events = client::get_storage_future(
+						rpc_client,
+						events_storage_key,
+						block_hash,
+						shard_num,
+					);
+					let result =
+						events.map(move |x| -> jsonrpc_core::Result<Option<BlockWithEvents>> {
+							let result = get_block_extrinsics_result(x.clone())?;
+							for (index, tx) in &mut block.extrinsics.iter_mut().enumerate() {
+								let result = result.get(&(index as u32));
+								tx.success = result.map(|x| x.success);
+								tx.fee = result.and_then(|x| x.fee);
+							}
+							Ok(Some((block, x)))
+						});
+					Box::new(result) as BoxFuture<jsonrpc_core::Result<Option<BlockWithEvents>>>
+				}
+				Ok(None) => Box::new(future::ok(Ok(None))),
+				Err(e) => Box::new(future::err(e)),
+			});
+			Box::new(result)
+		};
+	let result = get_block_with_extrinsic_result();
+
+	Box::new(result)
+}
+
+/// The block through the block cache, the raw events are read from the node once either way
+fn get_cached_block_with_events_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+	hash: Hex<Vec<u8>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<BlockWithEvents>>> {
+	if let Some(block) = block_cache.get(shard_num, &hash.0) {
+		let events = client::get_storage_future(
+			rpc_client,
+			&get_events_storage_key(),
+			&Some(hash),
+			shard_num,
+		);
+		return Box::new(events.map(move |events| Ok(Some((block, events)))));
+	}
+
+	let result = get_block_with_events_future(rpc_client.clone(), shard_num, hash);
+
+	let result = result.and_then(
+		move |x| -> BoxFuture<jsonrpc_core::Result<Option<BlockWithEvents>>> {
+			let (block, events) = match x {
+				Ok(Some(x)) => x,
+				other => return Box::new(future::ok(other)),
+			};
+			let result = cache_block_future(
+				rpc_client,
+				block_cache,
+				shard_num,
+				Box::new(future::ok(Ok(Some(block)))),
+			);
+			Box::new(result.map(move |x| x.map(|x| x.map(|block| (block, events)))))
+		},
+	);
+
+	Box::new(result)
+}
+
+/// Cache the block if it is finalized, blocks above the finalized number may be reorganized
+fn cache_block_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+	block_future: BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>> {
+	let result = block_future.and_then(move |x| match x {
+		Ok(Some(block)) => {
+			if block_cache.is_finalized(shard_num, block.header.number) {
+				block_cache.insert(shard_num, block.clone());
+				return Box::new(future::ok(Ok(Some(block))))
+					as BoxFuture<jsonrpc_core::Result<Option<ResultBlock>>>;
+			}
+
+			// the block may have been finalized since the last refresh
+			if !block_cache.should_refresh_finalized(shard_num) {
+				return Box::new(future::ok(Ok(Some(block))));
+			}
+			let result = get_finalized_number_future(rpc_client, shard_num);
+			let result = result.then(
+				move |finalized| -> jsonrpc_core::Result<jsonrpc_core::Result<Option<ResultBlock>>> {
+					if let Ok(Some(finalized)) = finalized {
+						block_cache.set_finalized_number(shard_num, finalized);
+						if block_cache.is_finalized(shard_num, block.header.number) {
+							block_cache.insert(shard_num, block.clone());
+						}
+					}
+					Ok(Ok(Some(block)))
+				},
+			);
+			Box::new(result)
+		}
+		other => Box::new(future::ok(other)),
+	});
+
+	Box::new(result)
+}
+
+fn get_finalized_number_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+) -> BoxFuture<Option<BlockNumber>> {
+	let result = client::get_finalized_hash_future(rpc_client.clone(), shard_num);
+
+	let result = result.and_then(move |hash| {
+		let header = client::get_header_future(rpc_client, &hash, shard_num);
+		header
+	});
+
+	let result = result.map(|x| x.map(|x| x.number));
+
+	Box::new(result)
+}
+
+/// Get the best and finalized number, a failed fetch degrades to none
+pub fn get_head_future(rpc_client: Arc<RpcClient>, shard_num: u16) -> BoxFuture<Head> {
+	let best = client::get_header_future(rpc_client.clone(), &None, shard_num)
+		.map(|x| x.map(|x| x.number))
+		.then(|x| -> jsonrpc_core::Result<Option<BlockNumber>> { Ok(x.ok().and_then(|x| x)) });
+	let finalized = get_finalized_number_future(rpc_client, shard_num)
+		.then(|x| -> jsonrpc_core::Result<Option<BlockNumber>> { Ok(x.ok().and_then(|x| x)) });
+
+	let result = best
+		.join(finalized)
+		.map(|(best, finalized)| Head::new(best, finalized));
+
+	Box::new(result)
+}
+
+fn get_option_value_future<T>(
+	future: BoxFuture<jsonrpc_core::Result<Option<T>>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<Value>>>
+where
+	T: TryInto<Value> + 'static,
+{
+	// convert to value to avoid jsonrpc u128 serialize problem
+	let convert_to_value = || -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
+		let result = future.map(|x| match x {
+			Ok(Some(o)) => {
+				let result: Value = o
+					.try_into()
+					.map_err(|_| errors::Error::from(errors::ErrorKind::ParseError))?;
+				Ok(Some(result))
+			}
+			Ok(None) => Ok(None),
+			Err(e) => Err(e),
+		});
+		Box::new(result)
+	};
+	let result = convert_to_value();
+
+	Box::new(result)
+}
+
+fn get_value_future<T>(
+	future: BoxFuture<jsonrpc_core::Result<T>>,
+) -> BoxFuture<jsonrpc_core::Result<Value>>
+where
+	T: TryInto<Value> + 'static,
+{
+	// convert to value to avoid jsonrpc u128 serialize problem
+	let convert_to_value = || -> BoxFuture<jsonrpc_core::Result<Value>> {
+		let result = future.map(|x| match x {
+			Ok(o) => {
+				let result: Value = o
+					.try_into()
+					.map_err(|_| errors::Error::from(errors::ErrorKind::ParseError))?;
+				Ok(result)
+			}
+			Err(e) => Err(e),
+		});
+		Box::new(result)
+	};
+	let result = convert_to_value();
+
+	Box::new(result)
+}
+
+fn get_value_with_address_future(
+	future: BoxFuture<jsonrpc_core::Result<Option<Value>>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
+	let hrp = HRP.read().expect("qed").clone();
+	let shard_count = SHARD_COUNT.read().expect("qed").clone();
+
+	let provide_address = move || -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
+		let result = future.map(move |x| match x {
+			Ok(Some(mut value)) => {
+				// process block
+				match value.get_mut("extrinsics") {
+					Some(extrinsics) => match extrinsics.as_array_mut() {
+						Some(extrinsics) => {
+							for extrinsic in extrinsics {
+								extrinsic_append_address(extrinsic, hrp.clone(), shard_count)
+							}
+						}
+						None => (),
+					},
+					None => (),
+				}
+
+				// process extrinsic
+				match value.get("call") {
+					Some(_) => extrinsic_append_address(&mut value, hrp.clone(), shard_count),
+					None => (),
+				}
+
+				Ok(Some(value))
+			}
+			Ok(None) => Ok(None),
+			Err(e) => Err(e),
+		});
+		Box::new(result)
+	};
+	let result = provide_address();
+
+	Box::new(result)
+}
+
+/// The names of the call by the table of the known calls, null if unknown
+fn describe_call(module: u8, method: u8) -> Value {
+	CALLS
+		.iter()
+		.find(|(x, y, ..)| *x == module && *y == method)
+		.map(|(_, _, module_name, method_name, params)| {
+			json!({
+				"module": module,
+				"method": method,
+				"module_name": module_name,
+				"method_name": method_name,
+				"params": params,
+			})
+		})
+		.unwrap_or(Value::Null)
+}
+
+fn extrinsic_append_address(extrinsic: &mut Value, hrp: Hrp, shard_count: u16) {
+	let call = &mut extrinsic["call"];
+	let module = call.get("module").and_then(|x| x.as_u64());
+	let method = call.get("method").and_then(|x| x.as_u64());
+	match (module, method) {
+		(Some(4), Some(0)) => {
+			let params = &mut call["params"];
+			if let Some(dest) = params["dest"].as_str() {
+				let dest = dest.trim_start_matches("0x");
+				match hex::decode(dest) {
+					Ok(dest) => {
+						if dest[0] == 0xFF {
+							let public = dest[1..].to_vec();
+							let shard_num = shard_num_for_bytes(&public, shard_count).expect("qed");
+							let address = public.to_address(hrp).expect("qed");
+							params["dest_address"] = Value::String(address.0);
+							params["dest_shard_num"] = Value::Number(shard_num.into());
+						}
+					}
+					Err(_) => (),
+				}
+			}
+		}
+		_ => (),
+	}
+}
+
+fn get_extrinsic_by_hash_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+	block_number: BlockNumber,
+	hash: Hex<Vec<u8>>,
+	with_raw: bool,
+) -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
+	// get block hash
+	let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
+		let result = client::get_block_hash_future(rpc_client.clone(), block_number, shard_num);
+		let result = result.map(|x| Ok(x));
+		Box::new(result)
+	};
+	let result = get_block_hash();
+
+	let result = get_block_future(rpc_client, block_cache, shard_num, with_raw, true, result);
+
+	// filter
+	let filter = move || -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
+		let result = result.map(move |x| match x {
+			Ok(Some(block)) => {
+				let extrinsic = block
+					.extrinsics
+					.into_iter()
+					.filter_map(|tx| {
+						if tx.hash.as_ref() == Some(&hash) {
+							Some(tx)
+						} else {
+							None
+						}
+					})
+					.next();
+				Ok(extrinsic)
+			}
+			Ok(None) => Ok(None),
+			Err(e) => Err(e),
+		});
+		Box::new(result)
+	};
+	let result = filter();
+
+	Box::new(result)
+}
+
+/// The extrinsic of the raw in the block, the raw stripped
+fn get_extrinsic_by_raw_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+	block_number: BlockNumber,
+	raw: Hex<Vec<u8>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
+	// get block hash
+	let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
+		let result = client::get_block_hash_future(rpc_client.clone(), block_number, shard_num);
+		let result = result.map(|x| Ok(x));
+		Box::new(result)
+	};
+	let result = get_block_hash();
+
+	let result = get_block_future(rpc_client, block_cache, shard_num, true, true, result);
+
+	// filter
+	let filter = move || -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
+		let result = result.map(move |x| match x {
+			Ok(Some(block)) => {
+				let extrinsic = block
+					.extrinsics
+					.into_iter()
+					.filter_map(|tx| {
+						if tx.raw.as_ref() == Some(&raw) {
+							Some(tx)
+						} else {
+							None
+						}
+					})
+					.next();
+				let extrinsic = extrinsic.map(|mut x| {
+					x.raw = None;
+					x
+				});
+				Ok(extrinsic)
+			}
+			Ok(None) => Ok(None),
+			Err(e) => Err(e),
+		});
+		Box::new(result)
+	};
+	let result = filter();
+
+	Box::new(result)
+}
+
+fn get_extrinsic_by_origin_hash_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+	block_number: BlockNumber,
+	origin_hash: Hex<Vec<u8>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
+	// get block hash
+	let get_block_hash = || -> BoxFuture<jsonrpc_core::Result<Option<Hex<Vec<u8>>>>> {
+		let result = client::get_block_hash_future(rpc_client.clone(), block_number, shard_num);
+		let result = result.map(|x| Ok(x));
+		Box::new(result)
+	};
+	let result = get_block_hash();
+
+	let result = get_block_future(
+		rpc_client.clone(),
+		block_cache,
+		shard_num,
+		false,
+		true,
+		result,
+	);
+
+	// filter
+	let filter = move || -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
+		let result = result.map(move |x| match x {
+			Ok(Some(block)) => {
+				let extrinsic = block
+					.extrinsics
+					.into_iter()
+					.filter_map(|mut tx| match get_relay_origin_hash(&tx.call) {
+						Some(origin_tx_hash) if origin_tx_hash == origin_hash.0 => {
+							tx.block_number = Some(block_number);
+							Some(tx)
+						}
+						_ => None,
+					})
+					.next();
+				Ok(extrinsic)
+			}
+			Ok(None) => Ok(None),
+			Err(e) => Err(e),
+		});
+		Box::new(result)
+	};
+	let result = filter();
+
+	Box::new(result)
+}
+
+/// The hash of the origin transaction of the relay transfer
+fn get_relay_origin_hash(call: &Call) -> Option<Vec<u8>> {
+	match call {
+		Call::Relay(relay::Call::Transfer(transfer)) => Some(blake2_256(&transfer.tx.0).to_vec()),
+		_ => None,
+	}
+}
+
+fn get_extrinsic_by_origin_hash_in_range_future(
+	rpc_client: Arc<RpcClient>,
+	block_cache: Arc<BlockCache>,
+	shard_num: u16,
+	from_block_number: BlockNumber,
+	to_block_number: BlockNumber,
+	origin_hash: Hex<Vec<u8>>,
+) -> BoxFuture<jsonrpc_core::Result<Option<ResultTransaction>>> {
+	let result = future::loop_fn(from_block_number, move |block_number| {
+		get_extrinsic_by_origin_hash_future(
+			rpc_client.clone(),
+			block_cache.clone(),
+			shard_num,
+			block_number,
+			origin_hash.clone(),
+		)
+		.and_then(move |x| {
+			x.map(|x| match x {
+				Some(tx) => {
+					let tx: jsonrpc_core::Result<Option<ResultTransaction>> = Ok(Some(tx));
+					Loop::Break(tx)
+				}
+				None => {
+					if block_number >= to_block_number {
+						Loop::Break(Ok(None))
+					} else {
+						Loop::Continue(block_number + 1)
+					}
+				}
+			})
+		})
+	});
+
+	Box::new(result)
+}
+
+fn get_value_with_tx_decoded_future(
+	future: BoxFuture<jsonrpc_core::Result<Option<Value>>>,
+	max_depth: usize,
+) -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
+	let hrp = HRP.read().expect("qed").clone();
+	let shard_count = SHARD_COUNT.read().expect("qed").clone();
+	let max_depth = match max_depth {
+		0 => usize::MAX,
+		max_depth => max_depth,
+	};
+
+	let provide_tx_decoded = move || -> BoxFuture<jsonrpc_core::Result<Option<Value>>> {
+		let result = future.map(move |x| match x {
+			Ok(Some(mut value)) => {
+				// process extrinsic
+				match value.get("call") {
+					Some(_) => {
+						extrinsic_append_tx_decoded(&mut value, hrp.clone(), shard_count, max_depth)
+					}
+					None => (),
+				}
+
+				Ok(Some(value))
+			}
+			Ok(None) => Ok(None),
+			Err(e) => Err(e),
+		});
+		Box::new(result)
+	};
+	let result = provide_tx_decoded();
+
+	Box::new(result)
+}
+
+/// Decode the nested transactions of the relay extrinsic down to `depth`,
+/// the `tx` beyond is left raw
+fn extrinsic_append_tx_decoded(extrinsic: &mut Value, hrp: Hrp, shard_count: u16, depth: usize) {
+	if depth == 0 {
+		return;
+	}
+	let call = &mut extrinsic["call"];
+	let module = call.get("module").and_then(|x| x.as_u64());
+	let method = call.get("method").and_then(|x| x.as_u64());
+	match (module, method) {
+		(Some(9), Some(0)) => {
+			let params = &mut call["params"];
+			if let Some(tx) = params["tx"].as_str() {
+				let tx = tx.trim_start_matches("0x");
+				let tx: Option<Transaction> = hex::decode(tx)
+					.ok()
+					.and_then(|tx| Decode::decode(&mut &tx[..]));
+				if let Some(tx) = tx {
+					let tx: ResultTransaction = tx.into();
+					let mut tx: Value = tx.try_into().expect("qed");
+					extrinsic_append_address(&mut tx, hrp.clone(), shard_count);
+					extrinsic_append_tx_decoded(&mut tx, hrp, shard_count, depth - 1);
+					params["tx_decoded"] = tx;
+				}
+			}
+		}
+		_ => (),
+	}
+}
+
+fn u64_from_slice(bytes: &[u8]) -> errors::Result<u64> {
+	const LEN: usize = 8;
+	if bytes.len() != LEN {
+		return Err(errors::ErrorKind::ParseError.into());
+	}
+	let mut array = [0; LEN];
+	let bytes = &bytes[..];
+	array.copy_from_slice(bytes);
+	Ok(u64::from_le_bytes(array))
+}
+
+fn u128_from_slice(bytes: &[u8]) -> errors::Result<u128> {
+	const LEN: usize = 16;
+	if bytes.len() != LEN {
+		return Err(errors::ErrorKind::ParseError.into());
+	}
+	let mut array = [0; LEN];
+	let bytes = &bytes[..];
+	array.copy_from_slice(bytes);
+	Ok(u128::from_le_bytes(array))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{Read, Write};
+	use std::net::{TcpListener, TcpStream};
+	use std::sync::{Arc, Mutex, RwLock};
+	use std::thread;
+
+	use futures::future;
+	use futures::Future;
+	use parity_codec::{Compact, Decode, Encode};
+	use runtime_primitives::generic::DigestItem;
+	use serde_json::json;
+	use srml_system::{EventRecord, Phase};
+	use substrate_primitives::blake2_256;
+	use tokio::runtime::Runtime;
+	use yee_consensus_pow_primitives::YEE_POW_ENGINE_ID;
+	use yee_primitives::{AddressCodec, Hrp};
+	use yee_runtime::{AuthorityId, Event, Hash};
+	use yee_sharding_primitives::utils::shard_num_for_bytes;
+
+	use crate::config::{Config, HRP, SHARD_COUNT};
+	use crate::rpc::cache::{Caches, Head};
+	use crate::rpc::client::RpcClient;
+	use crate::rpc::errors;
+	use crate::rpc::serde::Hex;
+	use crate::rpc::types::{get_map_storage_key, Digest, Encoding, Header, NumberOrHex};
+
+	use super::{
+		check_block_number_margin, check_finalized, check_hash_list, check_produced, check_range,
+		check_shard_num, decode_address, decode_events, decode_header, describe_call,
+		estimate_finalization_seconds, extrinsic_append_tx_decoded, get_address_shard_num,
+		get_balance_locks, get_block_author, get_block_extrinsics_result, get_block_future,
+		get_block_time_stats, get_block_timestamp, get_cached_block_with_events_future,
+		get_default_shard, get_digest_log, get_liveness, get_next_nonce, get_sample_numbers,
+		get_shard_layout, get_storage_changes, get_value_with_tx_decoded_future, parse_block_id,
+		parse_dry_run_result, BlockId, Chain, ChainApi, EXAMPLE_PUBLIC_KEY,
+		PRE_RUNTIME_DIGEST_TYPE,
+	};
+
+	#[test]
+	fn test_decode_events() {
+		// [ApplyExtrinsic(0): system::ExtrinsicSuccess, ApplyExtrinsic(1): system::ExtrinsicFailed]
+		let raw = hex::decode("080000000000000000010000000001").unwrap();
+
+		let events = decode_events(&raw).unwrap();
+		let events = events.as_array().unwrap();
+		assert_eq!(events.len(), 2);
+		assert_eq!(events[0]["phase"], json!({ "applyExtrinsic": 0 }));
+		assert!(events[0]["event"]
+			.as_str()
+			.unwrap()
+			.contains("ExtrinsicSuccess"));
+		assert_eq!(events[1]["phase"], json!({ "applyExtrinsic": 1 }));
+		assert!(events[1]["event"]
+			.as_str()
+			.unwrap()
+			.contains("ExtrinsicFailed"));
+
+		match decode_events(&[0x08, 0x00]) {
+			Err(errors::Error(errors::ErrorKind::DecodeFailed("events"), _)) => (),
+			_ => panic!("expected DecodeFailed"),
+		}
+	}
+
+	#[test]
+	fn test_decode_header() {
+		// a header with the roots of the block 394 and a made up seal, encoded as by the node
+		let header = Header {
+			extrinsics_root: hex::decode(
+				"549cf14e3874c61be194dd27e3e930891daf2a1dd4cd60ed7fc39a0923c4a261",
+			)
+			.unwrap(),
+			parent_hash: hex::decode(
+				"f78eb90a94e881b488b8c3a81905d0424e2c55834a819164c7afba2594f43318",
+			)
+			.unwrap(),
+			state_root: hex::decode(
+				"488c4a38429adbe510bf2e37230244f795638b4964d2bfcee6649e38b3343a21",
+			)
+			.unwrap(),
+			number: 394,
+			digest: Digest {
+				logs: vec![Hex(DigestItem::<Hash, AuthorityId, ()>::Consensus(
+					YEE_POW_ENGINE_ID,
+					vec![1u8; 8],
+				)
+				.encode())],
+			},
+		};
+		let raw = header.encode();
+
+		let result = decode_header(&raw).unwrap();
+		assert_eq!(
+			result,
+			json!({
+				"block_hash": format!("0x{}", hex::encode(blake2_256(&raw))),
+				"extrinsics_root": "0x549cf14e3874c61be194dd27e3e930891daf2a1dd4cd60ed7fc39a0923c4a261",
+				"parent_hash": "0xf78eb90a94e881b488b8c3a81905d0424e2c55834a819164c7afba2594f43318",
+				"state_root": "0x488c4a38429adbe510bf2e37230244f795638b4964d2bfcee6649e38b3343a21",
+				"number": 394,
+			})
+		);
+
+		// truncated, or with trailing bytes
+		for raw in &[raw[..40].to_vec(), [&raw[..], &[0u8]].concat()] {
+			match decode_header(raw) {
+				Err(errors::Error(errors::ErrorKind::DecodeFailed("header"), _)) => (),
+				_ => panic!("expected DecodeFailed"),
+			}
+		}
+	}
+
+	#[test]
+	fn test_parse_dry_run_result() {
+		assert_eq!(
+			parse_dry_run_result(&json!("0x0000")).unwrap(),
+			json!({
+				"result": { "applied": true, "success": true },
+				"events": null,
+			})
+		);
+		assert_eq!(
+			parse_dry_run_result(&json!("0x0103")).unwrap()["result"],
+			json!({ "applied": false, "error": "CantPay" })
+		);
+
+		let result = parse_dry_run_result(&json!({
+			"result": "0x0001",
+			"events": "0x080000000000000000010000000001",
+		}))
+		.unwrap();
+		assert_eq!(
+			result["result"],
+			json!({ "applied": true, "success": false })
+		);
+		assert_eq!(result["events"].as_array().unwrap().len(), 2);
+
+		assert!(parse_dry_run_result(&json!("0x02")).is_err());
+		assert!(parse_dry_run_result(&json!(1)).is_err());
+	}
+
+	#[test]
+	fn test_check_range() {
+		assert!(check_range(400, 499, 100).is_ok());
+		assert!(check_range(400, 400, 100).is_ok());
+		assert!(check_range(500, 400, 100).is_ok());
+
+		match check_range(400, 500, 100) {
+			Err(errors::Error(errors::ErrorKind::RangeTooLarge(span, max), _)) => {
+				assert_eq!((span, max), (101, 100));
+			}
+			_ => panic!("expected RangeTooLarge"),
+		}
+		assert!(check_range(0, u64::max_value(), 100).is_err());
+	}
+
+	#[test]
+	fn test_get_liveness() {
+		let block = json!({
+			"header": { "number": 65 },
+			"extrinsics": [
+				{
+					"call": { "module": 0, "method": 0, "params": { "now": 1596874295 } },
+				},
+			],
+		});
+		let timestamp = get_block_timestamp(&block);
+		assert_eq!(timestamp, Some(1596874295));
+
+		let liveness = get_liveness(0, Some(65), timestamp, 1596874300, 60);
+		assert_eq!(liveness["age"], 5);
+		assert_eq!(liveness["healthy"], true);
+
+		// stale
+		let liveness = get_liveness(0, Some(65), timestamp, 1596874400, 60);
+		assert_eq!(liveness["age"], 105);
+		assert_eq!(liveness["healthy"], false);
+
+		// unknown
+		assert_eq!(get_block_timestamp(&json!({ "extrinsics": [] })), None);
+		assert_eq!(
+			get_liveness(0, None, None, 1596874300, 60)["healthy"],
+			false
+		);
+	}
+
+	#[test]
+	fn test_get_block_extrinsics_result() {
+		let events = vec![
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: Event::system(srml_system::Event::ExtrinsicSuccess),
+			},
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(1),
+				event: Event::balances(yee_srml_balances::RawEvent::Transfer(
+					Default::default(),
+					Default::default(),
+					10000000000,
+					100,
+				)),
+			},
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(1),
+				event: Event::system(srml_system::Event::ExtrinsicSuccess),
+			},
+		];
+		let result = get_block_extrinsics_result(Some(Hex(events.encode()))).unwrap();
+
+		// inherent
+		assert_eq!(result[&0].success, true);
+		assert_eq!(result[&0].fee, None);
+
+		// transfer
+		assert_eq!(result[&1].success, true);
+		assert_eq!(result[&1].fee, Some(100));
+		assert_eq!(result[&1].events.len(), 1);
+
+		assert!(get_block_extrinsics_result(None).unwrap().is_empty());
+
+		match get_block_extrinsics_result(Some(Hex(vec![0x08, 0x00]))) {
+			Err(errors::Error(errors::ErrorKind::DecodeFailed("events"), _)) => (),
+			_ => panic!("expected DecodeFailed"),
+		}
+	}
+
+	#[test]
+	fn test_get_default_shard() {
+		let config: Config = toml::from_str(
+			r#"
+			default_shard = 1
+			[shards]
+			[shards.0]
+			rpc = ["http://127.0.0.1:9933"]
+			[shards.1]
+			rpc = ["http://127.0.0.1:9934"]
+			"#,
+		)
+		.unwrap();
+		assert_eq!(get_default_shard(&config).unwrap(), 1);
+
+		// unset
+		let config: Config = toml::from_str("[shards]").unwrap();
+		let error: jsonrpc_core::Error = get_default_shard(&config).unwrap_err().into();
+		assert_eq!(error.message, "Default shard not configured");
+	}
+
+	#[test]
+	fn test_check_hash_list() {
+		assert!(check_hash_list(0, 100).is_ok());
+		assert!(check_hash_list(100, 100).is_ok());
+
+		let error: jsonrpc_core::Error = check_hash_list(101, 100).unwrap_err().into();
+		assert_eq!(error.message, "Hash list too long");
+		assert_eq!(error.data, Some(json!({ "count": 101, "max": 100 })));
+	}
+
+	/// The globals are set up once by the config loading
+	fn init_globals() {
+		let _ = HRP.set(Hrp::TESTNET);
+		let _ = SHARD_COUNT.set(4);
+	}
+
+	/// A node serving an empty block, recording the methods called
+	fn start_node() -> (String, Arc<Mutex<Vec<String>>>) {
+		start_node_with(json!([]), serde_json::Value::Null)
+	}
+
+	/// A node serving a block of the extrinsics and the raw events,
+	/// the extrinsics are pending too
+	fn start_node_with(
+		extrinsics: serde_json::Value,
+		events: serde_json::Value,
+	) -> (String, Arc<Mutex<Vec<String>>>) {
+		start_node_by(move |method, params| {
+			// the best number is 0x41
+			let above_best = params[0].as_u64().map_or(false, |x| x > 0x41);
+
+			match method {
+				"chain_getBlockHash" if above_best => serde_json::Value::Null,
+				"chain_getBlockHash" | "chain_getFinalizedHead" => {
+					json!(format!("0x{}", "00".repeat(32)))
+				}
+				"chain_getHeader" => json!({
+					"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+					"parentHash": format!("0x{}", "00".repeat(32)),
+					"stateRoot": format!("0x{}", "00".repeat(32)),
+					"number": "0x41",
+				}),
+				"system_peers" => json!([{}, {}]),
+				"system_name" => json!("yee-node"),
+				"system_version" => json!("1.0.0"),
+				"chain_getBlock" => json!({
+					"block": {
+						"header": {
+							"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+							"parentHash": format!("0x{}", "00".repeat(32)),
+							"stateRoot": format!("0x{}", "00".repeat(32)),
+							"number": "0x41",
+						},
+						"extrinsics": extrinsics,
+					},
+				}),
+				"state_getStorage" => events.clone(),
+				"author_pendingExtrinsics" => extrinsics.clone(),
+				_ => serde_json::Value::Null,
+			}
+		})
+	}
+
+	/// A node answering each request by the method and the params, recording the methods called
+	fn start_node_by<F>(answer: F) -> (String, Arc<Mutex<Vec<String>>>)
+	where
+		F: Fn(&str, &serde_json::Value) -> serde_json::Value + Send + 'static,
+	{
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let uri = format!("http://{}", listener.local_addr().unwrap());
+		let methods = Arc::new(Mutex::new(Vec::new()));
+
+		let called = methods.clone();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				let mut stream = stream.unwrap();
+				let request: serde_json::Value =
+					serde_json::from_slice(&read_body(&mut stream)).unwrap();
+				let method = request["method"].as_str().unwrap().to_string();
+
+				let result = answer(&method, &request["params"]);
+				called.lock().unwrap().push(method);
+
+				let body = json!({ "jsonrpc": "2.0", "result": result, "id": request["id"] });
+				let body = body.to_string();
+				let response = format!(
+					"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					body.len(),
+					body
+				);
+				stream.write_all(response.as_bytes()).unwrap();
+			}
+		});
+
+		(uri, methods)
+	}
+
+	fn read_body(stream: &mut TcpStream) -> Vec<u8> {
+		let mut buf = Vec::new();
+		let mut chunk = [0u8; 1024];
+		loop {
+			if let Some(pos) = buf.windows(4).position(|x| x == b"\r\n\r\n") {
+				let headers = String::from_utf8_lossy(&buf[..pos]).to_lowercase();
+				let len = headers
+					.lines()
+					.find(|x| x.starts_with("content-length:"))
+					.map(|x| {
+						x["content-length:".len()..]
+							.trim()
+							.parse::<usize>()
+							.unwrap()
+					})
+					.unwrap_or(0);
+				if buf.len() >= pos + 4 + len {
+					return buf[pos + 4..pos + 4 + len].to_vec();
+				}
+			}
+			let n = stream.read(&mut chunk).unwrap();
+			assert!(n > 0);
+			buf.extend_from_slice(&chunk[..n]);
+		}
+	}
+
+	/// A chain of 4 shards served by a node answering every storage read with `storage`
+	fn chain_with_storage(storage: serde_json::Value) -> Chain {
+		let (uri, _methods) = start_node_with(json!([]), storage);
+		chain_with_node(&uri, 4)
+	}
+
+	/// A chain of the shards, all served by the node
+	fn chain_with_node(uri: &str, shards: u16) -> Chain {
+		chain_with_node_config(uri, shards, "")
+	}
+
+	/// A chain of the shards, all served by the node, with the config put before `[shards]`
+	fn chain_with_node_config(uri: &str, shards: u16, config: &str) -> Chain {
+		let shards = (0..shards)
+			.map(|x| format!("[shards.{}]\nrpc = [\"{}\"]\n", x, uri))
+			.collect::<String>();
+		let config: Config = toml::from_str(&format!("{}\n[shards]\n{}", config, shards)).unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches)
+	}
+
+	#[test]
+	fn test_get_block_future_without_success() {
+		let (uri, methods) = start_node();
+		let config: Config = toml::from_str(&format!(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["{}"]
+			"#,
+			uri
+		))
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let mut runtime = Runtime::new().unwrap();
+
+		let mut get_block = |with_success: bool| {
+			let hash = Box::new(future::ok(Ok(Some(Hex(vec![0u8; 32])))));
+			let result = get_block_future(
+				rpc_client.clone(),
+				caches.block.clone(),
+				0,
+				false,
+				with_success,
+				hash,
+			);
+			runtime.block_on(result).unwrap().unwrap().unwrap()
+		};
+
+		// only the block is read
+		let block = get_block(false);
+		assert_eq!(block.header.number, 65);
+		assert_eq!(*methods.lock().unwrap(), vec!["chain_getBlock"]);
+
+		// the events are read by default
+		methods.lock().unwrap().clear();
+		get_block(true);
+		let methods = methods.lock().unwrap();
+		assert_eq!(methods[..2], ["chain_getBlock", "state_getStorage"]);
+	}
+
+	#[test]
+	fn test_get_block_future_cached() {
+		let (uri, methods) = start_node();
+		let config: Config = toml::from_str(&format!(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["{}"]
+			"#,
+			uri
+		))
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let mut runtime = Runtime::new().unwrap();
+
+		let mut get_block = |hash: Vec<u8>| {
+			let hash = Box::new(future::ok(Ok(Some(Hex(hash)))));
+			let result = get_block_future(
+				rpc_client.clone(),
+				caches.block.clone(),
+				0,
+				false,
+				true,
+				hash,
+			);
+			runtime.block_on(result).unwrap().unwrap().unwrap()
+		};
+
+		// the finalized number is refreshed by the miss, the block 65 is finalized
+		let block = get_block(vec![0u8; 32]);
+		assert_eq!(
+			*methods.lock().unwrap(),
+			vec![
+				"chain_getBlock",
+				"state_getStorage",
+				"chain_getFinalizedHead",
+				"chain_getHeader"
+			]
+		);
+
+		// served from the cache
+		methods.lock().unwrap().clear();
+		let cached = get_block(block.header.block_hash.clone().unwrap().0);
+		assert_eq!(cached.header.number, 65);
+		assert!(methods.lock().unwrap().is_empty());
+		assert_eq!(caches.block.block_counters().hits, 1);
+	}
+
+	#[test]
+	fn test_parse_block_id() {
+		assert_eq!(parse_block_id(&json!(394)).unwrap(), BlockId::Number(394));
+
+		let hash = "0xc34449ad91dfa044c4d314b1b22762189bb3ad4a8577a9050e90e443f3550afc";
+		assert_eq!(
+			parse_block_id(&json!(hash)).unwrap(),
+			BlockId::Hash(Hex(hex::decode(&hash[2..]).unwrap()))
+		);
+
+		// ambiguous or invalid
+		assert!(parse_block_id(&json!("0x18a")).is_err());
+		assert!(parse_block_id(&json!("394")).is_err());
+		assert!(parse_block_id(&json!(-1)).is_err());
+		assert!(parse_block_id(&json!(null)).is_err());
+		assert!(parse_block_id(&json!(format!("0x{}", "zz".repeat(32)))).is_err());
+	}
+
+	#[test]
+	fn test_get_sample_numbers() {
+		assert_eq!(
+			get_sample_numbers(10, 20, 5, 100).unwrap(),
+			vec![10, 15, 20]
+		);
+		assert_eq!(
+			get_sample_numbers(10, 22, 5, 100).unwrap(),
+			vec![10, 15, 20]
+		);
+		assert_eq!(get_sample_numbers(10, 10, 5, 100).unwrap(), vec![10]);
+		assert!(get_sample_numbers(20, 10, 5, 100).unwrap().is_empty());
+
+		// the end of the chain
+		assert_eq!(
+			get_sample_numbers(u64::max_value() - 1, u64::max_value(), 1, 100).unwrap(),
+			vec![u64::max_value() - 1, u64::max_value()]
+		);
+
+		assert!(get_sample_numbers(10, 20, 0, 100).is_err());
+		assert!(get_sample_numbers(0, u64::max_value(), 1, 100).is_err());
+
+		let error: jsonrpc_core::Error = get_sample_numbers(0, 1000, 5, 100).unwrap_err().into();
+		assert_eq!(error.message, "Too many samples");
+		assert_eq!(error.data, Some(json!({ "count": 201, "max": 100 })));
+	}
+
+	#[test]
+	fn test_get_address_balance_history() {
+		init_globals();
+
+		let address = "tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6";
+		let (uri, _methods) = start_node();
+		let chain = chain_with_node(&uri, 4);
+		let mut runtime = Runtime::new().unwrap();
+
+		let mut get_history = |from: u64, to: u64| {
+			runtime.block_on(chain.get_address_balance_history(
+				address.to_string(),
+				NumberOrHex::Number(from),
+				NumberOrHex::Number(to),
+				NumberOrHex::Number(5),
+			))
+		};
+
+		// up to the best 65
+		let result = get_history(55, 65).unwrap();
+		assert_eq!(result.as_array().unwrap().len(), 3);
+		assert_eq!(result[2]["block_number"], json!(65));
+
+		// not read at the best state
+		let error = get_history(60, 70).unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": 70, "best": 65 })));
+	}
+
+	#[test]
+	fn test_storage_key() {
+		let config: Config = toml::from_str("[shards]").unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+
+		let public_key =
+			hex::decode("3c7f227375e8b6eb0648e17b48fb5ff48fc6da5cb0eac1c68133e1c6c46d1465")
+				.unwrap();
+		let storage_key = chain
+			.get_map_storage_key("System AccountNonce".to_string(), Hex(public_key))
+			.wait()
+			.unwrap();
+		assert_eq!(
+			storage_key,
+			"0xbee2737ed0aa54dff1566afb46dcc789f4b332bf49e3e3bb5983ca354b0a7da9"
+		);
+
+		let storage_key = chain
+			.get_value_storage_key("System Events".to_string())
+			.wait()
+			.unwrap();
+		assert_eq!(storage_key, "0xcc956bdb7605e3547539f321ac2bc95c");
+	}
+
+	#[test]
+	fn test_check_finalized() {
+		assert!(check_finalized(64, Some(65)).is_ok());
+		assert!(check_finalized(65, Some(65)).is_ok());
+
+		let error: jsonrpc_core::Error = check_finalized(66, Some(65)).unwrap_err().into();
+		assert_eq!(error.message, "Block not finalized");
+		assert_eq!(error.data, Some(json!({ "number": 66, "finalized": 65 })));
+
+		// the finalized number unknown
+		let error: jsonrpc_core::Error = check_finalized(0, None).unwrap_err().into();
+		assert_eq!(error.data, Some(json!({ "number": 0, "finalized": null })));
+	}
+
+	#[test]
+	fn test_check_produced() {
+		assert!(check_produced(65, Some(65)).is_ok());
+
+		let error: jsonrpc_core::Error = check_produced(66, Some(65)).unwrap_err().into();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": 66, "best": 65 })));
+
+		// the best number unknown
+		assert!(check_produced(66, None).is_ok());
+	}
+
+	#[test]
+	fn test_check_block_number_margin() {
+		assert!(check_block_number_margin(165, Some(65), 100).is_ok());
+		assert!(check_block_number_margin(166, Some(65), 100).is_err());
+		assert!(check_block_number_margin(u64::MAX, Some(65), u64::MAX).is_ok());
+
+		// the best number unknown
+		assert!(check_block_number_margin(u64::MAX, None, 100).is_ok());
+	}
+
+	#[test]
+	fn test_get_finalized_block_by_number() {
+		init_globals();
+
+		let (uri, methods) = start_node();
+		let chain = chain_with_node(&uri, 1);
+		chain.caches.block.set_finalized_number(0, 65);
+		let mut runtime = Runtime::new().unwrap();
+
+		// finalized
+		let block = runtime
+			.block_on(chain.get_finalized_block_by_number(0, 65))
+			.unwrap()
+			.unwrap();
+		assert_eq!(block["header"]["number"], 65);
+		assert!(!methods
+			.lock()
+			.unwrap()
+			.contains(&"chain_getFinalizedHead".to_string()));
+
+		// not finalized yet, the finalized number is refreshed
+		let error = runtime
+			.block_on(chain.get_finalized_block_by_number(0, 66))
+			.unwrap_err();
+		assert_eq!(error.message, "Block not finalized");
+		assert_eq!(error.data, Some(json!({ "number": 66, "finalized": 65 })));
+		assert!(methods
+			.lock()
+			.unwrap()
+			.contains(&"chain_getFinalizedHead".to_string()));
+	}
+
+	#[test]
+	fn test_get_extrinsic_shard() {
+		init_globals();
+
+		let config: Config = toml::from_str(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["http://127.0.0.1:9033"]
+			[shards.1]
+			rpc = ["http://127.0.0.1:9133"]
+			[shards.2]
+			rpc = ["http://127.0.0.1:9233"]
+			[shards.3]
+			rpc = ["http://127.0.0.1:9333"]
+			"#,
+		)
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+
+		// a signed transfer
+		let call = "0400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+		let raw = format!(
+			"310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b503{}",
+			call
+		);
+		let result = chain
+			.get_extrinsic_shard(Hex(hex::decode(&raw).unwrap()))
+			.wait()
+			.unwrap();
+
+		let signer = result["signer"].as_str().unwrap().to_string();
+		let (public_key, shard_num) = get_address_shard_num(signer, 4).unwrap();
+		assert_eq!(
+			public_key.to_vec(),
+			hex::decode("1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc54")
+				.unwrap()
+		);
+		assert_eq!(result["shard_num"], shard_num);
+
+		// an unsigned extrinsic, version 1 without the signed bit
+		let raw = format!("a001{}", call);
+		let error = chain
+			.get_extrinsic_shard(Hex(hex::decode(&raw).unwrap()))
+			.wait()
+			.unwrap_err();
+		assert_eq!(error.message, "Unsigned extrinsic has no signer");
+
+		// malformed
+		let error = chain
+			.get_extrinsic_shard(Hex(vec![0xff]))
+			.wait()
+			.unwrap_err();
+		assert_eq!(error.message, "Decode failed");
+		assert_eq!(error.data, Some(json!({ "structure": "transaction" })));
+	}
+
+	#[test]
+	fn test_get_block_with_events() {
+		init_globals();
+
+		// a signed transfer
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+		// [ApplyExtrinsic(0): system::ExtrinsicSuccess]
+		let events = json!("0x0400000000000000");
+
+		let (uri, methods) = start_node_with(json!([extrinsic]), events);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let result = runtime
+			.block_on(chain.get_block_with_events(0, 65))
+			.unwrap()
+			.unwrap();
+
+		let block = &result["block"];
+		assert_eq!(block["header"]["number"], 65);
+		let extrinsics = block["extrinsics"].as_array().unwrap();
+		assert_eq!(extrinsics.len(), 1);
+		assert_eq!(extrinsics[0]["success"], true);
+		assert!(extrinsics[0]["call"]["params"]["dest_address"].is_string());
+
+		let events = result["events"].as_array().unwrap();
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0]["phase"], json!({ "applyExtrinsic": 0 }));
+		assert!(events[0]["event"]
+			.as_str()
+			.unwrap()
+			.contains("ExtrinsicSuccess"));
+
+		// the events are read once
+		let reads = |method: &str| {
+			let methods = methods.lock().unwrap();
+			methods.iter().filter(|x| *x == method).count()
+		};
+		assert_eq!(reads("state_getStorage"), 1);
+
+		// the block cached, the events read again
+		let hash = block["header"]["block_hash"].clone();
+		let hash: Hex<Vec<u8>> = serde_json::from_value(hash).unwrap();
+		let result = get_cached_block_with_events_future(
+			chain.rpc_client.clone(),
+			chain.caches.block.clone(),
+			0,
+			hash,
+		);
+		let (block, events) = runtime.block_on(result).unwrap().unwrap().unwrap();
+		assert_eq!(block.extrinsics[0].success, Some(true));
+		assert!(events.is_some());
+		assert_eq!(reads("chain_getBlock"), 1);
+		assert_eq!(reads("state_getStorage"), 2);
+	}
+
+	#[test]
+	fn test_get_relay_transfer_status() {
+		init_globals();
+
+		// shard 1 is unreachable
+		let (uri, _methods) = start_node();
+		let config: Config = toml::from_str(&format!(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["{}"]
+			[shards.1]
+			rpc = ["http://127.0.0.1:1"]
+			"#,
+			uri
+		))
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+		let mut runtime = Runtime::new().unwrap();
+
+		let status = runtime
+			.block_on(chain.get_relay_transfer_status(
+				Hex(vec![0; 32]),
+				NumberOrHex::Number(60),
+				NumberOrHex::Number(61),
+			))
+			.unwrap();
+		assert_eq!(
+			status,
+			json!({
+				"status": "unknown",
+				"failed_shards": [1],
+			})
+		);
+
+		assert!(runtime
+			.block_on(chain.get_relay_transfer_status(
+				Hex(vec![0; 32]),
+				NumberOrHex::Number(0),
+				NumberOrHex::Number(1000),
+			))
+			.is_err());
+	}
+
+	#[test]
+	fn test_get_peer_count() {
+		let (uri, methods) = start_node();
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let result = runtime.block_on(chain.get_peer_count(0)).unwrap();
+		assert_eq!(result, json!(2));
+		assert_eq!(*methods.lock().unwrap(), vec!["system_peers"]);
+
+		assert!(runtime.block_on(chain.get_peer_count(1)).is_err());
+	}
+
+	#[test]
+	fn test_get_node_version() {
+		let (uri, methods) = start_node();
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let result = runtime.block_on(chain.get_node_version(0)).unwrap();
+		assert_eq!(result, json!({ "name": "yee-node", "version": "1.0.0" }));
+		assert_eq!(methods.lock().unwrap().len(), 2);
+
+		// served from the cache
+		let result = runtime.block_on(chain.get_node_version(0)).unwrap();
+		assert_eq!(result["version"], json!("1.0.0"));
+		assert_eq!(methods.lock().unwrap().len(), 2);
+	}
+
+	#[test]
+	fn test_get_finalized_hash() {
+		let (uri, methods) = start_node();
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let result = runtime.block_on(chain.get_finalized_hash(0)).unwrap();
+		assert_eq!(result.unwrap().0, vec![0u8; 32]);
+		assert_eq!(*methods.lock().unwrap(), vec!["chain_getFinalizedHead"]);
+
+		assert!(runtime.block_on(chain.get_finalized_hash(1)).is_err());
+	}
+
+	#[test]
+	fn test_get_overview() {
+		init_globals();
+
+		// shard 1 is unreachable
+		let (uri, _methods) = start_node();
+		let config: Config = toml::from_str(&format!(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["{}"]
+			[shards.1]
+			rpc = ["http://127.0.0.1:1"]
+			"#,
+			uri
+		))
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+		let mut runtime = Runtime::new().unwrap();
+
+		let overview = runtime.block_on(chain.get_overview()).unwrap();
+		let overview = overview.as_array().unwrap();
+		assert_eq!(overview.len(), 2);
+
+		assert_eq!(overview[0]["shard_num"], 0);
+		assert_eq!(overview[0]["best"], 65);
+		assert_eq!(overview[0]["finalized"], 65);
+		assert_eq!(overview[0]["peer_count"], 2);
+		// the empty block has no timestamp
+		assert!(overview[0]["block_age"].is_null());
+
+		assert_eq!(
+			overview[1],
+			json!({
+				"shard_num": 1,
+				"best": null,
+				"finalized": null,
+				"peer_count": null,
+				"block_age": null,
+			})
+		);
+	}
+
+	#[test]
+	fn test_get_account_info_batch() {
+		let (uri, methods) = start_node();
+		let chain = chain_with_node(&uri, 2);
+		let mut runtime = Runtime::new().unwrap();
+
+		let public_keys = [[1u8; 32], [2u8; 32], [3u8; 32]];
+		let address =
+			|public_key: &[u8; 32]| public_key.to_vec().to_address(Hrp::TESTNET).unwrap().0;
+		let addresses = vec![
+			address(&public_keys[0]),
+			"tyee1invalid".to_string(),
+			address(&public_keys[1]),
+			address(&public_keys[2]),
+		];
+
+		let result = runtime
+			.block_on(
+				chain.get_account_info_batch(addresses.clone(), Some(NumberOrHex::Number(65))),
+			)
+			.unwrap();
+		let result = result.as_array().unwrap();
+		assert_eq!(result.len(), 4);
+
+		// in the order of the addresses
+		for (i, public_key) in [0, 2, 3].iter().zip(&public_keys) {
+			let shard_num = shard_num_for_bytes(public_key, 2).unwrap();
+			assert_eq!(
+				result[*i],
+				json!({
+					"address": addresses[*i],
+					"shard_num": shard_num,
+					"nonce": { "nonce": 0, "nonce_hex": "0x0" },
+					"free_balance": 0,
+					"reserved_balance": 0,
+				})
+			);
+		}
+
+		assert_eq!(result[1]["address"], "tyee1invalid");
+		assert_eq!(result[1]["error"]["message"], "Invalid address");
+
+		// a block hash read for each shard
+		let shard_count = public_keys
+			.iter()
+			.map(|x| shard_num_for_bytes(x, 2).unwrap())
+			.collect::<std::collections::HashSet<_>>()
+			.len();
+		assert_eq!(
+			methods
+				.lock()
+				.unwrap()
+				.iter()
+				.filter(|x| *x == "chain_getBlockHash")
+				.count(),
+			shard_count
+		);
+
+		// not read at the best state above the best number
+		let result = runtime
+			.block_on(chain.get_account_info_batch(addresses, Some(NumberOrHex::Number(70))))
+			.unwrap();
+		for i in &[0, 2, 3] {
+			assert_eq!(result[*i]["error"]["message"], "Block not yet produced");
+			assert_eq!(
+				result[*i]["error"]["data"],
+				json!({ "number": 70, "best": 65 })
+			);
+		}
+	}
+
+	#[test]
+	fn test_get_block_with_raw() {
+		init_globals();
+
+		// a signed transfer
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+
+		let (uri, _methods) = start_node_with(json!([extrinsic]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		// not by default
+		let block = runtime
+			.block_on(chain.get_block_by_number(0, NumberOrHex::Number(65), None, None, None, None))
+			.unwrap()
+			.unwrap();
+		assert!(block["extrinsics"][0].get("raw").is_none());
+		assert!(block["extrinsics"][0]["call"].is_object());
+
+		// on request
+		let block = runtime
+			.block_on(chain.get_block_by_number(
+				0,
+				NumberOrHex::Number(65),
+				None,
+				Some(true),
+				None,
+				None,
+			))
+			.unwrap()
+			.unwrap();
+		assert_eq!(block["extrinsics"][0]["raw"], extrinsic);
+		assert!(block["extrinsics"][0]["call"].is_object());
+
+		let hash = Hex(vec![0u8; 32]);
+		let block = runtime
+			.block_on(chain.get_block_by_hash(0, hash.clone(), None, Some(false), None, None))
+			.unwrap()
+			.unwrap();
+		assert!(block["extrinsics"][0].get("raw").is_none());
+
+		let tx_hash: Hex<Vec<u8>> =
+			serde_json::from_value(block["extrinsics"][0]["hash"].clone()).unwrap();
+		let extrinsic_value = runtime
+			.block_on(chain.get_extrinsic_by_hash(
+				0,
+				NumberOrHex::Number(65),
+				tx_hash,
+				Some(true),
+				None,
+			))
+			.unwrap()
+			.unwrap();
+		assert_eq!(extrinsic_value["raw"], extrinsic);
+	}
+
+	#[test]
+	fn test_scale_encoding() {
+		init_globals();
+
+		// a signed transfer
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+
+		let (uri, _methods) = start_node_with(json!([extrinsic]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let decode_hex = |x: serde_json::Value| hex::decode(&x.as_str().unwrap()[2..]).unwrap();
+
+		// header
+		let header = runtime
+			.block_on(chain.get_header_by_number(0, NumberOrHex::Number(65), Some(Encoding::Scale)))
+			.unwrap()
+			.unwrap();
+		let header = decode_hex(header);
+		let decoded: yee_runtime::Header = Decode::decode(&mut &header[..]).unwrap();
+		assert_eq!(decoded.number, 65);
+		assert_eq!(decoded.encode(), header);
+
+		let hash = Hex(vec![0u8; 32]);
+		let json_header = runtime
+			.block_on(chain.get_header_by_hash(0, hash.clone(), None))
+			.unwrap()
+			.unwrap();
+		assert_eq!(json_header["number"], 65);
+
+		// block
+		let block = runtime
+			.block_on(chain.get_block_by_hash(0, hash, None, None, Some(Encoding::Scale), None))
+			.unwrap()
+			.unwrap();
+		let block = decode_hex(block);
+		let decoded: yee_runtime::Block = Decode::decode(&mut &block[..]).unwrap();
+		assert_eq!(decoded.header.number, 65);
+		assert_eq!(decoded.extrinsics.len(), 1);
+		assert_eq!(decoded.encode(), block);
+
+		// the extrinsic is as the node serves it
+		let raw = hex::decode(&extrinsic[2..]).unwrap();
+		assert_eq!(decoded.extrinsics[0].encode(), raw.encode());
+
+		// unknown encoding
+		assert!(serde_json::from_str::<Encoding>(r#""xml""#).is_err());
+	}
+
+	#[test]
+	fn test_best_number_ttl() {
+		let (uri, methods) = start_node();
+
+		let get_best_number_twice = |ttl_ms: u64| {
+			let chain =
+				chain_with_node_config(&uri, 1, &format!("best_number_ttl_ms = {}", ttl_ms));
+			let mut runtime = Runtime::new().unwrap();
+
+			methods.lock().unwrap().clear();
+			for _ in 0..2 {
+				let number = runtime.block_on(chain.get_best_number(0)).unwrap();
+				assert_eq!(number, Some(65));
+			}
+			let methods = methods.lock().unwrap();
+			methods.iter().filter(|x| *x == "chain_getHeader").count()
+		};
+
+		// not cached by default
+		assert_eq!(get_best_number_twice(0), 2);
+
+		// the second call within the ttl is served from the cache
+		assert_eq!(get_best_number_twice(2000), 1);
+	}
+
+	#[test]
+	fn test_get_extrinsic_by_hash_in_range() {
+		init_globals();
+
+		// a signed transfer
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+
+		let (uri, _methods) = start_node_with(json!([extrinsic]), serde_json::Value::Null);
+		let chain = chain_with_node_config(&uri, 1, "max_block_range = 10");
+		let mut runtime = Runtime::new().unwrap();
+
+		let block = runtime
+			.block_on(chain.get_block_by_number(0, NumberOrHex::Number(65), None, None, None, None))
+			.unwrap()
+			.unwrap();
+		let hash: Hex<Vec<u8>> =
+			serde_json::from_value(block["extrinsics"][0]["hash"].clone()).unwrap();
+
+		// every block of the mock node has the extrinsic, the first is returned
+		let tx = runtime
+			.block_on(chain.get_extrinsic_by_hash_in_range(0, 60, 65, hash.clone()))
+			.unwrap()
+			.unwrap();
+		assert_eq!(tx["block_number"], 60);
+		assert_eq!(tx["hash"], block["extrinsics"][0]["hash"]);
+		assert!(tx["call"].is_object());
+
+		// not found
+		let tx = runtime
+			.block_on(chain.get_extrinsic_by_hash_in_range(0, 60, 65, Hex(vec![0u8; 32])))
+			.unwrap();
+		assert!(tx.is_none());
+
+		// out of the max span
+		assert!(runtime
+			.block_on(chain.get_extrinsic_by_hash_in_range(0, 0, 100, hash))
+			.is_err());
+	}
+
+	#[test]
+	fn test_extrinsic_append_tx_decoded_depth() {
+		init_globals();
+
+		// a signed transfer
+		let transfer = hex::decode("310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717").unwrap();
+
+		// an unsigned relay transfer of the tx: relay_type, tx, number, hash, parent
+		let relay = |tx: &[u8]| -> Vec<u8> {
+			let mut call = vec![9u8, 0, 0];
+			call.extend(tx.to_vec().encode());
+			call.extend(394u64.encode());
+			call.extend(vec![0u8; 32]);
+			call.extend(vec![0u8; 32]);
+			let mut extrinsic = vec![1u8];
+			extrinsic.extend(call);
+			extrinsic.encode()
+		};
+
+		// relay within relay
+		const NESTED: usize = 32;
+		let mut tx = transfer;
+		for _ in 0..NESTED {
+			tx = relay(&tx);
+		}
+		let extrinsic = json!({
+			"call": {
+				"module": 9,
+				"method": 0,
+				"params": { "tx": format!("0x{}", hex::encode(&tx)) },
+			}
+		});
+
+		let depth_of = |extrinsic: &serde_json::Value| {
+			let mut depth = 0;
+			let mut current = extrinsic;
+			while let Some(tx) = current["call"]["params"].get("tx_decoded") {
+				depth += 1;
+				current = tx;
+			}
+			(depth, current.clone())
+		};
+
+		// stop at the depth, the tx beyond is raw
+		let mut value = extrinsic.clone();
+		extrinsic_append_tx_decoded(&mut value, Hrp::TESTNET, 4, 3);
+		let (depth, last) = depth_of(&value);
+		assert_eq!(depth, 3);
+		assert_eq!(last["call"]["module"], 9);
+		assert!(last["call"]["params"]["tx"].is_string());
+
+		// decoded down to the transfer
+		let mut value = extrinsic.clone();
+		extrinsic_append_tx_decoded(&mut value, Hrp::TESTNET, 4, NESTED);
+		let (depth, last) = depth_of(&value);
+		assert_eq!(depth, NESTED);
+		assert_eq!(last["call"]["module"], 4);
+
+		// unlimited
+		let mut runtime = Runtime::new().unwrap();
+		let result =
+			get_value_with_tx_decoded_future(Box::new(future::ok(Ok(Some(extrinsic.clone())))), 0);
+		let value = runtime.block_on(result).unwrap().unwrap().unwrap();
+		let (depth, last) = depth_of(&value);
+		assert_eq!(depth, NESTED);
+		assert_eq!(last["call"]["module"], 4);
+
+		// malformed tx is left raw
+		let mut value = json!({
+			"call": { "module": 9, "method": 0, "params": { "tx": "0x00ff" } }
+		});
+		extrinsic_append_tx_decoded(&mut value, Hrp::TESTNET, 4, 3);
+		assert!(value["call"]["params"].get("tx_decoded").is_none());
+	}
+
+	#[test]
+	fn test_get_nonce_with_pending() {
+		init_globals();
+
+		// a signed transfer of nonce 1
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+		let address = "tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6";
+		let public_key =
+			hex::decode("1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc54")
+				.unwrap();
+
+		// the on-chain nonce is 1
+		let (uri, _methods) = start_node_with(json!([extrinsic]), json!("0x0100000000000000"));
+		let chain = chain_with_node(&uri, 4);
+		let mut runtime = Runtime::new().unwrap();
+
+		let nonce = runtime
+			.block_on(chain.get_nonce_with_pending(address.to_string()))
+			.unwrap();
+		assert_eq!(nonce["nonce"], 2);
+
+		// pending of the other signers and after a gap do not count
+		let mut key = [0u8; 32];
+		key.copy_from_slice(&public_key);
+		let pending = vec![Hex(hex::decode(&extrinsic[2..]).unwrap())];
+		assert_eq!(get_next_nonce(1, &pending, &key), 2);
+		assert_eq!(get_next_nonce(0, &pending, &key), 0);
+		assert_eq!(get_next_nonce(1, &pending, &[0u8; 32]), 1);
+	}
+
+	#[test]
+	fn test_get_storage_batch() {
+		// the mock node has a value for every key
+		let (uri, methods) = start_node_with(json!([]), json!("0x0100000000000000"));
+		let chain = chain_with_node_config(&uri, 1, "max_storage_keys = 3");
+		let mut runtime = Runtime::new().unwrap();
+
+		let keys = vec![Hex(vec![1u8; 32]), Hex(vec![2u8; 32]), Hex(vec![3u8; 32])];
+		let result = runtime
+			.block_on(chain.get_storage_batch(0, keys.clone(), Some(65)))
+			.unwrap();
+		assert_eq!(result, json!(["0x0100000000000000"; 3]));
+
+		// the block hash is read once
+		let called = methods.lock().unwrap().clone();
+		let count = |method: &str| called.iter().filter(|x| *x == method).count();
+		assert_eq!(count("chain_getBlockHash"), 1);
+		assert_eq!(count("state_getStorage"), 3);
+
+		// above the best block instead of the best state
+		methods.lock().unwrap().clear();
+		let error = runtime
+			.block_on(chain.get_storage_batch(0, keys.clone(), Some(70)))
+			.unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": 70, "best": 65 })));
+		assert!(!methods
+			.lock()
+			.unwrap()
+			.contains(&"state_getStorage".to_string()));
+
+		// capped
+		let mut keys = keys;
+		keys.push(Hex(vec![4u8; 32]));
+		let error = runtime
+			.block_on(chain.get_storage_batch(0, keys, None))
+			.unwrap_err();
+		assert_eq!(error.message, "Too many storage keys");
+		assert_eq!(error.data, Some(json!({ "count": 4, "max": 3 })));
+	}
+
+	#[test]
+	fn test_get_block_author() {
+		init_globals();
+
+		let public_key =
+			hex::decode("1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc54")
+				.unwrap();
+
+		// the coinbase followed by the rest of the seal
+		let mut seal = public_key.clone();
+		seal.extend_from_slice(&[1u8; 16]);
+		let log = DigestItem::<Hash, AuthorityId, ()>::Consensus(YEE_POW_ENGINE_ID, seal).encode();
+		let other = DigestItem::<Hash, AuthorityId, ()>::Other(vec![1, 2, 3]).encode();
+
+		let header = |logs: Vec<Vec<u8>>| -> Header {
+			serde_json::from_value(json!({
+				"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+				"parentHash": format!("0x{}", "00".repeat(32)),
+				"stateRoot": format!("0x{}", "00".repeat(32)),
+				"number": "0x41",
+				"digest": {
+					"logs": logs.iter().map(|x| format!("0x{}", hex::encode(x))).collect::<Vec<_>>(),
+				},
+			}))
+			.unwrap()
+		};
+
+		assert_eq!(
+			get_block_author(&header(vec![other.clone(), log]), Hrp::TESTNET),
+			Some("tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6".to_string())
+		);
+
+		// no seal
+		assert_eq!(get_block_author(&header(vec![other]), Hrp::TESTNET), None);
+
+		// a truncated seal
+		let log = DigestItem::<Hash, AuthorityId, ()>::Consensus(YEE_POW_ENGINE_ID, vec![1u8; 8])
+			.encode();
+		assert_eq!(get_block_author(&header(vec![log]), Hrp::TESTNET), None);
+
+		// the header of the mock node has no digest
+		let (uri, _methods) = start_node();
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let author = runtime.block_on(chain.get_block_author(0, 65)).unwrap();
+		assert_eq!(author, serde_json::Value::Null);
+	}
+
+	#[test]
+	fn test_get_shard_layout() {
+		init_globals();
+
+		let config: Config = toml::from_str(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["http://127.0.0.1:9033"]
+			[shards.1]
+			rpc = ["http://127.0.0.1:10033"]
+			"#,
+		)
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config.clone())), rpc_client, caches);
+
+		let layout = chain.get_shard_layout().wait().unwrap();
+		assert_eq!(layout["shard_count"], config.shards.len());
+		assert_eq!(layout["shard_bits"], 1);
+		assert_eq!(
+			layout["example"]["address"],
+			"tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6"
+		);
+		assert_eq!(
+			layout["example"]["shard_num"],
+			shard_num_for_bytes(&EXAMPLE_PUBLIC_KEY, 2).unwrap()
+		);
+
+		let layout = get_shard_layout(4, Hrp::TESTNET);
+		assert_eq!(layout["shard_bits"], 2);
+
+		// not a power of two
+		let layout = get_shard_layout(3, Hrp::TESTNET);
+		assert_eq!(layout["shard_bits"], serde_json::Value::Null);
+		assert_eq!(layout["example"]["shard_num"], serde_json::Value::Null);
+	}
+
+	#[test]
+	fn test_block_not_yet_produced() {
+		init_globals();
+
+		let (uri, _methods) = start_node();
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		// above the best number 65
+		let error = runtime
+			.block_on(chain.get_block_by_number(0, NumberOrHex::Number(66), None, None, None, None))
+			.unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": 66, "best": 65 })));
+
+		let error = runtime
+			.block_on(chain.get_header_by_number(0, NumberOrHex::Number(66), None))
+			.unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+
+		// the range keeps null for the blocks not yet produced
+		let headers = runtime
+			.block_on(chain.get_headers_by_number_range(
+				0,
+				NumberOrHex::Number(65),
+				NumberOrHex::Number(66),
+			))
+			.unwrap();
+		assert_eq!(headers[0]["number"], 65);
+		assert_eq!(headers[1], serde_json::Value::Null);
+	}
+
+	#[test]
+	fn test_describe_call() {
+		// transfer
+		assert_eq!(
+			describe_call(4, 0),
+			json!({
+				"module": 4,
+				"method": 0,
+				"module_name": "balances",
+				"method_name": "transfer",
+				"params": ["dest", "value"],
+			})
+		);
+
+		// relay transfer
+		let call = describe_call(9, 0);
+		assert_eq!(call["module_name"], "relay");
+		assert_eq!(call["method_name"], "transfer");
+		assert_eq!(
+			call["params"],
+			json!(["relay_type", "tx", "number", "hash", "parent"])
+		);
+
+		// unknown
+		assert_eq!(describe_call(4, 9), serde_json::Value::Null);
+	}
+
+	#[test]
+	fn test_get_extrinsics_by_sender_in_block() {
+		init_globals();
+
+		// signed transfers of two senders
+		let sender = "1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc54";
+		let other = "94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d";
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+		let other_extrinsic = extrinsic.replacen(sender, other, 1);
+
+		let (uri, _methods) = start_node_with(
+			json!([extrinsic, other_extrinsic, extrinsic]),
+			serde_json::Value::Null,
+		);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let address = |public_key: &str| {
+			hex::decode(public_key)
+				.unwrap()
+				.to_address(Hrp::TESTNET)
+				.unwrap()
+				.0
+		};
+
+		let extrinsics = runtime
+			.block_on(chain.get_extrinsics_by_sender_in_block(0, 65, address(sender)))
+			.unwrap();
+		let extrinsics = extrinsics.as_array().unwrap();
+		assert_eq!(extrinsics.len(), 2);
+		assert_eq!(extrinsics[0]["index"], 0);
+		assert_eq!(extrinsics[1]["index"], 2);
+		assert_eq!(
+			extrinsics[0]["signature"]["sender_address"],
+			address(sender)
+		);
+
+		let extrinsics = runtime
+			.block_on(chain.get_extrinsics_by_sender_in_block(0, 65, address(other)))
+			.unwrap();
+		assert_eq!(extrinsics.as_array().unwrap().len(), 1);
+		assert_eq!(extrinsics[0]["raw"], other_extrinsic);
+
+		// no extrinsic of the sender
+		let public_key = format!("{}01", "00".repeat(31));
+		let extrinsics = runtime
+			.block_on(chain.get_extrinsics_by_sender_in_block(0, 65, address(&public_key)))
+			.unwrap();
+		assert_eq!(extrinsics, json!([]));
+
+		// invalid address
+		assert!(runtime
+			.block_on(chain.get_extrinsics_by_sender_in_block(0, 65, "tyee1invalid".to_string()))
+			.is_err());
+	}
+
+	#[test]
+	fn test_get_relay_origin_hash() {
+		init_globals();
+
+		// a signed transfer
+		let transfer = hex::decode("310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717").unwrap();
+
+		// an unsigned relay transfer of the transfer: relay_type, tx, number, hash, parent
+		let mut call = vec![9u8, 0, 0];
+		call.extend(transfer.encode());
+		call.extend(394u64.encode());
+		call.extend(vec![0u8; 32]);
+		call.extend(vec![0u8; 32]);
+		let mut relay = vec![1u8];
+		relay.extend(call);
+		let relay = relay.encode();
+
+		let config: Config = toml::from_str("[shards]").unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+
+		let origin_hash = chain.get_relay_origin_hash(Hex(relay)).wait().unwrap();
+		assert_eq!(
+			origin_hash,
+			format!("0x{}", hex::encode(blake2_256(&transfer)))
+		);
+
+		// the transfer itself
+		let error = chain
+			.get_relay_origin_hash(Hex(transfer))
+			.wait()
+			.unwrap_err();
+		assert_eq!(error.message, "Not a relay transfer");
+
+		// not an extrinsic
+		assert!(chain.get_relay_origin_hash(Hex(vec![0xff])).wait().is_err());
+	}
+
+	#[test]
+	fn test_get_storage_changes() {
+		let value = |x: u8| Some(Hex(vec![x; 8]));
+
+		// set at 11, changed at 13, killed at 14
+		let values = vec![None, value(1), value(1), value(2), None];
+		let changes = get_storage_changes(10, values);
+		assert_eq!(
+			changes,
+			json!([
+				{ "block_number": 11, "before": null, "after": "0x0101010101010101" },
+				{ "block_number": 13, "before": "0x0101010101010101", "after": "0x0202020202020202" },
+				{ "block_number": 14, "before": "0x0202020202020202", "after": null },
+			])
+		);
+
+		assert_eq!(get_storage_changes(10, vec![value(1)]), json!([]));
+		assert_eq!(get_storage_changes(10, vec![]), json!([]));
+
+		// the value of the mock node never changes
+		let (uri, methods) = start_node_with(json!([]), json!("0x0100000000000000"));
+		let chain = chain_with_node_config(&uri, 1, "max_block_range = 3");
+		let mut runtime = Runtime::new().unwrap();
+
+		let key = Hex(vec![1u8; 32]);
+		let changes = runtime
+			.block_on(chain.get_storage_changes(0, key.clone(), 63, 65))
+			.unwrap();
+		assert_eq!(changes, json!([]));
+		let count = methods
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|x| *x == "state_getStorage")
+			.count();
+		assert_eq!(count, 3);
+
+		// the max span
+		assert!(runtime
+			.block_on(chain.get_storage_changes(0, key.clone(), 62, 65))
+			.is_err());
+
+		// beyond the best block instead of a change to null
+		let error = runtime
+			.block_on(chain.get_storage_changes(0, key, 64, 66))
+			.unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": 66, "best": 65 })));
+	}
+
+	#[test]
+	fn test_get_block_with_fields() {
+		init_globals();
+
+		// a signed transfer
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+
+		let (uri, _methods) = start_node_with(json!([extrinsic]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let fields = vec!["header.number".to_string(), "extrinsics.hash".to_string()];
+		let block = runtime
+			.block_on(chain.get_block_by_number(
+				0,
+				NumberOrHex::Number(65),
+				None,
+				None,
+				None,
+				Some(fields),
+			))
+			.unwrap()
+			.unwrap();
+		assert_eq!(block["header"], json!({ "number": 65 }));
+		let extrinsic_value = block["extrinsics"][0].as_object().unwrap();
+		assert_eq!(extrinsic_value.len(), 1);
+		assert!(extrinsic_value["hash"].is_string());
+
+		let tx_hash: Hex<Vec<u8>> =
+			serde_json::from_value(block["extrinsics"][0]["hash"].clone()).unwrap();
+		let extrinsic_value = runtime
+			.block_on(chain.get_extrinsic_by_hash(
+				0,
+				NumberOrHex::Number(65),
+				tx_hash,
+				None,
+				Some(vec!["signature.nonce".to_string(), "unknown".to_string()]),
+			))
+			.unwrap()
+			.unwrap();
+		assert_eq!(extrinsic_value, json!({ "signature": { "nonce": 1 } }));
+	}
+
+	#[test]
+	fn test_get_best_header_all() {
+		init_globals();
+
+		// shard 1 is unreachable
+		let (uri, methods) = start_node();
+		let config: Config = toml::from_str(&format!(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["{}"]
+			[shards.1]
+			rpc = ["http://127.0.0.1:1"]
+			[shards.2]
+			rpc = ["{}"]
+			"#,
+			uri, uri
+		))
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+		let mut runtime = Runtime::new().unwrap();
+
+		let headers = runtime.block_on(chain.get_best_header_all()).unwrap();
+		let headers = headers.as_object().unwrap();
+		assert_eq!(headers.len(), 3);
+
+		for shard_num in &["0", "2"] {
+			assert_eq!(headers[*shard_num]["number"], 65);
+			assert_eq!(
+				headers[*shard_num]["block_hash"],
+				format!("0x{}", "00".repeat(32))
+			);
+		}
+		assert_eq!(headers["1"], serde_json::Value::Null);
+
+		// the header by the best hash
+		let methods = methods.lock().unwrap().clone();
+		let count = |method: &str| methods.iter().filter(|x| *x == method).count();
+		assert_eq!(count("chain_getBlockHash"), 2);
+		assert_eq!(count("chain_getHeader"), 2);
+	}
+
+	#[test]
+	fn test_get_extrinsic_count_by_address() {
+		init_globals();
+
+		let sender = "1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc54";
+		let other = "94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d";
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+		let other_extrinsic = extrinsic.replacen(sender, other, 1);
+
+		// every block has two transfers of the sender and one of the other
+		let (uri, _methods) = start_node_with(
+			json!([extrinsic, other_extrinsic, extrinsic]),
+			serde_json::Value::Null,
+		);
+		let chain = chain_with_node_config(&uri, 1, "max_block_range = 3");
+		let mut runtime = Runtime::new().unwrap();
+
+		let address = |public_key: &str| {
+			hex::decode(public_key)
+				.unwrap()
+				.to_address(Hrp::TESTNET)
+				.unwrap()
+				.0
+		};
+
+		let count = runtime
+			.block_on(chain.get_extrinsic_count_by_address(0, address(sender), 60, 62))
+			.unwrap();
+		assert_eq!(count, 6);
+
+		let count = runtime
+			.block_on(chain.get_extrinsic_count_by_address(0, address(other), 65, 65))
+			.unwrap();
+		assert_eq!(count, 1);
+
+		let public_key = format!("{}01", "00".repeat(31));
+		let count = runtime
+			.block_on(chain.get_extrinsic_count_by_address(0, address(&public_key), 60, 62))
+			.unwrap();
+		assert_eq!(count, 0);
+
+		// over the max span
+		assert!(runtime
+			.block_on(chain.get_extrinsic_count_by_address(0, address(sender), 60, 64))
+			.is_err());
+
+		// not yet produced
+		assert!(runtime
+			.block_on(chain.get_extrinsic_count_by_address(0, address(sender), 65, 66))
+			.is_err());
+	}
+
+	#[test]
+	fn test_get_address_formats() {
+		init_globals();
+
+		let config: Config = toml::from_str(
+			r#"
+			ss58_prefix = 42
+			[shards]
+			[shards.0]
+			rpc = ["http://127.0.0.1:9033"]
+			"#,
+		)
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+
+		let formats = chain.get_address_formats().wait().unwrap();
+		assert_eq!(formats, json!({ "hrp": "tyee", "ss58_prefix": 42 }));
+
+		// ss58 not configured
+		let config: Config = toml::from_str("[shards]").unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+
+		let formats = chain.get_address_formats().wait().unwrap();
+		assert_eq!(formats["hrp"], "tyee");
+		assert_eq!(formats["ss58_prefix"], serde_json::Value::Null);
+	}
+
+	#[test]
+	fn test_decode_address() {
+		let public_key =
+			hex::decode("1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc54")
+				.unwrap();
+
+		let bech32 =
+			decode_address("tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6");
+		assert_eq!(bech32.unwrap().to_vec(), public_key);
+
+		let ss58 = decode_address("5CRx2uyYvhFDLtbgfEsFPwR52A38ZoToJrBaRm8iG2ddUUXU");
+		assert_eq!(ss58.unwrap().to_vec(), public_key);
+
+		// bad checksum
+		match decode_address("5CRx2uyYvhFDLtbgfEsFPwR52A38ZoToJrBaRm8iG2ddUUXV") {
+			Err(errors::Error(errors::ErrorKind::InvalidAddress, _)) => (),
+			_ => panic!("expected InvalidAddress"),
+		}
+		match decode_address("tyee1invalid") {
+			Err(errors::Error(errors::ErrorKind::InvalidAddress, _)) => (),
+			_ => panic!("expected InvalidAddress"),
+		}
+	}
+
+	#[test]
+	fn test_max_range_concurrency() {
+		const MAX: usize = 3;
+
+		init_globals();
+
+		// a node answering each request a while later, (current, peak) of the requests in flight
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let uri = format!("http://{}", listener.local_addr().unwrap());
+		let counter = Arc::new(Mutex::new((0, 0)));
+		let in_flight = counter.clone();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				let mut stream = stream.unwrap();
+				let counter = in_flight.clone();
+				thread::spawn(move || {
+					let request: serde_json::Value =
+						serde_json::from_slice(&read_body(&mut stream)).unwrap();
+					{
+						let mut counter = counter.lock().unwrap();
+						counter.0 += 1;
+						counter.1 = std::cmp::max(counter.0, counter.1);
+					}
+					thread::sleep(std::time::Duration::from_millis(20));
+
+					// the hash is the block number
+					let result = match request["method"].as_str().unwrap() {
+						"chain_getBlockHash" => {
+							json!(format!("0x{:064x}", request["params"][0].as_u64().unwrap()))
+						}
+						_ => json!({
+							"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+							"parentHash": format!("0x{}", "00".repeat(32)),
+							"stateRoot": format!("0x{}", "00".repeat(32)),
+							"number": "0x41",
+						}),
+					};
+					counter.lock().unwrap().0 -= 1;
+
+					let body = json!({ "jsonrpc": "2.0", "result": result, "id": request["id"] });
+					let body = body.to_string();
+					let response = format!(
+						"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+						body.len(),
+						body
+					);
+					stream.write_all(response.as_bytes()).unwrap();
+				});
+			}
+		});
+
+		let chain = chain_with_node_config(&uri, 1, &format!("max_range_concurrency = {}", MAX));
+		let mut runtime = Runtime::new().unwrap();
+
+		let headers = runtime
+			.block_on(chain.get_headers_by_number_range(
+				0,
+				NumberOrHex::Number(0),
+				NumberOrHex::Number(19),
+			))
+			.unwrap();
+
+		// in the order of the range
+		let headers = headers.as_array().unwrap();
+		assert_eq!(headers.len(), 20);
+		for (number, header) in headers.iter().enumerate() {
+			assert_eq!(header["block_hash"], format!("0x{:064x}", number));
+		}
+
+		let (current, peak) = *counter.lock().unwrap();
+		assert_eq!(current, 0);
+		assert!(peak <= MAX, "peak {}", peak);
+
+		// the blocks are fetched concurrently under the cap
+		assert!(peak > 1, "peak {}", peak);
+	}
+
+	#[test]
+	fn test_get_events_raw() {
+		init_globals();
+
+		let events = vec![
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: Event::system(srml_system::Event::ExtrinsicSuccess),
+			},
+			EventRecord {
+				phase: Phase::ApplyExtrinsic(1),
+				event: Event::system(srml_system::Event::ExtrinsicFailed),
+			},
+		];
+		let raw = format!("0x{}", hex::encode(events.encode()));
+
+		let (uri, _methods) = start_node_with(json!([]), json!(raw));
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let result = runtime
+			.block_on(chain.get_events_raw(0, 65))
+			.unwrap()
+			.unwrap();
+		assert_eq!(result, json!(raw));
+
+		// decodes back to the event records
+		let result = hex::decode(&result.as_str().unwrap()[2..]).unwrap();
+		let decoded: Vec<EventRecord<Event>> = Decode::decode(&mut &result[..]).unwrap();
+		assert_eq!(decoded.len(), 2);
+		assert_eq!(decoded[1].phase, Phase::ApplyExtrinsic(1));
+		assert_eq!(decoded.encode(), events.encode());
+
+		// not yet produced
+		assert!(runtime.block_on(chain.get_events_raw(0, 66)).is_err());
+
+		// no events
+		let (uri, _methods) = start_node_with(json!([]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+
+		assert_eq!(runtime.block_on(chain.get_events_raw(0, 65)).unwrap(), None);
+	}
+
+	#[test]
+	fn test_get_finalized_timestamp() {
+		init_globals();
+
+		// an unsigned timestamp::set(now)
+		let mut call = vec![0x01, 0x00, 0x00];
+		call.extend(Compact(1596874295u64).encode());
+		let mut extrinsic = Compact(call.len() as u32).encode();
+		extrinsic.extend(call);
+		let extrinsic = format!("0x{}", hex::encode(extrinsic));
+
+		let (uri, methods) = start_node_with(json!([extrinsic]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let result = runtime.block_on(chain.get_finalized_timestamp(0)).unwrap();
+		assert_eq!(result, json!({ "number": 65, "timestamp": 1596874295u64 }));
+		assert!(methods
+			.lock()
+			.unwrap()
+			.contains(&"chain_getFinalizedHead".to_string()));
+
+		// no timestamp inherent
+		let (uri, _methods) = start_node_with(json!([]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+
+		let result = runtime.block_on(chain.get_finalized_timestamp(0)).unwrap();
+		assert_eq!(result, serde_json::Value::Null);
+	}
+
+	#[test]
+	fn test_get_block_by_number_signed() {
+		init_globals();
+
+		let sender = "1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc54";
+		let other = "94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d";
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+		let other_extrinsic = extrinsic.replacen(sender, other, 1);
+
+		// an unsigned timestamp::set(now)
+		let mut call = vec![0x01, 0x00, 0x00];
+		call.extend(Compact(1596874295u64).encode());
+		let mut unsigned = Compact(call.len() as u32).encode();
+		unsigned.extend(call);
+		let unsigned = format!("0x{}", hex::encode(unsigned));
+
+		let (uri, _methods) = start_node_with(
+			json!([unsigned, extrinsic, other_extrinsic]),
+			serde_json::Value::Null,
+		);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let address = |public_key: &str| {
+			hex::decode(public_key)
+				.unwrap()
+				.to_address(Hrp::TESTNET)
+				.unwrap()
+				.0
+		};
+
+		let block = runtime
+			.block_on(chain.get_block_by_number_signed(0, NumberOrHex::Number(65)))
+			.unwrap()
+			.unwrap();
+		let extrinsics = block["extrinsics"].as_array().unwrap();
+		assert_eq!(extrinsics.len(), 3);
+
+		assert_eq!(extrinsics[0]["signature"], serde_json::Value::Null);
+		assert_eq!(
+			extrinsics[1]["signature"]["sender_address"],
+			json!(address(sender))
+		);
+		assert_eq!(
+			extrinsics[2]["signature"]["sender_address"],
+			json!(address(other))
+		);
+		for extrinsic in &extrinsics[1..] {
+			assert!(extrinsic["signature"]["sender_shard_num"].is_u64());
+		}
+
+		// not yet produced
+		let result = runtime.block_on(chain.get_block_by_number_signed(0, NumberOrHex::Number(66)));
+		assert!(result.is_err());
+		let error = result.unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": 66, "best": 65 })));
+	}
+
+	#[test]
+	fn test_get_balance_finalized_state() {
+		init_globals();
+
+		let address = "tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6";
+
+		let chain_with = |finalized_state: bool| {
+			let (uri, methods) = start_node();
+			let config = format!("finalized_state = {}", finalized_state);
+			(chain_with_node_config(&uri, 4, &config), methods)
+		};
+		let mut runtime = Runtime::new().unwrap();
+
+		// the best state by default
+		let (chain, methods) = chain_with(false);
+		runtime
+			.block_on(chain.get_balance(address.to_string(), None))
+			.unwrap();
+		runtime
+			.block_on(chain.get_nonce(address.to_string(), None))
+			.unwrap();
+		let called = methods.lock().unwrap().clone();
+		assert!(!called.contains(&"chain_getFinalizedHead".to_string()));
+
+		// the finalized state read at the finalized hash
+		let (chain, methods) = chain_with(true);
+		runtime
+			.block_on(chain.get_balance(address.to_string(), None))
+			.unwrap();
+		runtime
+			.block_on(chain.get_nonce(address.to_string(), None))
+			.unwrap();
+		let called = methods.lock().unwrap().clone();
+		let finalized = called
+			.iter()
+			.filter(|x| x.as_str() == "chain_getFinalizedHead")
+			.count();
+		assert_eq!(finalized, 2);
+
+		// the batch too, read once for the shard
+		let (chain, methods) = chain_with(true);
+		runtime
+			.block_on(chain.get_account_info_batch(vec![address.to_string(); 2], None))
+			.unwrap();
+		let called = methods.lock().unwrap().clone();
+		let finalized = called
+			.iter()
+			.filter(|x| x.as_str() == "chain_getFinalizedHead")
+			.count();
+		assert_eq!(finalized, 1);
+
+		// a given block number is not affected
+		let (chain, methods) = chain_with(true);
+		runtime
+			.block_on(chain.get_balance(address.to_string(), Some(NumberOrHex::Number(60))))
+			.unwrap();
+		let called = methods.lock().unwrap().clone();
+		assert!(called.contains(&"chain_getBlockHash".to_string()));
+		assert!(!called.contains(&"chain_getFinalizedHead".to_string()));
+	}
+
+	#[test]
+	fn test_get_block_hash_batch() {
+		init_globals();
+
+		let (uri, methods) = start_node_with(json!([]), serde_json::Value::Null);
+		let chain = chain_with_node_config(&uri, 1, "max_block_hash_batch = 4");
+		chain.caches.block.set_finalized_number(0, 60);
+		let mut runtime = Runtime::new().unwrap();
+
+		let count = || {
+			methods
+				.lock()
+				.unwrap()
+				.iter()
+				.filter(|x| *x == "chain_getBlockHash")
+				.count()
+		};
+
+		let hash = format!("0x{}", "00".repeat(32));
+		let result = runtime
+			.block_on(chain.get_block_hash_batch(0, vec![1, 65, 66, 100]))
+			.unwrap();
+		assert_eq!(
+			result,
+			json!({ "1": hash, "65": hash, "66": null, "100": null })
+		);
+		assert_eq!(count(), 4);
+
+		// the finalized hashes are cached
+		let result = runtime
+			.block_on(chain.get_block_hash_batch(0, vec![1, 65]))
+			.unwrap();
+		assert_eq!(result, json!({ "1": hash, "65": hash }));
+		assert_eq!(count(), 5);
+
+		let result = runtime.block_on(chain.get_block_hash_batch(0, vec![1, 2, 3, 4, 5]));
+		assert_eq!(
+			result.unwrap_err().message,
+			"Too many block numbers".to_string()
+		);
+	}
+
+	#[test]
+	fn test_submit_extrinsic_unsigned() {
+		init_globals();
+
+		let (uri, methods) = start_node_with(json!([]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		// an unsigned timestamp::set(now)
+		let mut call = vec![0x01, 0x00, 0x00];
+		call.extend(Compact(1596874295u64).encode());
+		let mut extrinsic = Compact(call.len() as u32).encode();
+		extrinsic.extend(call);
+
+		// rejected before reaching the node
+		let result = runtime.block_on(chain.submit_extrinsic(Hex(extrinsic)));
+		assert_eq!(
+			result.unwrap_err().message,
+			"Unsigned extrinsic has no signer".to_string()
+		);
+		assert!(methods.lock().unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_estimate_finalization_seconds() {
+		// 10 blocks finalized in 300 seconds
+		let from = (90, 1596874000);
+		let to = (100, 1596874300);
+		assert_eq!(estimate_finalization_seconds(105, 100, from, to), Some(150));
+		assert_eq!(estimate_finalization_seconds(100, 100, from, to), Some(0));
+
+		// the rate unknown
+		assert_eq!(estimate_finalization_seconds(105, 100, to, to), None);
+		assert_eq!(estimate_finalization_seconds(105, 100, to, from), None);
+		assert_eq!(
+			estimate_finalization_seconds(105, 100, (90, to.1), to),
+			None
+		);
+
+		// overflow
+		assert_eq!(
+			estimate_finalization_seconds(u64::max_value(), 100, from, to),
+			None
+		);
+	}
+
+	#[test]
+	fn test_get_finalization_estimate() {
+		init_globals();
+
+		// an unsigned timestamp::set(now)
+		let mut call = vec![0x01, 0x00, 0x00];
+		call.extend(Compact(1596874295u64).encode());
+		let mut extrinsic = Compact(call.len() as u32).encode();
+		extrinsic.extend(call);
+		let extrinsic = format!("0x{}", hex::encode(extrinsic));
+
+		let (uri, _methods) = start_node_with(json!([extrinsic]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		// already finalized
+		let result = runtime
+			.block_on(chain.get_finalization_estimate(0, 60))
+			.unwrap();
+		assert_eq!(
+			result,
+			json!({ "number": 60, "finalized_number": 65, "seconds": 0 })
+		);
+
+		// every block of the node has the same timestamp, the rate unknown
+		let result = runtime
+			.block_on(chain.get_finalization_estimate(0, 70))
+			.unwrap();
+		assert_eq!(
+			result,
+			json!({ "number": 70, "finalized_number": 65, "seconds": null })
+		);
+	}
+
+	#[test]
+	fn test_get_finalization_estimate_rate() {
+		init_globals();
+
+		// the hash is the block number, a block every 30 seconds, finalized at 100 and the best is 110
+		let number_of = |hash: &serde_json::Value| {
+			u64::from_str_radix(hash.as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+		};
+		let header = |number: u64| {
+			json!({
+				"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+				"parentHash": format!("0x{:064x}", number.saturating_sub(1)),
+				"stateRoot": format!("0x{}", "00".repeat(32)),
+				"number": format!("0x{:x}", number),
+			})
+		};
+		let (uri, _methods) = start_node_by(move |method, params| match method {
+			"chain_getBlockHash" => json!(format!("0x{:064x}", params[0].as_u64().unwrap())),
+			"chain_getFinalizedHead" => json!(format!("0x{:064x}", 100)),
+			"chain_getHeader" if params[0].is_string() => header(number_of(&params[0])),
+			"chain_getHeader" => header(110),
+			"chain_getBlock" => {
+				let number = number_of(&params[0]);
+
+				// an unsigned timestamp::set(now)
+				let mut call = vec![0x01, 0x00, 0x00];
+				call.extend(Compact(1596874000 + number * 30).encode());
+				let mut extrinsic = Compact(call.len() as u32).encode();
+				extrinsic.extend(call);
+
+				json!({
+					"block": {
+						"header": header(number),
+						"extrinsics": [format!("0x{}", hex::encode(extrinsic))],
+					},
+				})
+			}
+			_ => serde_json::Value::Null,
+		});
+		let chain = chain_with_node_config(&uri, 1, "max_block_number_margin = 20");
+		chain.caches.head.insert(0, Head::new(Some(110), Some(100)));
+		let mut runtime = Runtime::new().unwrap();
+
+		let result = runtime
+			.block_on(chain.get_finalization_estimate(0, 105))
+			.unwrap();
+		assert_eq!(
+			result,
+			json!({ "number": 105, "finalized_number": 100, "seconds": 150 })
+		);
+
+		// too far above the best number
+		let error = runtime
+			.block_on(chain.get_finalization_estimate(0, 131))
+			.unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": 131, "best": 110 })));
+	}
+
+	#[test]
+	fn test_get_extrinsic_by_raw_in_range() {
+		init_globals();
+
+		// a signed transfer
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+		let raw = Hex(hex::decode(&extrinsic[2..]).unwrap());
+
+		let (uri, _methods) = start_node_with(json!([extrinsic]), serde_json::Value::Null);
+		let chain = chain_with_node_config(&uri, 1, "max_block_range = 10");
+		let mut runtime = Runtime::new().unwrap();
+
+		// every block of the mock node has the extrinsic, the first is returned
+		let tx = runtime
+			.block_on(chain.get_extrinsic_by_raw_in_range(0, 60, 65, raw.clone()))
+			.unwrap()
+			.unwrap();
+		assert_eq!(tx["block_number"], 60);
+		assert!(tx["call"].is_object());
+		assert!(tx.get("raw").map(|x| x.is_null()).unwrap_or(true));
+
+		// not found
+		let tx = runtime
+			.block_on(chain.get_extrinsic_by_raw_in_range(0, 60, 65, Hex(vec![0u8; 32])))
+			.unwrap();
+		assert!(tx.is_none());
+
+		// out of the max span
+		assert!(runtime
+			.block_on(chain.get_extrinsic_by_raw_in_range(0, 0, 100, raw))
+			.is_err());
+	}
+
+	#[test]
+	fn test_get_public_key() {
+		init_globals();
+
+		let config: Config = toml::from_str("[shards]").unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+
+		// round trip of the address encoding
+		let public_key = "1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc54";
+		let address = hex::decode(public_key)
+			.unwrap()
+			.to_address(Hrp::TESTNET)
+			.unwrap()
+			.0;
+		let result = chain.get_public_key(address).wait().unwrap();
+		assert_eq!(result, json!(format!("0x{}", public_key)));
+
+		let result = chain.get_public_key("tyee1invalid".to_string()).wait();
+		assert_eq!(result.unwrap_err().message, "Invalid address".to_string());
+	}
+
+	#[test]
+	fn test_max_block_number_margin() {
+		init_globals();
+
+		let (uri, methods) = start_node();
+		let chain = chain_with_node_config(&uri, 1, "max_block_number_margin = 100");
+		chain.caches.head.insert(0, Head::new(Some(65), Some(60)));
+		let mut runtime = Runtime::new().unwrap();
+
+		// rejected without a node call
+		let result = runtime.block_on(chain.get_block_by_number(
+			0,
+			NumberOrHex::Number(u64::MAX),
+			None,
+			None,
+			None,
+			None,
+		));
+		let error = result.unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": u64::MAX, "best": 65 })));
+		assert!(methods.lock().unwrap().is_empty());
+
+		// the state, the batches and the ranges too
+		let address = "tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6".to_string();
+		let max = Some(NumberOrHex::Number(u64::MAX));
+		let key = Hex(vec![1u8; 32]);
+		let results = vec![
+			runtime.block_on(chain.get_nonce(address.clone(), max.clone())),
+			runtime.block_on(chain.get_balance(address.clone(), max.clone())),
+			runtime.block_on(chain.get_account_exists(address.clone(), max.clone())),
+			runtime.block_on(chain.get_balance_locks(address.clone(), max.clone())),
+			runtime.block_on(chain.get_full_account(address.clone(), max.clone())),
+			runtime.block_on(chain.get_block_hash_batch(0, vec![60, u64::MAX])),
+			runtime.block_on(chain.get_storage_batch(0, vec![key.clone()], Some(u64::MAX))),
+			runtime.block_on(chain.get_storage_changes(0, key, u64::MAX - 1, u64::MAX)),
+			runtime.block_on(chain.get_address_balance_history(
+				address.clone(),
+				NumberOrHex::Number(u64::MAX - 1),
+				NumberOrHex::Number(u64::MAX),
+				NumberOrHex::Number(1),
+			)),
+			runtime.block_on(chain.get_headers_by_number_range(
+				0,
+				NumberOrHex::Number(u64::MAX - 1),
+				NumberOrHex::Number(u64::MAX),
+			)),
+			runtime.block_on(chain.get_relay_transfer_status(
+				Hex(vec![0u8; 32]),
+				NumberOrHex::Number(u64::MAX - 1),
+				NumberOrHex::Number(u64::MAX),
+			)),
+		];
+		for result in results {
+			assert_eq!(result.unwrap_err().message, "Block not yet produced");
+		}
+		let result = runtime
+			.block_on(chain.get_account_info_batch(vec![address], max))
+			.unwrap();
+		assert_eq!(result[0]["error"]["message"], "Block not yet produced");
+		assert!(methods.lock().unwrap().is_empty());
+
+		// within the margin, up to the node
+		let result =
+			runtime.block_on(chain.get_header_by_number(0, NumberOrHex::Number(100), None));
+		assert!(result.is_err());
+		assert!(!methods.lock().unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_get_finality_lag_all() {
+		let chain = chain_with_node("http://127.0.0.1:1", 3);
+		chain.caches.head.insert(0, Head::new(Some(100), Some(98)));
+		// the finality of the shard stalls
+		chain.caches.head.insert(1, Head::new(Some(100), Some(20)));
+		let mut runtime = Runtime::new().unwrap();
+
+		// the shard 2 degrades, its node unreachable
+		let result = runtime.block_on(chain.get_finality_lag_all()).unwrap();
+		assert_eq!(
+			result,
+			json!({
+				"shards": [
+					{ "shard_num": 0, "best": 100, "finalized": 98, "lag": 2 },
+					{ "shard_num": 1, "best": 100, "finalized": 20, "lag": 80 },
+					{ "shard_num": 2, "best": null, "finalized": null, "lag": null },
+				],
+				"max_lag": 80,
+			})
+		);
+	}
+
+	#[test]
+	fn test_max_batch_addresses() {
+		init_globals();
+
+		let (uri, methods) = start_node();
+		let chain = chain_with_node_config(&uri, 1, "max_batch_addresses = 2");
+		let mut runtime = Runtime::new().unwrap();
+
+		let addresses = |count: usize| vec!["invalid".to_string(); count];
+
+		// at the limit, the invalid addresses fail alone
+		let result = runtime
+			.block_on(chain.get_account_info_batch(addresses(2), None))
+			.unwrap();
+		assert_eq!(result.as_array().unwrap().len(), 2);
+
+		// over the limit
+		let error = runtime
+			.block_on(chain.get_account_info_batch(addresses(3), None))
+			.unwrap_err();
+		assert_eq!(error.message, "Range too large");
+		assert_eq!(error.data, Some(json!({ "span": 3, "max": 2 })));
+		assert!(methods.lock().unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_get_account_exists() {
+		init_globals();
+
+		let address = "tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6";
+
+		let mut runtime = Runtime::new().unwrap();
+
+		// the keys present, even of a zero value
+		let chain = chain_with_storage(json!("0x0000000000000000"));
+		let result = runtime
+			.block_on(chain.get_account_exists(address.to_string(), None))
+			.unwrap();
+		assert_eq!(result, json!(true));
+
+		// never used
+		let chain = chain_with_storage(serde_json::Value::Null);
+		let result = runtime
+			.block_on(chain.get_account_exists(address.to_string(), Some(NumberOrHex::Number(60))))
+			.unwrap();
+		assert_eq!(result, json!(false));
+
+		// not read at the best state above the best number
+		let chain = chain_with_storage(json!("0x0000000000000000"));
+		let error = runtime
+			.block_on(chain.get_account_exists(address.to_string(), Some(NumberOrHex::Number(70))))
+			.unwrap_err();
+		assert_eq!(error.message, "Block not yet produced");
+		assert_eq!(error.data, Some(json!({ "number": 70, "best": 65 })));
+
+		assert!(runtime
+			.block_on(chain.get_account_exists("tyee1invalid".to_string(), None))
+			.is_err());
+	}
+
+	#[test]
+	fn test_get_balance_locks() {
+		init_globals();
+
+		let address = "tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6";
+
+		// encoded as the lock of (id, amount, until, reasons)
+		let lock = (*b"staking ", 1000u128, 100u64, 0b0000_0011u8);
+		let raw = vec![lock].encode();
+		assert_eq!(
+			get_balance_locks(&raw).unwrap(),
+			json!([{
+				"id": "staking ",
+				"amount": 1000,
+				"until": 100,
+				"reasons": ["TransactionPayment", "Transfer"],
+			}])
+		);
+		assert!(get_balance_locks(&raw[..raw.len() - 1]).is_err());
+
+		let mut runtime = Runtime::new().unwrap();
+
+		let chain = chain_with_storage(json!(Hex(raw)));
+		let result = runtime
+			.block_on(chain.get_balance_locks(address.to_string(), None))
+			.unwrap();
+		assert_eq!(result[0]["amount"], json!(1000));
+		assert_eq!(result.as_array().unwrap().len(), 1);
+
+		// no locks stored
+		let chain = chain_with_storage(serde_json::Value::Null);
+		let result = runtime
+			.block_on(chain.get_balance_locks(address.to_string(), Some(NumberOrHex::Number(60))))
+			.unwrap();
+		assert_eq!(result, json!([]));
+	}
+
+	#[test]
+	fn test_get_full_account() {
+		init_globals();
+
+		let address = "tyee1zqe7q4mgy2n2sdhkz2sexqmqggzsu2rd53tp7hx9mrh9vrrym32q0c3km6";
+
+		let mut runtime = Runtime::new().unwrap();
+
+		// never used
+		let chain = chain_with_storage(serde_json::Value::Null);
+		let result = runtime
+			.block_on(chain.get_full_account(address.to_string(), None))
+			.unwrap();
+		let (_, shard_num) = get_address_shard_num(address.to_string(), 4).unwrap();
+		assert_eq!(result["shard_num"], json!(shard_num));
+		assert_eq!(result["nonce"]["nonce"], json!(0));
+		assert_eq!(result["free_balance"], json!(0));
+		assert_eq!(result["reserved_balance"], json!(0));
+		assert_eq!(result["locks"], json!([]));
+
+		// the value of a single byte is no nonce nor balance, but an empty lock list
+		let chain = chain_with_storage(json!("0x00"));
+		let result = runtime
+			.block_on(chain.get_full_account(address.to_string(), Some(NumberOrHex::Number(60))))
+			.unwrap();
+		assert_eq!(result["nonce"], serde_json::Value::Null);
+		assert_eq!(result["free_balance"], serde_json::Value::Null);
+		assert_eq!(result["reserved_balance"], serde_json::Value::Null);
+		assert_eq!(result["locks"], json!([]));
+
+		assert!(runtime
+			.block_on(chain.get_full_account("tyee1invalid".to_string(), None))
+			.is_err());
+
+		// the read of the nonce failed by an unexpected response, the others still returned
+		let (public_key, _) = get_address_shard_num(address.to_string(), 4).unwrap();
+		let nonce_key = get_map_storage_key(&public_key, b"System AccountNonce");
+		let nonce_key = json!(Hex(nonce_key.0));
+		let (uri, _methods) = start_node_by(move |method, params| match method {
+			"chain_getBlockHash" => json!(format!("0x{}", "00".repeat(32))),
+			"chain_getHeader" => json!({
+				"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+				"parentHash": format!("0x{}", "00".repeat(32)),
+				"stateRoot": format!("0x{}", "00".repeat(32)),
+				"number": "0x41",
+			}),
+			"state_getStorage" if params[0] == nonce_key => json!(5),
+			_ => serde_json::Value::Null,
+		});
+		let chain = chain_with_node(&uri, 4);
+		let result = runtime
+			.block_on(chain.get_full_account(address.to_string(), None))
+			.unwrap();
+		assert_eq!(result["nonce"], serde_json::Value::Null);
+		assert_eq!(result["free_balance"], json!(0));
+		assert_eq!(result["reserved_balance"], json!(0));
+		assert_eq!(result["locks"], json!([]));
+	}
+
+	#[test]
+	fn test_get_parent_header() {
+		// the hash is the block number
+		let hash = |number: u64| format!("0x{:064x}", number);
+		let (uri, _methods) = start_node_by(move |method, params| match method {
+			"chain_getHeader" => {
+				let number =
+					u64::from_str_radix(params[0].as_str().unwrap().trim_start_matches("0x"), 16)
+						.unwrap();
+				json!({
+					"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+					"parentHash": hash(number.saturating_sub(1)),
+					"stateRoot": format!("0x{}", "00".repeat(32)),
+					"number": format!("0x{:x}", number),
+				})
+			}
+			_ => serde_json::Value::Null,
+		});
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		// walk back from the block 3 to the genesis by the parent hash
+		let mut block_hash = Hex(hex::decode(&hash(3)[2..]).unwrap());
+		let mut parent_hash = json!(hash(2));
+		for number in (0..3).rev() {
+			let header = runtime
+				.block_on(chain.get_parent_header(0, block_hash))
+				.unwrap()
+				.unwrap();
+			assert_eq!(header["number"], number);
+			assert_eq!(header["block_hash"], parent_hash);
+			block_hash = serde_json::from_value(header["block_hash"].clone()).unwrap();
+			parent_hash = header["parent_hash"].clone();
+		}
+
+		// the genesis has no parent
+		assert_eq!(block_hash, Hex(vec![0u8; 32]));
+		let header = runtime
+			.block_on(chain.get_parent_header(0, block_hash))
+			.unwrap();
+		assert_eq!(header, None);
+	}
+
+	#[test]
+	fn test_get_block_time_stats() {
+		// 30s, 10s, 20s, and block 14 without a timestamp
+		let timestamps = vec![
+			(10, 1596874000),
+			(11, 1596874030),
+			(12, 1596874040),
+			(13, 1596874060),
+			(15, 1596874100),
+		];
+		assert_eq!(
+			get_block_time_stats(&timestamps),
+			json!({ "intervals": 3, "min": 10, "max": 30, "average": 20.0 })
+		);
+
+		assert_eq!(
+			get_block_time_stats(&timestamps[..1]),
+			json!({ "intervals": 0, "min": null, "max": null, "average": null })
+		);
+	}
+
+	#[test]
+	fn test_get_block_time_stats_of_node() {
+		init_globals();
+
+		// an unsigned timestamp::set(now)
+		let mut call = vec![0x01, 0x00, 0x00];
+		call.extend(Compact(1596874295u64).encode());
+		let mut extrinsic = Compact(call.len() as u32).encode();
+		extrinsic.extend(call);
+		let extrinsic = format!("0x{}", hex::encode(extrinsic));
+
+		let (uri, _methods) = start_node_with(json!([extrinsic]), serde_json::Value::Null);
+		let chain = chain_with_node_config(&uri, 1, "max_block_time_samples = 10");
+		let mut runtime = Runtime::new().unwrap();
+
+		// every block of the node has the same timestamp
+		let result = runtime.block_on(chain.get_block_time_stats(0, 5)).unwrap();
+		assert_eq!(
+			result,
+			json!({ "from": 61, "to": 65, "intervals": 4, "min": 0, "max": 0, "average": 0.0 })
+		);
+
+		assert!(runtime.block_on(chain.get_block_time_stats(0, 11)).is_err());
+	}
+
+	#[test]
+	fn test_get_extrinsic_by_hash_all_shards() {
+		init_globals();
+
+		// a signed transfer
+		let extrinsic = "0x310281ff1033e0576822a6a836f612a193036042050e286da4561f5cc5d8ee560c64dc5440b2595b4c269c29377b658abe62303d59f975d0914f205d5fe8c7f24974a56007b979bf73e76211c3c3826293def93b882ef03a0a48e479693c2cbcb0425c0704b5030400ff94d988b42d96dcbd6605ff47f19c6ab35f626eb1bc8bbd28f59a74997a253a3d0284d717";
+
+		// only the node of shard 2 has the extrinsic
+		let uris = (0..4)
+			.map(|shard_num| {
+				let extrinsics = match shard_num {
+					2 => json!([extrinsic]),
+					_ => json!([]),
+				};
+				start_node_with(extrinsics, serde_json::Value::Null).0
+			})
+			.collect::<Vec<_>>();
+		let config: Config = toml::from_str(&format!(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["{}"]
+			[shards.1]
+			rpc = ["{}"]
+			[shards.2]
+			rpc = ["{}"]
+			[shards.3]
+			rpc = ["{}"]
+			"#,
+			uris[0], uris[1], uris[2], uris[3]
+		))
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+		let chain = Chain::new(Arc::new(RwLock::new(config)), rpc_client, caches);
+		let mut runtime = Runtime::new().unwrap();
+
+		let block = runtime
+			.block_on(chain.get_block_by_number(2, NumberOrHex::Number(65), None, None, None, None))
+			.unwrap()
+			.unwrap();
+		let hash: Hex<Vec<u8>> =
+			serde_json::from_value(block["extrinsics"][0]["hash"].clone()).unwrap();
+
+		let tx = runtime
+			.block_on(chain.get_extrinsic_by_hash_all_shards(65, hash))
+			.unwrap()
+			.unwrap();
+		assert_eq!(tx["shard_num"], 2);
+		assert_eq!(tx["hash"], block["extrinsics"][0]["hash"]);
+		assert!(tx["call"].is_object());
+
+		// not found
+		let tx = runtime
+			.block_on(chain.get_extrinsic_by_hash_all_shards(65, Hex(vec![0u8; 32])))
+			.unwrap();
+		assert!(tx.is_none());
+	}
+
+	#[test]
+	fn test_check_shard_num() {
+		let (uri, methods) = start_node();
+		let chain = chain_with_node(&uri, 4);
+
+		assert!(check_shard_num(3, &chain.config()).is_ok());
+		match check_shard_num(4, &chain.config()) {
+			Err(errors::Error(errors::ErrorKind::InvalidShard, _)) => (),
+			_ => panic!("expected InvalidShard"),
+		}
+
+		// the shard num of the shard count is rejected before any node request
+		let mut runtime = Runtime::new().unwrap();
+		let e = runtime.block_on(chain.get_shard_summary(4)).unwrap_err();
+		assert_eq!(e.message, "Invalid shard");
+		let e = runtime.block_on(chain.get_peer_count(4)).unwrap_err();
+		assert_eq!(e.message, "Invalid shard");
+		let e = runtime.block_on(chain.get_node_version(4)).unwrap_err();
+		assert_eq!(e.message, "Invalid shard");
+		let e = runtime.block_on(chain.get_finalized_hash(4)).unwrap_err();
+		assert_eq!(e.message, "Invalid shard");
+		assert!(methods.lock().unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_get_digest_log() {
+		let log = DigestItem::<Hash, AuthorityId, ()>::Consensus(YEE_POW_ENGINE_ID, vec![1, 2, 3])
+			.encode();
+		assert_eq!(
+			get_digest_log(&log),
+			json!({
+				"type": "consensus",
+				"engine_id": String::from_utf8_lossy(&YEE_POW_ENGINE_ID),
+				"data": "0x010203",
+			})
+		);
+
+		let log = DigestItem::<Hash, AuthorityId, ()>::Other(vec![4, 5]).encode();
+		assert_eq!(
+			get_digest_log(&log),
+			json!({ "type": "other", "data": "0x0405" })
+		);
+
+		// the seal signature is not decoded, the payload is returned as is
+		let log = DigestItem::<Hash, AuthorityId, ()>::Seal(7, ()).encode();
+		assert_eq!(
+			get_digest_log(&log),
+			json!({ "type": "seal", "data": "0x0700000000000000" })
+		);
+
+		let log =
+			DigestItem::<Hash, AuthorityId, ()>::ChangesTrieRoot(Hash::from_slice(&[1u8; 32]))
+				.encode();
+		assert_eq!(
+			get_digest_log(&log),
+			json!({ "type": "changes_trie_root", "data": format!("0x{}", "01".repeat(32)) })
+		);
+
+		let authority = AuthorityId::decode(&mut &[2u8; 32][..]).unwrap();
+		let log = DigestItem::<Hash, AuthorityId, ()>::AuthoritiesChange(vec![authority]).encode();
+		assert_eq!(
+			get_digest_log(&log),
+			json!({
+				"type": "authorities_change",
+				"authorities": [format!("0x{}", "02".repeat(32))],
+				"data": format!("0x04{}", "02".repeat(32)),
+			})
+		);
+
+		let mut log = vec![PRE_RUNTIME_DIGEST_TYPE];
+		log.extend((YEE_POW_ENGINE_ID, vec![7u8, 8]).encode());
+		assert_eq!(
+			get_digest_log(&log),
+			json!({
+				"type": "pre_runtime",
+				"engine_id": String::from_utf8_lossy(&YEE_POW_ENGINE_ID),
+				"data": "0x0708",
+			})
+		);
+		assert_eq!(
+			get_digest_log(&[PRE_RUNTIME_DIGEST_TYPE, 1]),
+			json!({ "type": "unknown", "data": "0x01" })
+		);
+
+		// an unknown item type
+		assert_eq!(
+			get_digest_log(&[0xff, 1, 2]),
+			json!({ "type": "unknown", "data": "0x0102" })
+		);
+		assert_eq!(
+			get_digest_log(&[]),
+			json!({ "type": "unknown", "data": "0x" })
+		);
+	}
+
+	#[test]
+	fn test_get_header_digest() {
+		// the header of the mock node has no digest
+		let (uri, methods) = start_node();
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		let result = runtime
+			.block_on(chain.get_header_digest(0, Hex(vec![0u8; 32])))
+			.unwrap();
+		assert_eq!(result, json!([]));
+		assert!(methods
+			.lock()
+			.unwrap()
+			.contains(&"chain_getHeader".to_string()));
+
+		assert!(runtime
+			.block_on(chain.get_header_digest(4, Hex(vec![0u8; 32])))
+			.is_err());
+
+		// a header with the pow seal, the changes trie root and an other item
+		let logs = vec![
+			DigestItem::<Hash, AuthorityId, ()>::Consensus(YEE_POW_ENGINE_ID, vec![1, 2, 3])
+				.encode(),
+			DigestItem::<Hash, AuthorityId, ()>::ChangesTrieRoot(Hash::from_slice(&[1u8; 32]))
+				.encode(),
+			DigestItem::<Hash, AuthorityId, ()>::Other(vec![4, 5]).encode(),
+		];
+		let (uri, _methods) = start_node_by(move |method, _params| match method {
+			"chain_getHeader" => json!({
+				"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+				"parentHash": format!("0x{}", "00".repeat(32)),
+				"stateRoot": format!("0x{}", "00".repeat(32)),
+				"number": "0x41",
+				"digest": {
+					"logs": logs.iter().map(|x| format!("0x{}", hex::encode(x))).collect::<Vec<_>>(),
+				},
+			}),
+			_ => serde_json::Value::Null,
+		});
+		let chain = chain_with_node(&uri, 1);
+
+		let result = runtime
+			.block_on(chain.get_header_digest(0, Hex(vec![0u8; 32])))
+			.unwrap();
+		assert_eq!(
+			result,
+			json!([
+				{
+					"type": "consensus",
+					"engine_id": String::from_utf8_lossy(&YEE_POW_ENGINE_ID),
+					"data": "0x010203",
+				},
+				{ "type": "changes_trie_root", "data": format!("0x{}", "01".repeat(32)) },
+				{ "type": "other", "data": "0x0405" },
+			])
+		);
+	}
+
+	#[test]
+	fn test_verify_extrinsics_root() {
+		let (uri, methods) = start_node_with(json!([]), serde_json::Value::Null);
+		let chain = chain_with_node(&uri, 1);
+		let mut runtime = Runtime::new().unwrap();
+
+		// the header of the mock node has a zero root
+		let result = runtime
+			.block_on(chain.verify_extrinsics_root(0, 65))
+			.unwrap();
+		assert_eq!(
+			result,
+			json!({
+				"valid": false,
+				"computed": "0x03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314",
+				"expected": format!("0x{}", "00".repeat(32)),
+			})
+		);
+		assert!(methods
+			.lock()
+			.unwrap()
+			.contains(&"chain_getBlock".to_string()));
+
+		// not yet produced
+		assert!(runtime
+			.block_on(chain.verify_extrinsics_root(0, 66))
+			.is_err());
+
+		// the header holding the root of the empty block
+		let root = "0x03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314";
+		let header = json!({
+			"extrinsicsRoot": root,
+			"parentHash": format!("0x{}", "00".repeat(32)),
+			"stateRoot": format!("0x{}", "00".repeat(32)),
+			"number": "0x41",
+		});
+		let (uri, _methods) = start_node_by(move |method, _params| match method {
+			"chain_getBlockHash" => json!(format!("0x{}", "00".repeat(32))),
+			"chain_getHeader" => header.clone(),
+			"chain_getBlock" => json!({
+				"block": {
+					"header": header.clone(),
+					"extrinsics": [],
+				},
+			}),
+			_ => serde_json::Value::Null,
+		});
+		let chain = chain_with_node(&uri, 1);
+
+		let result = runtime
+			.block_on(chain.verify_extrinsics_root(0, 65))
+			.unwrap();
+		assert_eq!(
+			result,
+			json!({
+				"valid": true,
+				"computed": root,
+				"expected": root,
+			})
+		);
 	}
-	let mut array = [0; LEN];
-	let bytes = &bytes[..];
-	array.copy_from_slice(bytes);
-	Ok(u128::from_le_bytes(array))
 }
@@ -0,0 +1,194 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::CircuitBreaker;
+
+/// Stop calling a node after consecutive failures, until the cooldown elapses.
+/// A single call is let through after the cooldown to probe the node, the others fail fast until
+/// it resolves, a failure of it opens again. A probe not resolved within the cooldown is lost.
+pub struct Breaker {
+	failure_threshold: u32,
+	cooldown: Duration,
+	state: Mutex<State>,
+}
+
+struct State {
+	consecutive_failures: u32,
+	opened_at: Option<Instant>,
+	probe_started_at: Option<Instant>,
+}
+
+impl Breaker {
+	pub fn new(config: &CircuitBreaker) -> Self {
+		Breaker {
+			failure_threshold: config.failure_threshold.max(1),
+			cooldown: Duration::from_millis(config.cooldown_ms),
+			state: Mutex::new(State {
+				consecutive_failures: 0,
+				opened_at: None,
+				probe_started_at: None,
+			}),
+		}
+	}
+
+	/// Return false if the calls should fail fast
+	pub fn check(&self) -> bool {
+		self.check_at(Instant::now())
+	}
+
+	fn check_at(&self, now: Instant) -> bool {
+		let mut state = self.state.lock().expect("qed");
+		let opened_at = match state.opened_at {
+			Some(opened_at) => opened_at,
+			None => return true,
+		};
+		if now.duration_since(opened_at) < self.cooldown {
+			return false;
+		}
+		match state.probe_started_at {
+			Some(probe_started_at) if now.duration_since(probe_started_at) < self.cooldown => false,
+			_ => {
+				state.probe_started_at = Some(now);
+				true
+			}
+		}
+	}
+
+	pub fn on_success(&self) {
+		let mut state = self.state.lock().expect("qed");
+		state.consecutive_failures = 0;
+		state.opened_at = None;
+		state.probe_started_at = None;
+	}
+
+	pub fn on_failure(&self) {
+		self.on_failure_at(Instant::now())
+	}
+
+	fn on_failure_at(&self, now: Instant) {
+		let mut state = self.state.lock().expect("qed");
+		state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+		if state.consecutive_failures >= self.failure_threshold {
+			state.opened_at = Some(now);
+			state.probe_started_at = None;
+		}
+	}
+
+	/// Milliseconds until the cooldown or the pending probe elapses, 0 if not open
+	pub fn retry_after_ms(&self) -> u64 {
+		self.retry_after_ms_at(Instant::now())
+	}
+
+	fn retry_after_ms_at(&self, now: Instant) -> u64 {
+		let state = self.state.lock().expect("qed");
+		match state.probe_started_at.or(state.opened_at) {
+			Some(started_at) => {
+				let remaining = self.cooldown.checked_sub(now.duration_since(started_at));
+				remaining.map(|x| x.as_millis() as u64).unwrap_or(0)
+			}
+			None => 0,
+		}
+	}
+
+	/// `closed`, `open` or `half_open`
+	pub fn state(&self) -> &'static str {
+		self.state_at(Instant::now())
+	}
+
+	fn state_at(&self, now: Instant) -> &'static str {
+		let state = self.state.lock().expect("qed");
+		match state.opened_at {
+			Some(opened_at) if now.duration_since(opened_at) < self.cooldown => "open",
+			Some(_) => "half_open",
+			None => "closed",
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+
+	use crate::config::CircuitBreaker;
+
+	use super::Breaker;
+
+	#[test]
+	fn test_breaker() {
+		let breaker = Breaker::new(&CircuitBreaker {
+			failure_threshold: 3,
+			cooldown_ms: 1000,
+		});
+
+		let now = Instant::now();
+
+		// below the threshold
+		breaker.on_failure_at(now);
+		breaker.on_failure_at(now);
+		assert!(breaker.check_at(now));
+		assert_eq!(breaker.state_at(now), "closed");
+
+		// a success resets the failures
+		breaker.on_success();
+		breaker.on_failure_at(now);
+		breaker.on_failure_at(now);
+		assert!(breaker.check_at(now));
+
+		// trip
+		breaker.on_failure_at(now);
+		assert!(!breaker.check_at(now));
+		assert!(!breaker.check_at(now + Duration::from_millis(999)));
+		assert_eq!(breaker.state_at(now), "open");
+		assert_eq!(breaker.retry_after_ms_at(now), 1000);
+		assert_eq!(
+			breaker.retry_after_ms_at(now + Duration::from_millis(400)),
+			600
+		);
+
+		// a single probe after the cooldown
+		let now = now + Duration::from_millis(1000);
+		assert!(breaker.check_at(now));
+		assert_eq!(breaker.state_at(now), "half_open");
+		assert!(!breaker.check_at(now));
+		assert!(!breaker.check_at(now + Duration::from_millis(500)));
+		assert_eq!(
+			breaker.retry_after_ms_at(now + Duration::from_millis(500)),
+			500
+		);
+
+		// the probe fails
+		breaker.on_failure_at(now);
+		assert!(!breaker.check_at(now));
+		assert_eq!(breaker.state_at(now), "open");
+
+		// the probe is lost, another one after the cooldown
+		let now = now + Duration::from_millis(1000);
+		assert!(breaker.check_at(now));
+		assert!(!breaker.check_at(now));
+		let now = now + Duration::from_millis(1000);
+		assert!(breaker.check_at(now));
+
+		// the probe succeeds
+		breaker.on_success();
+		assert_eq!(breaker.state_at(now), "closed");
+		assert!(breaker.check_at(now));
+		assert!(breaker.check_at(now));
+	}
+}
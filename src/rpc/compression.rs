@@ -0,0 +1,211 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::io::Write;
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use futures::{Future, Stream};
+use http::hyper;
+
+use crate::config::Compression;
+
+pub type ResponseFuture =
+	Box<dyn Future<Item = hyper::Response<hyper::Body>, Error = hyper::Error> + Send>;
+
+/// Compress the http responses by `Accept-Encoding`, on the front server
+/// as the json-rpc http server can not post-process the responses
+pub struct Compressor {
+	min_size: usize,
+}
+
+impl Compressor {
+	pub fn new(config: &Compression) -> Self {
+		Compressor {
+			min_size: config.min_size,
+		}
+	}
+
+	/// The encoding of the response accepted by the request
+	pub fn accepted_encoding(&self, request: &hyper::Request<hyper::Body>) -> Option<&'static str> {
+		request
+			.headers()
+			.get(hyper::header::ACCEPT_ENCODING)
+			.and_then(|x| x.to_str().ok())
+			.and_then(get_encoding)
+	}
+
+	/// Compress the body of the response by the encoding if set,
+	/// a response already encoded is left as is
+	pub fn compress(
+		&self,
+		response: hyper::Response<hyper::Body>,
+		encoding: Option<&'static str>,
+	) -> ResponseFuture {
+		if encoding.is_none()
+			|| response
+				.headers()
+				.contains_key(hyper::header::CONTENT_ENCODING)
+		{
+			return Box::new(futures::future::ok(response));
+		}
+
+		let min_size = self.min_size;
+		let (mut parts, body) = response.into_parts();
+
+		let response = body.concat2().map(move |body| {
+			let (encoding, body) = encode(body.to_vec(), encoding, min_size);
+
+			parts.headers.insert(
+				hyper::header::VARY,
+				hyper::header::HeaderValue::from_static("Accept-Encoding"),
+			);
+			if let Some(encoding) = encoding {
+				parts.headers.insert(
+					hyper::header::CONTENT_ENCODING,
+					hyper::header::HeaderValue::from_static(encoding),
+				);
+			}
+			parts
+				.headers
+				.insert(hyper::header::CONTENT_LENGTH, body.len().into());
+
+			hyper::Response::from_parts(parts, hyper::Body::from(body))
+		});
+
+		Box::new(response)
+	}
+}
+
+/// The supported encoding of the highest quality in `Accept-Encoding`, the first one if equal,
+/// none of `q=0`
+fn get_encoding(accept_encoding: &str) -> Option<&'static str> {
+	let mut result: Option<(&'static str, f32)> = None;
+
+	for item in accept_encoding.split(',') {
+		let mut params = item.split(';');
+		let encoding = match params.next().unwrap_or("").trim() {
+			"gzip" => "gzip",
+			"deflate" => "deflate",
+			_ => continue,
+		};
+
+		// a malformed quality is not acceptable
+		let quality = params
+			.map(|x| x.trim())
+			.find(|x| x.starts_with("q="))
+			.map(|x| x[2..].parse::<f32>().unwrap_or(0.0))
+			.unwrap_or(1.0);
+
+		if quality > 0.0 && result.map_or(true, |(_, best)| quality > best) {
+			result = Some((encoding, quality));
+		}
+	}
+
+	result.map(|(encoding, _)| encoding)
+}
+
+/// Return the content encoding and the body, a body smaller than `min_size` is not compressed
+fn encode(
+	body: Vec<u8>,
+	encoding: Option<&'static str>,
+	min_size: usize,
+) -> (Option<&'static str>, Vec<u8>) {
+	if body.len() < min_size {
+		return (None, body);
+	}
+
+	let encoded = match encoding {
+		Some("gzip") => {
+			let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+			encoder.write_all(&body).and_then(|_| encoder.finish())
+		}
+		// the `deflate` content coding is the zlib format, not the raw deflate
+		Some("deflate") => {
+			let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+			encoder.write_all(&body).and_then(|_| encoder.finish())
+		}
+		_ => return (None, body),
+	};
+
+	match encoded {
+		Ok(encoded) => (encoding, encoded),
+		Err(_) => (None, body),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Read;
+
+	use flate2::read::{GzDecoder, ZlibDecoder};
+
+	use super::{encode, get_encoding};
+
+	#[test]
+	fn test_get_encoding() {
+		// the highest quality
+		assert_eq!(get_encoding("deflate;q=0.5, gzip"), Some("gzip"));
+		assert_eq!(get_encoding("gzip;q=0.5, deflate;q=0.8"), Some("deflate"));
+		assert_eq!(get_encoding("br, gzip;q=1.0"), Some("gzip"));
+
+		// the first one of the same quality
+		assert_eq!(get_encoding("deflate, gzip"), Some("deflate"));
+
+		// not acceptable
+		assert_eq!(get_encoding("gzip;q=0"), None);
+		assert_eq!(get_encoding("gzip;q=0, deflate"), Some("deflate"));
+		assert_eq!(get_encoding("gzip;q=high"), None);
+
+		// not advertised
+		assert_eq!(get_encoding("identity"), None);
+	}
+
+	#[test]
+	fn test_encode() {
+		let body = format!(
+			r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#,
+			"0".repeat(4096)
+		);
+		let body = body.into_bytes();
+
+		let (encoding, encoded) = encode(body.clone(), Some("gzip"), 1024);
+		assert_eq!(encoding, Some("gzip"));
+		assert!(encoded.len() < body.len());
+
+		let mut decoded = Vec::new();
+		GzDecoder::new(&encoded[..])
+			.read_to_end(&mut decoded)
+			.unwrap();
+		assert_eq!(decoded, body);
+
+		let (encoding, encoded) = encode(body.clone(), Some("deflate"), 1024);
+		assert_eq!(encoding, Some("deflate"));
+		assert!(encoded.len() < body.len());
+
+		let mut decoded = Vec::new();
+		ZlibDecoder::new(&encoded[..])
+			.read_to_end(&mut decoded)
+			.unwrap();
+		assert_eq!(decoded, body);
+
+		assert_eq!(encode(body.clone(), None, 1024), (None, body.clone()));
+
+		// small
+		let small = b"{}".to_vec();
+		assert_eq!(encode(small.clone(), Some("gzip"), 1024), (None, small));
+	}
+}
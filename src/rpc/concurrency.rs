@@ -0,0 +1,162 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use futures::future;
+use futures::sync::oneshot;
+use futures::Future;
+
+use crate::rpc::errors;
+
+/// Limit of the outstanding requests to a node, the exceeding requests queue in order
+pub struct ConcurrencyLimiter {
+	state: Mutex<State>,
+}
+
+struct State {
+	available: usize,
+	waiters: VecDeque<oneshot::Sender<Permit>>,
+}
+
+/// Released on drop
+pub struct Permit {
+	limiter: Option<Arc<ConcurrencyLimiter>>,
+}
+
+impl ConcurrencyLimiter {
+	pub fn new(max: usize) -> Self {
+		ConcurrencyLimiter {
+			state: Mutex::new(State {
+				available: max,
+				waiters: VecDeque::new(),
+			}),
+		}
+	}
+
+	pub fn acquire(
+		limiter: &Arc<Self>,
+	) -> Box<dyn Future<Item = Permit, Error = errors::Error> + Send> {
+		let mut state = limiter.state.lock().expect("qed");
+		if state.available > 0 {
+			state.available -= 1;
+			return Box::new(future::ok(Permit {
+				limiter: Some(limiter.clone()),
+			}));
+		}
+
+		let (sender, receiver) = oneshot::channel();
+		state.waiters.push_back(sender);
+
+		let result = receiver.map_err(|_| {
+			errors::Error::from(errors::ErrorKind::TransportError(
+				"Concurrency limiter closed".to_string(),
+			))
+		});
+
+		Box::new(result)
+	}
+
+	/// Hand the permit over to the first waiter still waiting
+	fn release(limiter: &Arc<Self>) {
+		loop {
+			let waiter = {
+				let mut state = limiter.state.lock().expect("qed");
+				match state.waiters.pop_front() {
+					Some(waiter) => waiter,
+					None => {
+						state.available += 1;
+						return;
+					}
+				}
+			};
+
+			// send out of the lock, a returned permit must not be released again
+			let permit = Permit {
+				limiter: Some(limiter.clone()),
+			};
+			match waiter.send(permit) {
+				Ok(()) => return,
+				Err(mut permit) => {
+					permit.limiter.take();
+				}
+			}
+		}
+	}
+}
+
+impl Drop for Permit {
+	fn drop(&mut self) {
+		if let Some(limiter) = self.limiter.take() {
+			ConcurrencyLimiter::release(&limiter);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, Mutex};
+	use std::time::{Duration, Instant};
+
+	use futures::future;
+	use futures::Future;
+	use tokio::runtime::Runtime;
+	use tokio::timer::Delay;
+
+	use super::ConcurrencyLimiter;
+
+	#[test]
+	fn test_concurrency_limiter() {
+		const MAX: usize = 3;
+
+		let limiter = Arc::new(ConcurrencyLimiter::new(MAX));
+		// (current, peak) of the concurrent requests
+		let counter = Arc::new(Mutex::new((0, 0)));
+
+		// each request holds the permit for a while
+		let requests = (0..20)
+			.map(|_| {
+				let counter = counter.clone();
+				ConcurrencyLimiter::acquire(&limiter).and_then(move |permit| {
+					{
+						let mut counter = counter.lock().unwrap();
+						counter.0 += 1;
+						counter.1 = std::cmp::max(counter.0, counter.1);
+					}
+					Delay::new(Instant::now() + Duration::from_millis(10))
+						.map_err(|_| "timer error".into())
+						.map(move |_| {
+							counter.lock().unwrap().0 -= 1;
+							drop(permit);
+						})
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let mut runtime = Runtime::new().unwrap();
+		runtime.block_on(future::join_all(requests)).unwrap();
+
+		assert_eq!(*counter.lock().unwrap(), (0, MAX));
+
+		// all the permits are released
+		let permits = (0..MAX)
+			.map(|_| ConcurrencyLimiter::acquire(&limiter).wait().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(permits.len(), MAX);
+	}
+}
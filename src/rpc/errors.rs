@@ -16,8 +16,13 @@
 // along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
 
 use error_chain::*;
-use jsonrpc_client_transports::RpcError;
 use log::warn;
+use serde_json::json;
+
+use crate::config::NODE_ERRORS;
+
+/// The code of the node for the extrinsics of an invalid format
+const AUTHOR_BAD_FORMAT: i64 = 1001;
 
 error_chain! {
 
@@ -38,6 +43,10 @@ error_chain! {
 			description("config error"),
 			display("Config error"),
 		}
+		InvalidConfig(reason: String) {
+			description("invalid config"),
+			display("Invalid config: {}", reason),
+		}
 		InvalidShard {
 			description("invalid shard"),
 			display("Invalid shard"),
@@ -46,6 +55,10 @@ error_chain! {
 			description("parse error"),
 			display("Parse error"),
 		}
+		DefaultShardUnset {
+			description("default shard unset"),
+			display("Default shard unset"),
+		}
 		InvalidAddress {
 			description("invalid address"),
 			display("Invalid address"),
@@ -54,13 +67,35 @@ error_chain! {
 			description("invalid shard code"),
 			display("Invalid shard code"),
 		}
-		InvalidExtrinsic {
-			description("invalid extrinsic"),
-			display("Invalid extrinsic"),
+		/// `structure` is one of `transaction`, `events`, `header`, `locks`
+		DecodeFailed(structure: &'static str) {
+			description("decode failed"),
+			display("Failed to decode {}", structure),
+		}
+		UnsignedExtrinsic {
+			description("unsigned extrinsic"),
+			display("Unsigned extrinsic"),
+		}
+		NotRelayTransfer {
+			description("not relay transfer"),
+			display("Not relay transfer"),
+		}
+		NodeError(e: jsonrpc_core::Error) {
+			description("node error"),
+			display("Node error: {}", e.message),
 		}
-		RpcError(e: jsonrpc_client_transports::RpcError) {
-			description("rpc error"),
-			display("Rpc error"),
+		TransportError(reason: String) {
+			description("transport error"),
+			display("Transport error: {}", reason),
+		}
+		/// The circuit breaker of the shard is open, until the cooldown elapses
+		NodeUnreachable(shard_num: u16, retry_after_ms: u64) {
+			description("node unreachable"),
+			display("Node of shard {} unreachable", shard_num),
+		}
+		UnexpectedNodeResponse(method: String, snippet: String) {
+			description("unexpected node response"),
+			display("Unexpected node response of {}: {}", method, snippet),
 		}
 		GetWorkError {
 			description("get work failed"),
@@ -70,49 +105,282 @@ error_chain! {
 			description("submit work failed"),
 			display("Submit work failed: {}", reason),
 		}
+		RangeTooLarge(span: u64, max: u64) {
+			description("range too large"),
+			display("Range too large: {} > {}", span, max),
+		}
+		TooManySamples(count: u64, max: u64) {
+			description("too many samples"),
+			display("Too many samples: {} > {}", count, max),
+		}
+		HashListTooLong(count: usize, max: usize) {
+			description("hash list too long"),
+			display("Hash list too long: {} > {}", count, max),
+		}
+		TooManyStorageKeys(count: usize, max: usize) {
+			description("too many storage keys"),
+			display("Too many storage keys: {} > {}", count, max),
+		}
+		TooManyBlockNumbers(count: usize, max: usize) {
+			description("too many block numbers"),
+			display("Too many block numbers: {} > {}", count, max),
+		}
+		BlockNotYetProduced(number: u64, best: u64) {
+			description("block not yet produced"),
+			display("Block {} not yet produced: {}", number, best),
+		}
+		NotFinalized(number: u64, finalized: Option<u64>) {
+			description("not finalized"),
+			display("Block {} not finalized: {:?}", number, finalized),
+		}
+		/// The node replied `Method not found`, like an older node without `system_dryRun`
+		MethodNotSupportedByNode(method: String) {
+			description("method not supported by node"),
+			display("Method {} not supported by the node", method),
+		}
+		RateLimited(retry_after_ms: u64) {
+			description("rate limited"),
+			display("Too many requests"),
+		}
+		TooManyConnections(max: usize) {
+			description("too many connections"),
+			display("Too many connections: {}", max),
+		}
+		BatchTooLarge(size: usize, max: usize) {
+			description("batch too large"),
+			display("Batch too large: {} > {}", size, max),
+		}
+		TooManySubscriptions(max: usize) {
+			description("too many subscriptions"),
+			display("Too many subscriptions: {}", max),
+		}
+		ResponseTooLarge(size: usize, max: usize) {
+			description("response too large"),
+			display("Response too large: {} > {}", size, max),
+		}
+		Timeout(method: String, timeout_ms: u64) {
+			description("timeout"),
+			display("Timeout of {} after {} ms", method, timeout_ms),
+		}
 	}
 }
 
 impl From<Error> for jsonrpc_core::Error {
 	fn from(e: Error) -> Self {
-		match e {
-			Error(ErrorKind::Unimplemented, _) => jsonrpc_core::Error {
-				code: jsonrpc_core::ErrorCode::ServerError(1),
-				message: "Not implemented yet".into(),
-				data: None,
-			},
-			Error(ErrorKind::ConfigError, _) => jsonrpc_core::Error {
-				code: jsonrpc_core::ErrorCode::ServerError(1),
-				message: "Internal error".into(),
-				data: None,
-			},
-			Error(ErrorKind::InvalidShard, _) => jsonrpc_core::Error {
-				code: jsonrpc_core::ErrorCode::ServerError(1),
-				message: "Invalid shard".into(),
-				data: None,
-			},
-			Error(ErrorKind::ParseError, _) => jsonrpc_core::Error {
-				code: jsonrpc_core::ErrorCode::ServerError(1),
-				message: "Parse error".into(),
-				data: None,
-			},
-			Error(ErrorKind::InvalidAddress, _) => jsonrpc_core::Error {
-				code: jsonrpc_core::ErrorCode::ServerError(1),
-				message: "Invalid address".into(),
-				data: None,
-			},
-			Error(ErrorKind::RpcError(e), _) => match e {
-				RpcError::JsonRpcError(e) => {
-					serde_json::from_str(&serde_json::to_string(&e).unwrap()).unwrap()
-				}
-				other => jsonrpc_core::Error {
-					code: jsonrpc_core::ErrorCode::ServerError(1),
-					message: jsonrpc_core::ErrorCode::ServerError(1).description(),
-					data: Some(format!("{:?}", other).into()),
-				},
-			},
-			e => internal(e),
+		if node_errors() {
+			return node_error(e);
 		}
+		panel_error(e)
+	}
+}
+
+/// The panel format, the message of the error kind with the details as the data
+fn panel_error(e: Error) -> jsonrpc_core::Error {
+	match e {
+		Error(ErrorKind::Unimplemented, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Not implemented yet".into(),
+			data: None,
+		},
+		Error(ErrorKind::ConfigError, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Internal error".into(),
+			data: None,
+		},
+		Error(ErrorKind::InvalidConfig(reason), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Invalid config".into(),
+			data: Some(json!({ "reason": reason })),
+		},
+		Error(ErrorKind::InvalidShard, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Invalid shard".into(),
+			data: None,
+		},
+		Error(ErrorKind::ParseError, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Parse error".into(),
+			data: None,
+		},
+		Error(ErrorKind::DefaultShardUnset, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Default shard not configured".into(),
+			data: None,
+		},
+		Error(ErrorKind::InvalidAddress, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Invalid address".into(),
+			data: None,
+		},
+		Error(ErrorKind::DecodeFailed(structure), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Decode failed".into(),
+			data: Some(json!({ "structure": structure })),
+		},
+		Error(ErrorKind::UnsignedExtrinsic, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Unsigned extrinsic has no signer".into(),
+			data: None,
+		},
+		Error(ErrorKind::NotRelayTransfer, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Not a relay transfer".into(),
+			data: None,
+		},
+		Error(ErrorKind::RangeTooLarge(span, max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Range too large".into(),
+			data: Some(json!({ "span": span, "max": max })),
+		},
+		Error(ErrorKind::TooManySamples(count, max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Too many samples".into(),
+			data: Some(json!({ "count": count, "max": max })),
+		},
+		Error(ErrorKind::HashListTooLong(count, max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Hash list too long".into(),
+			data: Some(json!({ "count": count, "max": max })),
+		},
+		Error(ErrorKind::TooManyStorageKeys(count, max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Too many storage keys".into(),
+			data: Some(json!({ "count": count, "max": max })),
+		},
+		Error(ErrorKind::TooManyBlockNumbers(count, max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Too many block numbers".into(),
+			data: Some(json!({ "count": count, "max": max })),
+		},
+		Error(ErrorKind::BlockNotYetProduced(number, best), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Block not yet produced".into(),
+			data: Some(json!({ "number": number, "best": best })),
+		},
+		Error(ErrorKind::NotFinalized(number, finalized), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Block not finalized".into(),
+			data: Some(json!({ "number": number, "finalized": finalized })),
+		},
+		Error(ErrorKind::MethodNotSupportedByNode(method), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Method not supported by the node".into(),
+			data: Some(json!({ "method": method })),
+		},
+		Error(ErrorKind::RateLimited(retry_after_ms), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Too many requests".into(),
+			data: Some(json!({ "retry_after_ms": retry_after_ms })),
+		},
+		Error(ErrorKind::TooManyConnections(max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Too many connections".into(),
+			data: Some(json!({ "max": max })),
+		},
+		Error(ErrorKind::BatchTooLarge(size, max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Batch too large".into(),
+			data: Some(json!({ "size": size, "max": max })),
+		},
+		Error(ErrorKind::TooManySubscriptions(max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Too many subscriptions".into(),
+			data: Some(json!({ "max": max })),
+		},
+		Error(ErrorKind::ResponseTooLarge(size, max), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Response too large".into(),
+			data: Some(json!({ "size": size, "max": max })),
+		},
+		Error(ErrorKind::Timeout(method, timeout_ms), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Request timeout".into(),
+			data: Some(json!({ "method": method, "timeout_ms": timeout_ms })),
+		},
+		Error(ErrorKind::NodeError(e), _) => e,
+		Error(ErrorKind::NodeUnreachable(shard_num, retry_after_ms), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Node unreachable".into(),
+			data: Some(json!({ "shard_num": shard_num, "retry_after_ms": retry_after_ms })),
+		},
+		Error(ErrorKind::UnexpectedNodeResponse(method, snippet), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Unexpected node response".into(),
+			data: Some(json!({ "method": method, "response": snippet })),
+		},
+		Error(ErrorKind::TransportError(reason), _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: jsonrpc_core::ErrorCode::ServerError(1).description(),
+			data: Some(reason.into()),
+		},
+		e => internal(e),
+	}
+}
+
+#[cfg(test)]
+thread_local! {
+	/// `NODE_ERRORS` is set once, overridden by the tests of the thread
+	static NODE_ERRORS_OVERRIDE: std::cell::Cell<Option<bool>> = std::cell::Cell::new(None);
+}
+
+fn node_errors() -> bool {
+	#[cfg(test)]
+	{
+		if let Some(node_errors) = NODE_ERRORS_OVERRIDE.with(|x| x.get()) {
+			return node_errors;
+		}
+	}
+
+	NODE_ERRORS.read().map(|x| *x).unwrap_or(false)
+}
+
+/// As the node formats its errors, the bad params are `Invalid params` with the reason as the
+/// message, the limits and the unavailable nodes keep the panel format with the data like
+/// `retry_after_ms`, the failures of the panel or the node connection are internal errors
+fn node_error(e: Error) -> jsonrpc_core::Error {
+	match e {
+		Error(ErrorKind::Unimplemented, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(1),
+			message: "Not implemented yet".into(),
+			data: None,
+		},
+		Error(ErrorKind::UnsignedExtrinsic, _) => jsonrpc_core::Error {
+			code: jsonrpc_core::ErrorCode::ServerError(AUTHOR_BAD_FORMAT),
+			message: "Extrinsic has invalid format.".into(),
+			data: None,
+		},
+		Error(ErrorKind::MethodNotSupportedByNode(_), _) => jsonrpc_core::Error::method_not_found(),
+		Error(ErrorKind::NodeError(e), _) => e,
+		Error(ErrorKind::InvalidShard, _)
+		| Error(ErrorKind::ParseError, _)
+		| Error(ErrorKind::DefaultShardUnset, _)
+		| Error(ErrorKind::InvalidAddress, _)
+		| Error(ErrorKind::InvalidShardCode, _)
+		| Error(ErrorKind::DecodeFailed(_), _)
+		| Error(ErrorKind::NotRelayTransfer, _)
+		| Error(ErrorKind::RangeTooLarge(..), _)
+		| Error(ErrorKind::TooManySamples(..), _)
+		| Error(ErrorKind::HashListTooLong(..), _)
+		| Error(ErrorKind::TooManyStorageKeys(..), _)
+		| Error(ErrorKind::TooManyBlockNumbers(..), _)
+		| Error(ErrorKind::BlockNotYetProduced(..), _)
+		| Error(ErrorKind::NotFinalized(..), _)
+		| Error(ErrorKind::BatchTooLarge(..), _)
+		| Error(ErrorKind::InvalidConfig(_), _) => jsonrpc_core::Error::invalid_params(e.to_string()),
+		Error(ErrorKind::RateLimited(_), _)
+		| Error(ErrorKind::NodeUnreachable(..), _)
+		| Error(ErrorKind::Timeout(..), _)
+		| Error(ErrorKind::TooManyConnections(_), _)
+		| Error(ErrorKind::TooManySubscriptions(_), _)
+		| Error(ErrorKind::ResponseTooLarge(..), _) => panel_error(e),
+		Error(ErrorKind::ConfigError, _)
+		| Error(ErrorKind::TransportError(_), _)
+		| Error(ErrorKind::UnexpectedNodeResponse(..), _)
+		| Error(ErrorKind::GetWorkError, _)
+		| Error(ErrorKind::SumbitWorkError(_), _)
+		| Error(ErrorKind::Io(_), _)
+		| Error(ErrorKind::Msg(_), _)
+		| Error(ErrorKind::__Nonexhaustive {}, _) => internal(e),
 	}
 }
 
@@ -124,3 +392,84 @@ pub fn internal<E: ::std::fmt::Debug>(e: E) -> jsonrpc_core::Error {
 		data: Some(format!("{:?}", e).into()),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::{node_error, Error, ErrorKind, NODE_ERRORS_OVERRIDE};
+
+	#[test]
+	fn test_node_error() {
+		let e = node_error(Error::from(ErrorKind::RangeTooLarge(200, 100)));
+		assert_eq!(e.code, jsonrpc_core::ErrorCode::InvalidParams);
+		assert_eq!(e.message, "Range too large: 200 > 100");
+		assert_eq!(e.data, None);
+
+		let e = node_error(Error::from(ErrorKind::UnsignedExtrinsic));
+		assert_eq!(e.code, jsonrpc_core::ErrorCode::ServerError(1001));
+		assert_eq!(e.message, "Extrinsic has invalid format.");
+
+		let e = node_error(Error::from(ErrorKind::MethodNotSupportedByNode(
+			"system_dryRun".to_string(),
+		)));
+		assert_eq!(e.code, jsonrpc_core::ErrorCode::MethodNotFound);
+
+		// the node errors are kept as they are
+		let e = node_error(Error::from(ErrorKind::NodeError(
+			jsonrpc_core::Error::invalid_request(),
+		)));
+		assert_eq!(e, jsonrpc_core::Error::invalid_request());
+
+		// the timeout keeps the panel format
+		let e = node_error(Error::from(ErrorKind::Timeout(
+			"chain_getBlock".to_string(),
+			1000,
+		)));
+		assert_eq!(e.code, jsonrpc_core::ErrorCode::ServerError(1));
+		assert_eq!(
+			e.data,
+			Some(json!({ "method": "chain_getBlock", "timeout_ms": 1000 }))
+		);
+
+		let e = node_error(Error::from(ErrorKind::TransportError(
+			"Connection refused".to_string(),
+		)));
+		assert_eq!(e.code, jsonrpc_core::ErrorCode::InternalError);
+	}
+
+	#[test]
+	fn test_from_error() {
+		NODE_ERRORS_OVERRIDE.with(|x| x.set(Some(true)));
+
+		let e = jsonrpc_core::Error::from(Error::from(ErrorKind::RangeTooLarge(200, 100)));
+		assert_eq!(e.code, jsonrpc_core::ErrorCode::InvalidParams);
+		assert_eq!(e.message, "Range too large: 200 > 100");
+
+		let e = jsonrpc_core::Error::from(Error::from(ErrorKind::RateLimited(500)));
+		assert_eq!(e.code, jsonrpc_core::ErrorCode::ServerError(1));
+		assert_eq!(e.message, "Too many requests");
+		assert_eq!(e.data, Some(json!({ "retry_after_ms": 500 })));
+
+		let e = jsonrpc_core::Error::from(Error::from(ErrorKind::NodeUnreachable(1, 3000)));
+		assert_eq!(e.message, "Node unreachable");
+		assert_eq!(
+			e.data,
+			Some(json!({ "shard_num": 1, "retry_after_ms": 3000 }))
+		);
+
+		let e = jsonrpc_core::Error::from(Error::from(ErrorKind::TooManyConnections(64)));
+		assert_eq!(e.message, "Too many connections");
+		assert_eq!(e.data, Some(json!({ "max": 64 })));
+
+		// the panel format
+		NODE_ERRORS_OVERRIDE.with(|x| x.set(Some(false)));
+
+		let e = jsonrpc_core::Error::from(Error::from(ErrorKind::RangeTooLarge(200, 100)));
+		assert_eq!(e.code, jsonrpc_core::ErrorCode::ServerError(1));
+		assert_eq!(e.message, "Range too large");
+		assert_eq!(e.data, Some(json!({ "span": 200, "max": 100 })));
+
+		NODE_ERRORS_OVERRIDE.with(|x| x.set(None));
+	}
+}
@@ -0,0 +1,622 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::{self, Loop};
+use futures::{Future, Stream};
+use http::hyper;
+use hyper::client::HttpConnector;
+use hyper::service::service_fn;
+use log::{debug, warn};
+use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
+use tokio::timer::Delay;
+
+use crate::config::Config;
+use crate::rpc::compression::{Compressor, ResponseFuture};
+use crate::rpc::errors;
+use crate::rpc::rate_limit::RateLimiter;
+
+/// The headers of the connection, not forwarded
+const HOP_HEADERS: [&str; 5] = [
+	"connection",
+	"keep-alive",
+	"proxy-connection",
+	"transfer-encoding",
+	"upgrade",
+];
+
+/// The public http server in front of the json-rpc http server, which exposes neither
+/// the peer address nor the connections to the middlewares, nor lets them post-process
+/// the responses.
+/// The requests are forwarded to the json-rpc http server listening on the loopback.
+pub struct Front {
+	rate_limiter: Option<RateLimiter>,
+	compressor: Option<Arc<Compressor>>,
+	/// Max connections open at once, 0 for no limit
+	max_connections: usize,
+	idle_timeout: Option<Duration>,
+}
+
+/// Served until dropped
+pub struct Server {
+	address: SocketAddr,
+	_runtime: Runtime,
+}
+
+struct Shared {
+	front: Front,
+	client: hyper::Client<HttpConnector>,
+	upstream: SocketAddr,
+	connections: Arc<AtomicUsize>,
+}
+
+/// An open connection, released on drop
+struct ConnectionGuard {
+	connections: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionGuard {
+	fn drop(&mut self) {
+		self.connections.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+/// The requests being served on a connection and the end of the last one
+struct Activity {
+	in_flight: AtomicUsize,
+	last: Mutex<Instant>,
+}
+
+impl Activity {
+	fn new() -> Self {
+		Activity {
+			in_flight: AtomicUsize::new(0),
+			last: Mutex::new(Instant::now()),
+		}
+	}
+
+	fn start(&self) {
+		self.in_flight.fetch_add(1, Ordering::SeqCst);
+	}
+
+	fn finish(&self) {
+		*self.last.lock().expect("qed") = Instant::now();
+		self.in_flight.fetch_sub(1, Ordering::SeqCst);
+	}
+
+	/// None if idle for the timeout, else when to check again
+	fn idle_deadline(&self, timeout: Duration, now: Instant) -> Option<Instant> {
+		if self.in_flight.load(Ordering::SeqCst) > 0 {
+			return Some(now + timeout);
+		}
+		let deadline = *self.last.lock().expect("qed") + timeout;
+		match deadline > now {
+			true => Some(deadline),
+			false => None,
+		}
+	}
+}
+
+impl Front {
+	/// None if the json-rpc http server can listen directly
+	pub fn new(config: &Config) -> errors::Result<Option<Self>> {
+		let rate_limiter = match &config.rate_limit {
+			Some(rate_limit) => Some(RateLimiter::new(rate_limit)?),
+			None => None,
+		};
+
+		let compressor = config
+			.compression
+			.as_ref()
+			.map(|x| Arc::new(Compressor::new(x)));
+
+		let max_connections = config.http.as_ref().map(|x| x.max_connections).unwrap_or(0);
+
+		let idle_timeout = config
+			.http
+			.as_ref()
+			.filter(|x| x.idle_timeout_ms > 0)
+			.map(|x| Duration::from_millis(x.idle_timeout_ms));
+
+		if rate_limiter.is_none()
+			&& compressor.is_none()
+			&& max_connections == 0
+			&& idle_timeout.is_none()
+		{
+			return Ok(None);
+		}
+
+		Ok(Some(Front {
+			rate_limiter,
+			compressor,
+			max_connections,
+			idle_timeout,
+		}))
+	}
+}
+
+impl Server {
+	pub fn address(&self) -> &SocketAddr {
+		&self.address
+	}
+}
+
+/// Listen on `addr` and forward to `upstream`
+pub fn start(
+	addr: &SocketAddr,
+	upstream: SocketAddr,
+	front: Front,
+	keep_alive: bool,
+) -> io::Result<Server> {
+	let listener = TcpListener::bind(addr)?;
+	let address = listener.local_addr()?;
+
+	let shared = Arc::new(Shared {
+		front,
+		client: hyper::Client::new(),
+		upstream,
+		connections: Arc::new(AtomicUsize::new(0)),
+	});
+
+	let mut http = hyper::server::conn::Http::new();
+	http.keep_alive(keep_alive);
+
+	// the connections beyond the limit are answered once and closed
+	let mut refusing = hyper::server::conn::Http::new();
+	refusing.keep_alive(false);
+
+	let server = listener
+		.incoming()
+		.map_err(|e| warn!("Failed to accept: {}", e))
+		.for_each(move |socket| {
+			let peer = match socket.peer_addr() {
+				Ok(peer) => peer.ip(),
+				Err(_) => return Ok(()),
+			};
+
+			let guard = match acquire(&shared.connections, shared.front.max_connections) {
+				Some(guard) => guard,
+				None => {
+					let max = shared.front.max_connections;
+					debug!("Too many connections: {}", max);
+					let service = service_fn(move |_request: hyper::Request<hyper::Body>| {
+						future::ok::<_, hyper::Error>(refuse_connection(max))
+					});
+					let connection = refusing
+						.serve_connection(socket, service)
+						.map_err(|e| debug!("Connection error: {}", e));
+					tokio::spawn(connection);
+					return Ok(());
+				}
+			};
+
+			let activity = Arc::new(Activity::new());
+
+			let service = {
+				let shared = shared.clone();
+				let activity = activity.clone();
+				service_fn(move |request| {
+					activity.start();
+					let activity = activity.clone();
+					handle(&shared, request, peer).then(move |x| {
+						activity.finish();
+						x
+					})
+				})
+			};
+			let connection = http
+				.serve_connection(socket, service)
+				.map_err(|e| debug!("Connection error: {}", e));
+
+			// dropping the connection closes it
+			let connection: Box<dyn Future<Item = (), Error = ()> + Send> =
+				match shared.front.idle_timeout {
+					Some(timeout) => Box::new(
+						connection
+							.select(watch_idle(activity, timeout))
+							.map(|_| ())
+							.map_err(|_| ()),
+					),
+					None => Box::new(connection),
+				};
+
+			tokio::spawn(connection.then(move |x| {
+				drop(guard);
+				x
+			}));
+			Ok(())
+		});
+
+	let mut runtime = Runtime::new()?;
+	runtime.spawn(server);
+
+	Ok(Server {
+		address,
+		_runtime: runtime,
+	})
+}
+
+/// None if `max` connections are open, 0 for no limit
+fn acquire(connections: &Arc<AtomicUsize>, max: usize) -> Option<ConnectionGuard> {
+	if connections.fetch_add(1, Ordering::SeqCst) >= max && max > 0 {
+		connections.fetch_sub(1, Ordering::SeqCst);
+		return None;
+	}
+	Some(ConnectionGuard {
+		connections: connections.clone(),
+	})
+}
+
+/// Resolved once the connection is idle for the timeout
+fn watch_idle(activity: Arc<Activity>, timeout: Duration) -> impl Future<Item = (), Error = ()> {
+	future::loop_fn(Instant::now() + timeout, move |deadline| {
+		let activity = activity.clone();
+		Delay::new(deadline)
+			.map_err(|e| warn!("Timer error: {}", e))
+			.map(
+				move |_| match activity.idle_deadline(timeout, Instant::now()) {
+					Some(deadline) => Loop::Continue(deadline),
+					None => Loop::Break(()),
+				},
+			)
+	})
+}
+
+/// 503 with the json-rpc error
+fn refuse_connection(max: usize) -> hyper::Response<hyper::Body> {
+	let error: jsonrpc_core::Error =
+		errors::Error::from(errors::ErrorKind::TooManyConnections(max)).into();
+	let output = jsonrpc_core::Output::Failure(jsonrpc_core::Failure {
+		jsonrpc: Some(jsonrpc_core::Version::V2),
+		error,
+		id: jsonrpc_core::Id::Null,
+	});
+	let body = serde_json::to_string(&output).expect("qed");
+
+	hyper::Response::builder()
+		.status(hyper::StatusCode::SERVICE_UNAVAILABLE)
+		.header(hyper::header::CONTENT_TYPE, "application/json")
+		.body(hyper::Body::from(body))
+		.expect("qed")
+}
+
+fn handle(shared: &Shared, request: hyper::Request<hyper::Body>, peer: IpAddr) -> ResponseFuture {
+	if let Some(rate_limiter) = &shared.front.rate_limiter {
+		let client = rate_limiter.client_ip(&request, peer);
+		if !rate_limiter.check(&client) {
+			return Box::new(future::ok(rate_limiter.refuse(&client)));
+		}
+	}
+
+	let compressor = match &shared.front.compressor {
+		Some(compressor) => compressor.clone(),
+		None => return forward(&shared.client, shared.upstream, request),
+	};
+
+	let encoding = compressor.accepted_encoding(&request);
+	let response = forward(&shared.client, shared.upstream, request)
+		.and_then(move |response| compressor.compress(response, encoding));
+
+	Box::new(response)
+}
+
+/// Send the request to the json-rpc http server, 502 if not reachable
+fn forward(
+	client: &hyper::Client<HttpConnector>,
+	upstream: SocketAddr,
+	request: hyper::Request<hyper::Body>,
+) -> ResponseFuture {
+	let (mut parts, body) = request.into_parts();
+
+	let path = parts
+		.uri
+		.path_and_query()
+		.map(|x| x.as_str())
+		.unwrap_or("/");
+	parts.uri = match format!("http://{}{}", upstream, path).parse() {
+		Ok(uri) => uri,
+		Err(_) => return Box::new(future::ok(status(hyper::StatusCode::BAD_REQUEST))),
+	};
+	for header in HOP_HEADERS.iter() {
+		parts.headers.remove(*header);
+	}
+
+	let response = client
+		.request(hyper::Request::from_parts(parts, body))
+		.map(|mut response| {
+			for header in HOP_HEADERS.iter() {
+				response.headers_mut().remove(*header);
+			}
+			response
+		})
+		.or_else(|e| {
+			warn!("Failed to forward the request: {}", e);
+			Ok(status(hyper::StatusCode::BAD_GATEWAY))
+		});
+
+	Box::new(response)
+}
+
+fn status(status: hyper::StatusCode) -> hyper::Response<hyper::Body> {
+	hyper::Response::builder()
+		.status(status)
+		.body(hyper::Body::empty())
+		.expect("qed")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{Read, Write};
+	use std::net::{SocketAddr, TcpStream};
+	use std::thread;
+	use std::time::{Duration, Instant};
+
+	use flate2::read::GzDecoder;
+	use jsonrpc_core::IoHandler;
+	use serde_json::json;
+
+	use crate::config::Config;
+
+	use super::{start, Front};
+
+	/// A json-rpc http server of `panel_version` and a large `panel_metrics`,
+	/// the health api of `system_health`
+	fn start_upstream() -> http::Server {
+		let mut io = IoHandler::new();
+		io.add_method("panel_version", |_| Ok(json!("0.1.0")));
+		io.add_method("panel_metrics", |_| Ok(json!("0".repeat(4096))));
+		io.add_method("system_health", |_| Ok(json!({ "peers": 2 })));
+		http::ServerBuilder::new(io)
+			.health_api(("/health", "system_health"))
+			.start_http(&"127.0.0.1:0".parse().unwrap())
+			.unwrap()
+	}
+
+	/// (status, body) of the raw http request
+	fn send(addr: &SocketAddr, request: &str) -> (u16, Vec<u8>) {
+		let (status, _head, body) = send_with_head(addr, request);
+		(status, body)
+	}
+
+	/// (status, lowercase head, body) of the raw http request
+	fn send_with_head(addr: &SocketAddr, request: &str) -> (u16, String, Vec<u8>) {
+		let mut stream = TcpStream::connect(addr).unwrap();
+		stream.write_all(request.as_bytes()).unwrap();
+		let mut response = Vec::new();
+		stream.read_to_end(&mut response).unwrap();
+
+		let head_end = response.windows(4).position(|x| x == b"\r\n\r\n").unwrap();
+		let head = String::from_utf8_lossy(&response[..head_end]).to_lowercase();
+		let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+		(status, head, response[head_end + 4..].to_vec())
+	}
+
+	fn post(body: &str) -> String {
+		format!(
+			"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+			body.len(),
+			body
+		)
+	}
+
+	#[test]
+	fn test_rate_limit() {
+		let upstream = start_upstream();
+
+		let config: Config = toml::from_str(
+			r#"
+			[rate_limit]
+			requests_per_second = 1
+			burst = 1
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let front = Front::new(&config).unwrap().unwrap();
+		let server = start(
+			&"127.0.0.1:0".parse().unwrap(),
+			*upstream.address(),
+			front,
+			true,
+		)
+		.unwrap();
+		let addr = *server.address();
+
+		let request = post(r#"{"jsonrpc":"2.0","method":"panel_version","params":[],"id":1}"#);
+
+		let (status, body) = send(&addr, &request);
+		assert_eq!(status, 200);
+		let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+		assert_eq!(body["result"], "0.1.0");
+
+		// the headers of an untrusted peer are ignored
+		let spoofed = request.replace("Host:", "X-Forwarded-For: 127.0.0.9\r\nHost:");
+		let (status, body) = send(&addr, &spoofed);
+		assert_eq!(status, 429);
+		let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+		assert_eq!(body["error"]["code"], 1);
+		assert_eq!(body["error"]["message"], "Too many requests");
+		assert!(body["error"]["data"]["retry_after_ms"].as_u64().unwrap() > 0);
+		assert_eq!(body["id"], serde_json::Value::Null);
+	}
+
+	#[test]
+	fn test_compression() {
+		let upstream = start_upstream();
+
+		let config: Config = toml::from_str(
+			r#"
+			[compression]
+			min_size = 1024
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let front = Front::new(&config).unwrap().unwrap();
+		let server = start(
+			&"127.0.0.1:0".parse().unwrap(),
+			*upstream.address(),
+			front,
+			true,
+		)
+		.unwrap();
+		let addr = *server.address();
+
+		let request = post(r#"{"jsonrpc":"2.0","method":"panel_metrics","params":[],"id":1}"#);
+		let accepting = |encoding: &str| {
+			request.replace("Host:", &format!("Accept-Encoding: {}\r\nHost:", encoding))
+		};
+
+		// compressed, the headers of the json-rpc http server kept
+		let (status, head, body) = send_with_head(&addr, &accepting("gzip"));
+		assert_eq!(status, 200);
+		assert!(head.contains("content-encoding: gzip\r\n"));
+		assert!(head.contains("content-type: application/json"));
+		assert!(head.contains(&format!("content-length: {}\r\n", body.len())));
+		let mut decoded = Vec::new();
+		GzDecoder::new(&body[..]).read_to_end(&mut decoded).unwrap();
+		let decoded: serde_json::Value = serde_json::from_slice(&decoded).unwrap();
+		assert_eq!(decoded["result"], json!("0".repeat(4096)));
+
+		// refused by the quality
+		let (status, head, body) = send_with_head(&addr, &accepting("gzip;q=0"));
+		assert_eq!(status, 200);
+		assert!(!head.contains("content-encoding"));
+		let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+		assert_eq!(body["result"], json!("0".repeat(4096)));
+
+		// small
+		let small = post(r#"{"jsonrpc":"2.0","method":"panel_version","params":[],"id":1}"#);
+		let small = small.replace("Host:", "Accept-Encoding: gzip\r\nHost:");
+		let (_status, head, _body) = send_with_head(&addr, &small);
+		assert!(!head.contains("content-encoding"));
+
+		// the health api served by the json-rpc http server
+		let health = "GET /health HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n";
+		let (status, _head, body) = send_with_head(&addr, health);
+		assert_eq!(status, 200);
+		let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+		assert_eq!(body["peers"], json!(2));
+	}
+
+	#[test]
+	fn test_max_connections() {
+		let upstream = start_upstream();
+
+		let config: Config = toml::from_str(
+			r#"
+			[http]
+			max_connections = 1
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let front = Front::new(&config).unwrap().unwrap();
+		let server = start(
+			&"127.0.0.1:0".parse().unwrap(),
+			*upstream.address(),
+			front,
+			true,
+		)
+		.unwrap();
+		let addr = *server.address();
+
+		let request = post(r#"{"jsonrpc":"2.0","method":"panel_version","params":[],"id":1}"#);
+
+		// an idle connection holds the only slot
+		let idle = TcpStream::connect(&addr).unwrap();
+		thread::sleep(Duration::from_millis(100));
+
+		let (status, body) = send(&addr, &request);
+		assert_eq!(status, 503);
+		let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+		assert_eq!(body["error"]["message"], "Too many connections");
+		assert_eq!(body["error"]["data"], json!({ "max": 1 }));
+		assert_eq!(body["id"], serde_json::Value::Null);
+
+		// released once closed
+		drop(idle);
+		let started = Instant::now();
+		loop {
+			let (status, body) = send(&addr, &request);
+			if status == 200 {
+				let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+				assert_eq!(body["result"], "0.1.0");
+				break;
+			}
+			assert!(started.elapsed() < Duration::from_secs(2));
+			thread::sleep(Duration::from_millis(50));
+		}
+	}
+
+	#[test]
+	fn test_idle_timeout() {
+		let upstream = start_upstream();
+
+		let config: Config = toml::from_str(
+			r#"
+			[http]
+			idle_timeout_ms = 200
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let front = Front::new(&config).unwrap().unwrap();
+		let server = start(
+			&"127.0.0.1:0".parse().unwrap(),
+			*upstream.address(),
+			front,
+			true,
+		)
+		.unwrap();
+		let addr = *server.address();
+
+		// a kept alive connection is closed after the response once idle
+		let request = post(r#"{"jsonrpc":"2.0","method":"panel_version","params":[],"id":1}"#);
+		let request = request.replace("Connection: close", "Connection: keep-alive");
+		let mut stream = TcpStream::connect(&addr).unwrap();
+		stream
+			.set_read_timeout(Some(Duration::from_secs(5)))
+			.unwrap();
+		stream.write_all(request.as_bytes()).unwrap();
+
+		let started = Instant::now();
+		let mut response = Vec::new();
+		stream.read_to_end(&mut response).unwrap();
+		assert!(started.elapsed() < Duration::from_secs(2));
+		let response = String::from_utf8_lossy(&response);
+		assert!(response.starts_with("HTTP/1.1 200"));
+		assert!(response.contains("0.1.0"));
+
+		// a connection never sending a request is closed too
+		let mut stream = TcpStream::connect(&addr).unwrap();
+		stream
+			.set_read_timeout(Some(Duration::from_secs(5)))
+			.unwrap();
+		let started = Instant::now();
+		let mut response = Vec::new();
+		stream.read_to_end(&mut response).unwrap();
+		assert!(started.elapsed() < Duration::from_secs(2));
+		assert!(response.is_empty());
+	}
+}
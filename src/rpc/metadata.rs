@@ -15,6 +15,7 @@
 // You should have received a copy of the GNU General Public License
 // along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 
 use futures::sync::mpsc;
@@ -28,6 +29,10 @@ use pubsub::{PubSubMetadata, Session};
 #[derive(Default, Clone)]
 pub struct Metadata {
 	session: Option<Arc<Session>>,
+	/// Count of the active subscriptions of the connection
+	subscriptions: Arc<AtomicUsize>,
+	/// Request id of the incoming http request
+	request_id: Option<String>,
 }
 
 impl jsonrpc_core::Metadata for Metadata {}
@@ -43,9 +48,27 @@ impl Metadata {
 	pub fn new(transport: mpsc::Sender<String>) -> Self {
 		Metadata {
 			session: Some(Arc::new(Session::new(transport))),
+			subscriptions: Arc::new(AtomicUsize::new(0)),
+			request_id: None,
 		}
 	}
 
+	/// Create new `Metadata` of a http request.
+	pub fn with_request_id(request_id: Option<String>) -> Self {
+		Metadata {
+			request_id,
+			..Default::default()
+		}
+	}
+
+	pub fn subscriptions(&self) -> Arc<AtomicUsize> {
+		self.subscriptions.clone()
+	}
+
+	pub fn request_id(&self) -> Option<String> {
+		self.request_id.clone()
+	}
+
 	/// Create new `Metadata` for tests.
 	#[cfg(test)]
 	pub fn new_test() -> (mpsc::Receiver<String>, Self) {
@@ -0,0 +1,128 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use crate::config::Config;
+use crate::rpc::errors;
+
+/// The exposed rpc methods, the others are not registered and so not found
+pub struct MethodFilter {
+	/// All the methods if not set
+	allow: Option<HashSet<String>>,
+	deny: HashSet<String>,
+	/// The methods seen on registration
+	known: Mutex<HashSet<String>>,
+}
+
+impl MethodFilter {
+	pub fn new(config: &Config) -> Self {
+		let (allow, deny) = match &config.methods {
+			Some(methods) => (
+				methods.allow.as_ref().map(|x| x.iter().cloned().collect()),
+				methods.deny.iter().cloned().collect(),
+			),
+			None => (None, HashSet::new()),
+		};
+		MethodFilter {
+			allow,
+			deny,
+			known: Mutex::new(HashSet::new()),
+		}
+	}
+
+	pub fn is_allowed(&self, name: &str) -> bool {
+		self.known.lock().expect("qed").insert(name.to_string());
+
+		let allowed = match &self.allow {
+			Some(allow) => allow.contains(name),
+			None => true,
+		};
+		allowed && !self.deny.contains(name)
+	}
+
+	/// The configured names must be of the methods seen on registration
+	pub fn check(&self) -> errors::Result<()> {
+		let known = self.known.lock().expect("qed");
+
+		let mut unknown = self
+			.allow
+			.iter()
+			.flatten()
+			.chain(self.deny.iter())
+			.filter(|x| !known.contains(*x))
+			.cloned()
+			.collect::<Vec<_>>();
+		unknown.sort();
+		unknown.dedup();
+
+		if !unknown.is_empty() {
+			return Err(format!("Unknown methods: {}", unknown.join(", ")).into());
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::config::Config;
+
+	use super::MethodFilter;
+
+	#[test]
+	fn test_method_filter() {
+		let config: Config = toml::from_str(
+			r#"
+			[methods]
+			allow = ["chain_getBestNumber", "author_submitExtrinsic"]
+			deny = ["author_submitExtrinsic"]
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let filter = MethodFilter::new(&config);
+
+		assert!(filter.is_allowed("chain_getBestNumber"));
+		assert!(!filter.is_allowed("chain_getFinalizedNumber"));
+		// denied over allowed
+		assert!(!filter.is_allowed("author_submitExtrinsic"));
+		assert!(filter.check().is_ok());
+
+		// unknown names
+		let config: Config = toml::from_str(
+			r#"
+			[methods]
+			deny = ["author_submitExtrinsics"]
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let filter = MethodFilter::new(&config);
+		assert!(filter.is_allowed("author_submitExtrinsic"));
+		assert_eq!(
+			filter.check().unwrap_err().to_string(),
+			"Unknown methods: author_submitExtrinsics"
+		);
+
+		// not configured
+		let config: Config = toml::from_str("[shards]").unwrap();
+		let filter = MethodFilter::new(&config);
+		assert!(filter.is_allowed("author_submitExtrinsic"));
+		assert!(filter.check().is_ok());
+	}
+}
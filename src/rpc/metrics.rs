@@ -0,0 +1,255 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Node request metrics of a shard
+pub struct ShardMetrics {
+	counters: Mutex<Counters>,
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct Counters {
+	pub requests: u64,
+	/// Failed in transport, by the http status or by a json-rpc error of the node
+	pub errors: u64,
+	pub latency_ms_total: u64,
+	pub latency_ms_max: u64,
+}
+
+impl ShardMetrics {
+	pub fn new() -> Self {
+		ShardMetrics {
+			counters: Mutex::new(Counters::default()),
+		}
+	}
+
+	pub fn record(&self, elapsed: Duration, success: bool) {
+		let elapsed_ms = elapsed.as_millis() as u64;
+
+		let mut counters = self.counters.lock().expect("qed");
+		counters.requests += 1;
+		if !success {
+			counters.errors += 1;
+		}
+		counters.latency_ms_total += elapsed_ms;
+		counters.latency_ms_max = std::cmp::max(counters.latency_ms_max, elapsed_ms);
+	}
+
+	pub fn counters(&self) -> Counters {
+		self.counters.lock().expect("qed").clone()
+	}
+}
+
+/// Hits and misses of a cache
+pub struct CacheMetrics {
+	counters: Mutex<CacheCounters>,
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct CacheCounters {
+	pub hits: u64,
+	pub misses: u64,
+}
+
+impl CacheMetrics {
+	pub fn new() -> Self {
+		CacheMetrics {
+			counters: Mutex::new(CacheCounters::default()),
+		}
+	}
+
+	pub fn record(&self, hit: bool) {
+		let mut counters = self.counters.lock().expect("qed");
+		if hit {
+			counters.hits += 1;
+		} else {
+			counters.misses += 1;
+		}
+	}
+
+	pub fn counters(&self) -> CacheCounters {
+		self.counters.lock().expect("qed").clone()
+	}
+}
+
+/// The node request metrics of the shards in the Prometheus text format
+pub fn format_prometheus(shards: &[(u16, Counters)]) -> String {
+	let families: [(&str, &str, &str, fn(&Counters) -> u64); 4] = [
+		(
+			"yee_panel_node_requests_total",
+			"counter",
+			"Count of the node requests",
+			|x| x.requests,
+		),
+		(
+			"yee_panel_node_errors_total",
+			"counter",
+			"Count of the node requests failed in transport, by the http status or by a json-rpc error",
+			|x| x.errors,
+		),
+		(
+			"yee_panel_node_latency_ms_total",
+			"counter",
+			"Total latency of the node requests in milliseconds",
+			|x| x.latency_ms_total,
+		),
+		(
+			"yee_panel_node_latency_ms_max",
+			"gauge",
+			"Max latency of the node requests in milliseconds",
+			|x| x.latency_ms_max,
+		),
+	];
+
+	let mut text = String::new();
+	for (name, kind, help, value) in families.iter() {
+		writeln!(text, "# HELP {} {}", name, help).expect("qed");
+		writeln!(text, "# TYPE {} {}", name, kind).expect("qed");
+		for (shard_num, counters) in shards {
+			writeln!(
+				text,
+				"{}{{shard=\"{}\"}} {}",
+				name,
+				shard_num,
+				value(counters)
+			)
+			.expect("qed");
+		}
+	}
+	text
+}
+
+/// The hits and misses of the caches by name in the Prometheus text format
+pub fn format_prometheus_caches(caches: &[(&str, CacheCounters)]) -> String {
+	let families: [(&str, &str, fn(&CacheCounters) -> u64); 2] = [
+		(
+			"yee_panel_cache_hits_total",
+			"Count of the cache hits",
+			|x| x.hits,
+		),
+		(
+			"yee_panel_cache_misses_total",
+			"Count of the cache misses",
+			|x| x.misses,
+		),
+	];
+
+	let mut text = String::new();
+	for (name, help, value) in families.iter() {
+		writeln!(text, "# HELP {} {}", name, help).expect("qed");
+		writeln!(text, "# TYPE {} counter", name).expect("qed");
+		for (cache, counters) in caches {
+			writeln!(text, "{}{{cache=\"{}\"}} {}", name, cache, value(counters)).expect("qed");
+		}
+	}
+	text
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use super::{
+		format_prometheus, format_prometheus_caches, CacheCounters, CacheMetrics, Counters,
+		ShardMetrics,
+	};
+
+	#[test]
+	fn test_shard_metrics() {
+		let metrics = ShardMetrics::new();
+		assert_eq!(metrics.counters(), Counters::default());
+
+		metrics.record(Duration::from_millis(30), true);
+		metrics.record(Duration::from_millis(10), false);
+
+		assert_eq!(
+			metrics.counters(),
+			Counters {
+				requests: 2,
+				errors: 1,
+				latency_ms_total: 40,
+				latency_ms_max: 30,
+			}
+		);
+	}
+
+	#[test]
+	fn test_cache_metrics() {
+		let metrics = CacheMetrics::new();
+		assert_eq!(metrics.counters(), CacheCounters::default());
+
+		metrics.record(false);
+		metrics.record(true);
+		metrics.record(true);
+
+		assert_eq!(metrics.counters(), CacheCounters { hits: 2, misses: 1 });
+	}
+
+	#[test]
+	fn test_format_prometheus() {
+		let counters = |requests, errors| Counters {
+			requests,
+			errors,
+			latency_ms_total: requests * 3,
+			latency_ms_max: 41,
+		};
+		let text = format_prometheus(&[(0, counters(1024, 0)), (1, counters(980, 12))]);
+
+		let lines = text.lines().collect::<Vec<_>>();
+		assert_eq!(lines.len(), 4 * 4);
+		assert_eq!(
+			&lines[..4],
+			&[
+				"# HELP yee_panel_node_requests_total Count of the node requests",
+				"# TYPE yee_panel_node_requests_total counter",
+				"yee_panel_node_requests_total{shard=\"0\"} 1024",
+				"yee_panel_node_requests_total{shard=\"1\"} 980",
+			]
+		);
+		assert!(lines.contains(&"yee_panel_node_errors_total{shard=\"1\"} 12"));
+		assert!(lines.contains(&"yee_panel_node_latency_ms_total{shard=\"0\"} 3072"));
+		assert!(lines.contains(&"# TYPE yee_panel_node_latency_ms_max gauge"));
+
+		// no shards
+		assert!(!format_prometheus(&[]).lines().any(|x| !x.starts_with('#')));
+	}
+
+	#[test]
+	fn test_format_prometheus_caches() {
+		let text = format_prometheus_caches(&[
+			("block", CacheCounters { hits: 2, misses: 1 }),
+			("head", CacheCounters { hits: 0, misses: 3 }),
+		]);
+
+		assert_eq!(
+			text.lines().collect::<Vec<_>>(),
+			vec![
+				"# HELP yee_panel_cache_hits_total Count of the cache hits",
+				"# TYPE yee_panel_cache_hits_total counter",
+				"yee_panel_cache_hits_total{cache=\"block\"} 2",
+				"yee_panel_cache_hits_total{cache=\"head\"} 0",
+				"# HELP yee_panel_cache_misses_total Count of the cache misses",
+				"# TYPE yee_panel_cache_misses_total counter",
+				"yee_panel_cache_misses_total{cache=\"block\"} 1",
+				"yee_panel_cache_misses_total{cache=\"head\"} 3",
+			]
+		);
+	}
+}
@@ -0,0 +1,188 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future;
+use futures::future::Future;
+use futures::stream::Stream;
+use log::{debug, warn};
+use tokio::runtime::Runtime;
+use tokio::timer::Interval;
+
+use crate::config::Prefetch;
+use crate::rpc::cache::Caches;
+use crate::rpc::chain::get_head_future;
+use crate::rpc::client::RpcClient;
+use crate::rpc::errors;
+
+/// Start the background refresh of the heads, stopped when the runtime is dropped
+pub fn start(
+	prefetch: &Prefetch,
+	shard_count: u16,
+	rpc_client: Arc<RpcClient>,
+	caches: Caches,
+) -> errors::Result<Runtime> {
+	let runtime = Runtime::new()?;
+
+	let interval = Duration::from_millis(prefetch.interval_ms);
+
+	// the next refresh waits for the previous one
+	let task = Interval::new(Instant::now(), interval)
+		.map_err(|e| warn!("Prefetch timer error: {:?}", e))
+		.for_each(move |_| refresh_future(rpc_client.clone(), caches.clone(), shard_count));
+
+	runtime.executor().spawn(task);
+
+	Ok(runtime)
+}
+
+/// Refresh the heads of all the shards once
+fn refresh_future(
+	rpc_client: Arc<RpcClient>,
+	caches: Caches,
+	shard_count: u16,
+) -> Box<dyn Future<Item = (), Error = ()> + Send> {
+	let futures = (0..shard_count)
+		.map(|shard_num| {
+			let caches = caches.clone();
+			get_head_future(rpc_client.clone(), shard_num).map(move |head| {
+				debug!("Prefetch shard {}: {:?}", shard_num, head);
+				if let Some(number) = head.finalized {
+					caches.block.set_finalized_number(shard_num, number);
+				}
+				caches.head.insert(shard_num, head);
+			})
+		})
+		.collect::<Vec<_>>();
+
+	let result = future::join_all(futures)
+		.map(|_| ())
+		.map_err(|e| warn!("Prefetch error: {:?}", e));
+
+	Box::new(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{Read, Write};
+	use std::net::TcpListener;
+	use std::sync::Arc;
+	use std::thread;
+
+	use serde_json::json;
+	use tokio::runtime::Runtime;
+
+	use crate::config::Config;
+	use crate::rpc::cache::Caches;
+	use crate::rpc::client::RpcClient;
+
+	use super::refresh_future;
+
+	#[test]
+	fn test_refresh_future() {
+		// nothing listens on the port, the heads degrade to none
+		let config: Config = toml::from_str(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["http://127.0.0.1:1"]
+			"#,
+		)
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+
+		assert!(caches.head.get(0).is_none());
+
+		let mut runtime = Runtime::new().unwrap();
+		runtime
+			.block_on(refresh_future(rpc_client, caches.clone(), 1))
+			.unwrap();
+
+		let head = caches.head.get(0).unwrap();
+		assert_eq!(head.best, None);
+		assert_eq!(head.finalized, None);
+		assert!(head.updated_at > 0);
+	}
+
+	#[test]
+	fn test_refresh_future_of_node() {
+		// a node of the best 65 and the finalized 64
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let uri = format!("http://{}", listener.local_addr().unwrap());
+		let header = |number: &str| {
+			json!({
+				"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+				"parentHash": format!("0x{}", "00".repeat(32)),
+				"stateRoot": format!("0x{}", "00".repeat(32)),
+				"number": number,
+			})
+		};
+		thread::spawn(move || {
+			let finalized_hash = format!("0x{}", "11".repeat(32));
+			for stream in listener.incoming() {
+				let mut stream = stream.unwrap();
+				// till the end of the json body
+				let mut request = String::new();
+				let mut buf = [0u8; 4096];
+				while !request.ends_with('}') {
+					let n = stream.read(&mut buf).unwrap();
+					assert!(n > 0);
+					request.push_str(&String::from_utf8_lossy(&buf[..n]));
+				}
+				let result = if request.contains("chain_getFinalizedHead") {
+					json!(finalized_hash)
+				} else if request.contains(&finalized_hash) {
+					header("0x40")
+				} else {
+					header("0x41")
+				};
+				let body = json!({ "jsonrpc": "2.0", "result": result, "id": 1 }).to_string();
+				let response = format!(
+					"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					body.len(),
+					body
+				);
+				stream.write_all(response.as_bytes()).unwrap();
+			}
+		});
+
+		let config: Config = toml::from_str(&format!(
+			r#"
+			[shards]
+			[shards.0]
+			rpc = ["{}"]
+			"#,
+			uri
+		))
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let caches = Caches::new(&config);
+
+		let mut runtime = Runtime::new().unwrap();
+		runtime
+			.block_on(refresh_future(rpc_client, caches.clone(), 1))
+			.unwrap();
+
+		let head = caches.head.get(0).unwrap();
+		assert_eq!(head.best, Some(65));
+		assert_eq!(head.finalized, Some(64));
+		assert_eq!(caches.block.finalized_number(0), Some(64));
+	}
+}
@@ -0,0 +1,131 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use serde_json::{Map, Value};
+
+/// Prune the value to the dot separated field paths, like `header.number`.
+/// The paths are applied to each item of the arrays, the unknown paths are ignored.
+pub fn project(value: &Value, fields: &[String]) -> Value {
+	let paths = fields
+		.iter()
+		.map(|x| x.split('.').collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+	let paths = paths.iter().map(|x| &x[..]).collect::<Vec<_>>();
+
+	match value {
+		Value::Object(_) | Value::Array(_) => {
+			project_paths(value, &paths).unwrap_or_else(|| Value::Object(Map::new()))
+		}
+		// like the SCALE encoded hex
+		_ => value.clone(),
+	}
+}
+
+/// None if no path applies to the value
+fn project_paths(value: &Value, paths: &[&[&str]]) -> Option<Value> {
+	// a path ending here selects the whole value
+	if paths.iter().any(|x| x.is_empty()) {
+		return Some(value.clone());
+	}
+
+	match value {
+		Value::Array(items) => Some(Value::Array(
+			items
+				.iter()
+				.map(|x| project_paths(x, paths).unwrap_or_else(|| Value::Object(Map::new())))
+				.collect(),
+		)),
+		Value::Object(map) => {
+			let mut result = Map::new();
+			for (key, child) in map {
+				let paths = paths
+					.iter()
+					.filter(|x| x[0] == key)
+					.map(|x| &x[1..])
+					.collect::<Vec<_>>();
+				if paths.is_empty() {
+					continue;
+				}
+				if let Some(child) = project_paths(child, &paths) {
+					result.insert(key.clone(), child);
+				}
+			}
+			Some(Value::Object(result))
+		}
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::project;
+
+	#[test]
+	fn test_project() {
+		let block = json!({
+			"header": { "number": 65, "block_hash": "0x00", "parent_hash": "0x01" },
+			"extrinsics": [
+				{ "hash": "0x02", "call": { "module": 0 }, "signature": null },
+				{ "hash": "0x03", "call": { "module": 4 }, "signature": { "nonce": 1 } },
+			],
+		});
+		let fields = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+		assert_eq!(
+			project(&block, &fields(&["header.number", "extrinsics.hash"])),
+			json!({
+				"header": { "number": 65 },
+				"extrinsics": [{ "hash": "0x02" }, { "hash": "0x03" }],
+			})
+		);
+
+		// a whole object, and a path within it
+		assert_eq!(
+			project(&block, &fields(&["header", "header.number"])),
+			json!({ "header": block["header"].clone() })
+		);
+
+		// the nested fields of each item
+		assert_eq!(
+			project(&block, &fields(&["extrinsics.signature.nonce"])),
+			json!({ "extrinsics": [{}, { "signature": { "nonce": 1 } }] })
+		);
+
+		// the unknown paths are ignored
+		assert_eq!(
+			project(
+				&block,
+				&fields(&[
+					"header.number",
+					"header.unknown",
+					"unknown",
+					"header.number.x"
+				])
+			),
+			json!({ "header": { "number": 65 } })
+		);
+		assert_eq!(project(&block, &fields(&["unknown"])), json!({}));
+
+		// not an object
+		assert_eq!(
+			project(&json!("0x0041"), &fields(&["header"])),
+			json!("0x0041")
+		);
+	}
+}
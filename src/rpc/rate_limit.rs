@@ -0,0 +1,301 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use http::hyper;
+use log::debug;
+
+use crate::config::RateLimit;
+use crate::rpc::errors;
+
+/// Buckets are pruned once there are more clients than this
+const MAX_BUCKETS: usize = 10000;
+
+/// The buckets are pruned at most once in this interval
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
+struct Bucket {
+	tokens: f64,
+	last: Instant,
+}
+
+struct Buckets {
+	buckets: HashMap<IpAddr, Bucket>,
+	pruned: Instant,
+}
+
+/// Token bucket rate limiter keyed by client ip
+pub struct RateLimiter {
+	rate: f64,
+	burst: f64,
+	/// The peers trusted to set `X-Forwarded-For` and `X-Real-IP`
+	trusted_proxies: Vec<IpAddr>,
+	buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+	pub fn new(config: &RateLimit) -> errors::Result<Self> {
+		let trusted_proxies = config
+			.trusted_proxies
+			.iter()
+			.map(|x| {
+				x.parse::<IpAddr>().map_err(|_| {
+					errors::ErrorKind::InvalidConfig(format!("Invalid trusted proxy: {}", x)).into()
+				})
+			})
+			.collect::<errors::Result<Vec<_>>>()?;
+
+		Ok(RateLimiter {
+			rate: config.requests_per_second as f64,
+			burst: config.burst.max(1) as f64,
+			trusted_proxies,
+			buckets: Mutex::new(Buckets {
+				buckets: HashMap::new(),
+				pruned: Instant::now(),
+			}),
+		})
+	}
+
+	/// Take a token for the client, return false if the client is over the limit
+	pub fn check(&self, client: &IpAddr) -> bool {
+		self.check_at(client, Instant::now())
+	}
+
+	fn check_at(&self, client: &IpAddr, now: Instant) -> bool {
+		let mut buckets = self.buckets.lock().expect("qed");
+
+		if buckets.buckets.len() > MAX_BUCKETS
+			&& now.duration_since(buckets.pruned) >= PRUNE_INTERVAL
+		{
+			let (rate, burst) = (self.rate, self.burst);
+			buckets.buckets.retain(|_, bucket| {
+				let elapsed = now.duration_since(bucket.last).as_secs_f64();
+				bucket.tokens + elapsed * rate < burst
+			});
+			buckets.pruned = now;
+		}
+
+		let burst = self.burst;
+		let bucket = buckets.buckets.entry(*client).or_insert(Bucket {
+			tokens: burst,
+			last: now,
+		});
+
+		let elapsed = now.duration_since(bucket.last).as_secs_f64();
+		bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+		bucket.last = now;
+
+		if bucket.tokens >= 1.0 {
+			bucket.tokens -= 1.0;
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Milliseconds until the client has a token again
+	pub fn retry_after_ms(&self, client: &IpAddr) -> u64 {
+		self.retry_after_ms_at(client, Instant::now())
+	}
+
+	fn retry_after_ms_at(&self, client: &IpAddr, now: Instant) -> u64 {
+		let buckets = self.buckets.lock().expect("qed");
+		let tokens = match buckets.buckets.get(client) {
+			Some(bucket) => {
+				let elapsed = now.duration_since(bucket.last).as_secs_f64();
+				bucket.tokens + elapsed * self.rate
+			}
+			None => return 0,
+		};
+		if tokens >= 1.0 || self.rate <= 0.0 {
+			return 0;
+		}
+		((1.0 - tokens) / self.rate * 1000.0).ceil() as u64
+	}
+
+	/// The peer itself, or the client forwarded by a trusted proxy:
+	/// the last address of `X-Forwarded-For` which is not a trusted proxy, or `X-Real-IP`
+	pub fn client_ip(&self, request: &hyper::Request<hyper::Body>, peer: IpAddr) -> IpAddr {
+		if !self.trusted_proxies.contains(&peer) {
+			return peer;
+		}
+
+		let headers = request.headers();
+		let forwarded = headers
+			.get_all("x-forwarded-for")
+			.iter()
+			.filter_map(|x| x.to_str().ok())
+			.flat_map(|x| x.split(','))
+			.filter_map(|x| x.trim().parse::<IpAddr>().ok())
+			.collect::<Vec<_>>();
+		if let Some(client) = forwarded
+			.iter()
+			.rev()
+			.find(|x| !self.trusted_proxies.contains(x))
+		{
+			return *client;
+		}
+
+		let real_ip = headers
+			.get("x-real-ip")
+			.and_then(|x| x.to_str().ok())
+			.and_then(|x| x.trim().parse::<IpAddr>().ok());
+
+		real_ip.or(forwarded.first().cloned()).unwrap_or(peer)
+	}
+
+	/// The 429 response of the limited client
+	pub fn refuse(&self, client: &IpAddr) -> hyper::Response<hyper::Body> {
+		debug!("Rate limited: {}", client);
+
+		let retry_after_ms = self.retry_after_ms(client);
+		let error: jsonrpc_core::Error =
+			errors::Error::from(errors::ErrorKind::RateLimited(retry_after_ms)).into();
+		let output = jsonrpc_core::Output::Failure(jsonrpc_core::Failure {
+			jsonrpc: Some(jsonrpc_core::Version::V2),
+			error,
+			id: jsonrpc_core::Id::Null,
+		});
+		let body = serde_json::to_string(&output).expect("qed");
+
+		hyper::Response::builder()
+			.status(hyper::StatusCode::TOO_MANY_REQUESTS)
+			.header(hyper::header::CONTENT_TYPE, "application/json")
+			.body(hyper::Body::from(body))
+			.expect("qed")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::IpAddr;
+	use std::time::{Duration, Instant};
+
+	use http::hyper;
+
+	use crate::config::RateLimit;
+
+	use super::RateLimiter;
+
+	fn ip(x: &str) -> IpAddr {
+		x.parse().unwrap()
+	}
+
+	#[test]
+	fn test_rate_limit() {
+		let limiter = RateLimiter::new(&RateLimit {
+			requests_per_second: 2,
+			burst: 3,
+			trusted_proxies: vec![],
+		})
+		.unwrap();
+
+		let now = Instant::now();
+
+		// burst
+		assert!(limiter.check_at(&ip("127.0.0.1"), now));
+		assert!(limiter.check_at(&ip("127.0.0.1"), now));
+		assert!(limiter.check_at(&ip("127.0.0.1"), now));
+		assert!(!limiter.check_at(&ip("127.0.0.1"), now));
+
+		// other clients are not affected
+		assert!(limiter.check_at(&ip("127.0.0.2"), now));
+
+		// refill
+		let now = now + Duration::from_millis(500);
+		assert!(limiter.check_at(&ip("127.0.0.1"), now));
+		assert!(!limiter.check_at(&ip("127.0.0.1"), now));
+	}
+
+	#[test]
+	fn test_retry_after_ms() {
+		let limiter = RateLimiter::new(&RateLimit {
+			requests_per_second: 2,
+			burst: 1,
+			trusted_proxies: vec![],
+		})
+		.unwrap();
+
+		let now = Instant::now();
+		assert_eq!(limiter.retry_after_ms_at(&ip("127.0.0.1"), now), 0);
+
+		// a token every 500 ms
+		assert!(limiter.check_at(&ip("127.0.0.1"), now));
+		assert!(!limiter.check_at(&ip("127.0.0.1"), now));
+		assert_eq!(limiter.retry_after_ms_at(&ip("127.0.0.1"), now), 500);
+		assert_eq!(
+			limiter.retry_after_ms_at(&ip("127.0.0.1"), now + Duration::from_millis(200)),
+			300
+		);
+		assert_eq!(
+			limiter.retry_after_ms_at(&ip("127.0.0.1"), now + Duration::from_millis(500)),
+			0
+		);
+	}
+
+	#[test]
+	fn test_client_ip() {
+		let limiter = RateLimiter::new(&RateLimit {
+			requests_per_second: 2,
+			burst: 1,
+			trusted_proxies: vec!["10.0.0.1".to_string(), "10.0.0.2".to_string()],
+		})
+		.unwrap();
+
+		let request = |forwarded: &str| {
+			hyper::Request::post("/")
+				.header("x-forwarded-for", forwarded)
+				.body(hyper::Body::empty())
+				.unwrap()
+		};
+
+		// an untrusted peer can not spoof another client
+		assert_eq!(
+			limiter.client_ip(&request("127.0.0.2"), ip("192.168.0.1")),
+			ip("192.168.0.1")
+		);
+
+		// the last address not a trusted proxy
+		assert_eq!(
+			limiter.client_ip(&request("127.0.0.2, 127.0.0.3, 10.0.0.2"), ip("10.0.0.1")),
+			ip("127.0.0.3")
+		);
+
+		let request = hyper::Request::post("/")
+			.header("x-real-ip", "127.0.0.4")
+			.body(hyper::Body::empty())
+			.unwrap();
+		assert_eq!(limiter.client_ip(&request, ip("10.0.0.1")), ip("127.0.0.4"));
+
+		// no header
+		let request = hyper::Request::post("/")
+			.body(hyper::Body::empty())
+			.unwrap();
+		assert_eq!(limiter.client_ip(&request, ip("10.0.0.1")), ip("10.0.0.1"));
+
+		let config = RateLimit {
+			requests_per_second: 2,
+			burst: 1,
+			trusted_proxies: vec!["not an ip".to_string()],
+		};
+		assert!(RateLimiter::new(&config).is_err());
+	}
+}
@@ -0,0 +1,174 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use futures::{Future, Poll};
+use http::hyper;
+use jsonrpc_core::{BoxFuture, Params, RemoteProcedure, RpcMethod, Value};
+
+use crate::config::Config;
+use crate::rpc::metadata::Metadata;
+
+thread_local! {
+	/// Request id of the rpc call being run on the thread
+	static CURRENT: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Request id of the rpc call being run, sent to the nodes by the rpc client
+pub fn current() -> Option<String> {
+	CURRENT.with(|x| x.borrow().clone())
+}
+
+/// Propagate the request id of the incoming requests to the node requests
+pub struct RequestIds {
+	header: Option<String>,
+}
+
+impl RequestIds {
+	pub fn new(config: &Config) -> Self {
+		RequestIds {
+			header: config.request_id.as_ref().map(|x| x.header.clone()),
+		}
+	}
+
+	/// Metadata with the request id of the incoming http request
+	pub fn read_metadata(&self, request: &hyper::Request<hyper::Body>) -> Metadata {
+		let request_id = self.header.as_ref().and_then(|header| {
+			request
+				.headers()
+				.get(header.as_str())
+				.and_then(|x| x.to_str().ok())
+				.map(|x| x.to_string())
+		});
+		Metadata::with_request_id(request_id)
+	}
+
+	/// Run the method with the request id of the call, a random one if the call has none
+	pub fn apply(&self, procedure: RemoteProcedure<Metadata>) -> RemoteProcedure<Metadata> {
+		match (procedure, &self.header) {
+			(RemoteProcedure::Method(method), Some(_)) => {
+				RemoteProcedure::Method(Arc::new(RequestIdMethod { method }))
+			}
+			(procedure, _) => procedure,
+		}
+	}
+}
+
+struct RequestIdMethod {
+	method: Arc<dyn RpcMethod<Metadata>>,
+}
+
+impl RpcMethod<Metadata> for RequestIdMethod {
+	fn call(&self, params: Params, meta: Metadata) -> BoxFuture<Value> {
+		let request_id = meta
+			.request_id()
+			.unwrap_or_else(|| format!("{:016x}", rand::random::<u64>()));
+
+		let method = self.method.clone();
+		let result = scoped(&Some(request_id.clone()), move || method.call(params, meta));
+
+		Box::new(Scoped {
+			request_id: Some(request_id),
+			inner: result,
+		})
+	}
+}
+
+/// The node requests of the future chain are sent on poll
+struct Scoped<F> {
+	request_id: Option<String>,
+	inner: F,
+}
+
+impl<F: Future> Future for Scoped<F> {
+	type Item = F::Item;
+	type Error = F::Error;
+
+	fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+		let inner = &mut self.inner;
+		scoped(&self.request_id, move || inner.poll())
+	}
+}
+
+/// Run `f` with the request id, the previous one is restored after
+fn scoped<R, F: FnOnce() -> R>(request_id: &Option<String>, f: F) -> R {
+	let previous = CURRENT.with(|x| x.replace(request_id.clone()));
+	let result = f();
+	CURRENT.with(|x| x.replace(previous));
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use futures::future;
+	use futures::Future;
+	use http::hyper;
+	use jsonrpc_core::{Params, RemoteProcedure, RpcMethod};
+	use serde_json::json;
+
+	use crate::config::Config;
+	use crate::rpc::metadata::Metadata;
+
+	use super::{current, RequestIds};
+
+	#[test]
+	fn test_request_ids() {
+		let config: Config = toml::from_str(
+			r#"
+			[request_id]
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let request_ids = RequestIds::new(&config);
+
+		// the header of the incoming request
+		let request = hyper::Request::post("http://127.0.0.1:10055")
+			.header("X-Request-Id", "abc")
+			.body(hyper::Body::empty())
+			.unwrap();
+		let meta = request_ids.read_metadata(&request);
+		assert_eq!(meta.request_id(), Some("abc".to_string()));
+
+		// seen by the method and by the future chain after it
+		let method = |_: Params, _: Metadata| {
+			let at_call = current();
+			future::ok::<_, jsonrpc_core::Error>(()).map(move |_| json!([at_call, current()]))
+		};
+		let method = match request_ids.apply(RemoteProcedure::Method(Arc::new(method))) {
+			RemoteProcedure::Method(method) => method,
+			_ => unreachable!(),
+		};
+		let result = method.call(Params::None, meta).wait().unwrap();
+		assert_eq!(result, json!(["abc", "abc"]));
+
+		// a random one without the header
+		let result = method
+			.call(Params::None, Metadata::default())
+			.wait()
+			.unwrap();
+		assert!(result[0].is_string());
+		assert_eq!(result[0], result[1]);
+
+		// restored after the call
+		assert!(current().is_none());
+	}
+}
@@ -0,0 +1,142 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use futures::Future;
+use jsonrpc_core::{BoxFuture, Metadata, Params, RemoteProcedure, RpcMethod, Value};
+use log::info;
+
+use crate::config::Config;
+use crate::rpc::request_id;
+
+/// Log of the rpc calls, the sampled methods are logged 1 in N
+pub struct RequestLogger {
+	enabled: bool,
+	sample: HashMap<String, u64>,
+}
+
+impl RequestLogger {
+	pub fn new(config: &Config) -> Self {
+		RequestLogger {
+			enabled: config.request_log.is_some(),
+			sample: config
+				.request_log
+				.as_ref()
+				.map(|x| x.sample.clone())
+				.unwrap_or_default(),
+		}
+	}
+
+	/// Log the calls of the method when they complete
+	pub fn apply<M: Metadata>(
+		&self,
+		name: &str,
+		procedure: RemoteProcedure<M>,
+	) -> RemoteProcedure<M> {
+		match procedure {
+			RemoteProcedure::Method(method) if self.enabled => {
+				let every = self.sample.get(name).cloned().unwrap_or(1);
+				RemoteProcedure::Method(Arc::new(LogMethod {
+					name: name.to_string(),
+					sampler: Sampler::new(every),
+					method,
+				}))
+			}
+			procedure => procedure,
+		}
+	}
+}
+
+/// 1 in `every` calls, all the calls if `every` is 0 or 1
+struct Sampler {
+	every: u64,
+	count: AtomicU64,
+}
+
+impl Sampler {
+	fn new(every: u64) -> Self {
+		Sampler {
+			every: every.max(1),
+			count: AtomicU64::new(0),
+		}
+	}
+
+	fn sample(&self) -> bool {
+		self.count.fetch_add(1, Ordering::Relaxed) % self.every == 0
+	}
+}
+
+struct LogMethod<M: Metadata> {
+	name: String,
+	sampler: Sampler,
+	method: Arc<dyn RpcMethod<M>>,
+}
+
+impl<M: Metadata> RpcMethod<M> for LogMethod<M> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Value> {
+		let result = self.method.call(params, meta);
+
+		if !self.sampler.sample() {
+			return result;
+		}
+
+		let name = self.name.clone();
+		let request_id = request_id::current();
+		let start = Instant::now();
+
+		let result = result.then(move |x| {
+			let elapsed = start.elapsed().as_millis();
+			let status = match &x {
+				Ok(_) => "ok".to_string(),
+				Err(e) => format!("error {}", e.message),
+			};
+			match request_id {
+				Some(request_id) => info!("{} {} {} ms [{}]", name, status, elapsed, request_id),
+				None => info!("{} {} {} ms", name, status, elapsed),
+			}
+			x
+		});
+
+		Box::new(result)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Sampler;
+
+	#[test]
+	fn test_sampler() {
+		const CALLS: usize = 10000;
+
+		// 1 in 100
+		let sampler = Sampler::new(100);
+		let sampled = (0..CALLS).filter(|_| sampler.sample()).count();
+		let ratio = sampled as f64 / CALLS as f64;
+		assert!((ratio - 0.01).abs() < 0.001, "ratio {}", ratio);
+
+		// all
+		let sampler = Sampler::new(1);
+		assert_eq!((0..CALLS).filter(|_| sampler.sample()).count(), CALLS);
+		let sampler = Sampler::new(0);
+		assert_eq!((0..CALLS).filter(|_| sampler.sample()).count(), CALLS);
+	}
+}
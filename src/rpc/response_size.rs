@@ -0,0 +1,152 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use futures::Future;
+use jsonrpc_core::{BoxFuture, Metadata, Params, RemoteProcedure, RpcMethod, Value};
+
+use crate::config::Config;
+use crate::rpc::errors;
+
+/// Max bytes of the serialized results of the rpc methods, 0 means unlimited
+pub struct ResponseSizes {
+	max: usize,
+}
+
+impl ResponseSizes {
+	pub fn new(config: &Config) -> Self {
+		ResponseSizes {
+			max: config.max_response_size,
+		}
+	}
+
+	/// Check the final result of the method, as sent to the client
+	pub fn apply<M: Metadata>(&self, procedure: RemoteProcedure<M>) -> RemoteProcedure<M> {
+		match (procedure, self.max) {
+			(RemoteProcedure::Method(method), max) if max > 0 => {
+				RemoteProcedure::Method(Arc::new(ResponseSizeMethod { max, method }))
+			}
+			(procedure, _) => procedure,
+		}
+	}
+}
+
+struct ResponseSizeMethod<M: Metadata> {
+	max: usize,
+	method: Arc<dyn RpcMethod<M>>,
+}
+
+impl<M: Metadata> RpcMethod<M> for ResponseSizeMethod<M> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Value> {
+		let max = self.max;
+
+		let result =
+			self.method
+				.call(params, meta)
+				.and_then(move |x| -> jsonrpc_core::Result<Value> {
+					check_response_size(&x, max)?;
+					Ok(x)
+				});
+
+		Box::new(result)
+	}
+}
+
+/// Check the serialized size of the value, 0 means unlimited
+fn check_response_size(value: &Value, max: usize) -> errors::Result<()> {
+	if max == 0 {
+		return Ok(());
+	}
+	let size = serde_json::to_vec(value)
+		.map_err(|_| errors::Error::from(errors::ErrorKind::ParseError))?
+		.len();
+	if size > max {
+		return Err(errors::ErrorKind::ResponseTooLarge(size, max).into());
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use futures::future;
+	use jsonrpc_core::{MetaIoHandler, Params, RemoteProcedure, RpcMethod};
+	use serde_json::json;
+
+	use crate::config::Config;
+	use crate::rpc::errors;
+
+	use super::{check_response_size, ResponseSizes};
+
+	fn block(count: usize) -> serde_json::Value {
+		json!({
+			"header": { "number": 1 },
+			"extrinsics": vec![json!({ "raw": "0x".to_string() + &"00".repeat(1024) }); count],
+		})
+	}
+
+	#[test]
+	fn test_check_response_size() {
+		assert!(check_response_size(&block(1), 64 * 1024).is_ok());
+		assert!(check_response_size(&block(1024), 0).is_ok());
+
+		match check_response_size(&block(1024), 64 * 1024) {
+			Err(errors::Error(errors::ErrorKind::ResponseTooLarge(size, max), _)) => {
+				assert!(size > max);
+				assert_eq!(max, 64 * 1024);
+			}
+			_ => panic!("expected ResponseTooLarge"),
+		}
+	}
+
+	#[test]
+	fn test_response_sizes() {
+		let config: Config = toml::from_str(
+			r#"
+			max_response_size = 65536
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let response_sizes = ResponseSizes::new(&config);
+
+		// results built by the methods of any size
+		let methods = [("small_block", 1), ("large_block", 1024)]
+			.iter()
+			.map(|(name, count)| {
+				let count = *count;
+				let method: Arc<dyn RpcMethod<()>> =
+					Arc::new(move |_: Params, _: ()| future::ok(block(count)));
+				let procedure = response_sizes.apply(RemoteProcedure::Method(method));
+				(name.to_string(), procedure)
+			})
+			.collect::<Vec<_>>();
+		let mut io = MetaIoHandler::<()>::default();
+		io.extend_with(methods);
+
+		let request = r#"{"jsonrpc":"2.0","method":"small_block","params":[],"id":1}"#;
+		let response = io.handle_request_sync(request, ()).unwrap();
+		assert!(response.contains("\"result\""));
+
+		let request = r#"{"jsonrpc":"2.0","method":"large_block","params":[],"id":1}"#;
+		let response: serde_json::Value =
+			serde_json::from_str(&io.handle_request_sync(request, ()).unwrap()).unwrap();
+		assert_eq!(response["error"]["message"], json!("Response too large"));
+	}
+}
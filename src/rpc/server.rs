@@ -15,22 +15,65 @@
 // You should have received a copy of the GNU General Public License
 // along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
 
+use http::hyper;
 use log::error;
 use std::io;
+use std::sync::Arc;
 
-const MAX_PAYLOAD: usize = 15 * 1024 * 1024;
+use crate::rpc::batch_limit::BatchLimiter;
+use crate::rpc::front::{self, Front};
+use crate::rpc::request_id::RequestIds;
+
+pub const MAX_PAYLOAD: usize = 15 * 1024 * 1024;
 
 type Metadata = crate::rpc::metadata::Metadata;
-type RpcHandler = pubsub::PubSubHandler<Metadata>;
+type RpcHandler = pubsub::PubSubHandler<Metadata, BatchLimiter>;
+
+/// The json-rpc http server, behind the front server if set
+pub struct HttpServer {
+	_server: http::Server,
+	_front: Option<front::Server>,
+}
 
-pub fn start_http(addr: &std::net::SocketAddr, io: RpcHandler) -> io::Result<http::Server> {
-	http::ServerBuilder::new(io)
-		.threads(4)
+pub fn start_http(
+	addr: &std::net::SocketAddr,
+	io: RpcHandler,
+	front: Option<Front>,
+	request_ids: Arc<RequestIds>,
+	keep_alive: bool,
+) -> io::Result<HttpServer> {
+	// the listeners of the threads would take a port each if bound to port 0
+	let threads = if front.is_some() { 1 } else { 4 };
+
+	let builder = http::ServerBuilder::new(io)
+		.threads(threads)
+		.keep_alive(keep_alive)
 		.health_api(("/health", "system_health"))
 		.rest_api(http::RestApi::Unsecure)
 		.cors(http::DomainsValidation::Disabled)
 		.max_request_body_size(MAX_PAYLOAD)
-		.start_http(addr)
+		.meta_extractor(move |request: &hyper::Request<hyper::Body>| {
+			request_ids.read_metadata(request)
+		});
+
+	let front = match front {
+		Some(front) => front,
+		None => {
+			return Ok(HttpServer {
+				_server: builder.start_http(addr)?,
+				_front: None,
+			})
+		}
+	};
+
+	// the front server forwards to the loopback
+	let server = builder.start_http(&([127, 0, 0, 1], 0).into())?;
+	let front = front::start(addr, *server.address(), front, keep_alive)?;
+
+	Ok(HttpServer {
+		_server: server,
+		_front: Some(front),
+	})
 }
 
 /// Start WS server listening on given address.
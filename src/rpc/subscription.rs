@@ -0,0 +1,480 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use futures::future::Future;
+use futures::sink::Sink;
+use futures::stream::{self, Stream};
+use futures::sync::oneshot;
+use jsonrpc_derive::rpc;
+use log::warn;
+use pubsub::typed::Subscriber;
+use pubsub::{PubSubMetadata, SubscriptionId};
+use serde_json::{json, Value};
+use tokio::runtime::TaskExecutor;
+use tokio::timer::Interval;
+
+use crate::config::Config;
+use crate::rpc::chain::check_shard_num;
+use crate::rpc::client::{self, RpcClient};
+use crate::rpc::errors;
+use crate::rpc::metadata::Metadata;
+use crate::rpc::serde::Hex;
+use crate::rpc::types::{BlockNumber, ResultHeader};
+
+/// The nodes are polled for the best header
+const NEW_HEADS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[rpc]
+pub trait SubscriptionApi {
+	type Metadata;
+
+	#[pubsub(
+		subscription = "chain_newHead",
+		subscribe,
+		name = "chain_subscribeNewHeads"
+	)]
+	fn subscribe_new_heads(
+		&self,
+		meta: Self::Metadata,
+		subscriber: Subscriber<Value>,
+		shard_num: u16,
+	);
+
+	#[pubsub(
+		subscription = "chain_newHead",
+		unsubscribe,
+		name = "chain_unsubscribeNewHeads"
+	)]
+	fn unsubscribe_new_heads(
+		&self,
+		meta: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
+}
+
+/// Subscription name of the new heads
+pub const NEW_HEADS: &str = "chain_newHead";
+
+pub struct Subscription {
+	/// Swapped on reload
+	config: Arc<RwLock<Config>>,
+	rpc_client: Arc<RpcClient>,
+	executor: TaskExecutor,
+	active: Arc<ActiveSubscriptions>,
+}
+
+/// The subscriptions of all the connections, shared by the handlers so that the ids are unique
+pub struct ActiveSubscriptions {
+	next_id: AtomicUsize,
+	subscriptions: Mutex<HashMap<SubscriptionId, Active>>,
+}
+
+/// Stopped by `cancel`
+struct Active {
+	name: &'static str,
+	shard_num: u16,
+	cancel: oneshot::Sender<()>,
+	_slot: Slot,
+}
+
+/// A subscription of the connection, released on drop
+struct Slot {
+	count: Arc<AtomicUsize>,
+}
+
+impl Slot {
+	/// None if the connection has `max` subscriptions already, 0 means unlimited
+	fn acquire(count: Arc<AtomicUsize>, max: usize) -> Option<Self> {
+		let current = count.fetch_add(1, Ordering::SeqCst);
+		if max > 0 && current >= max {
+			count.fetch_sub(1, Ordering::SeqCst);
+			return None;
+		}
+		Some(Slot { count })
+	}
+}
+
+impl Drop for Slot {
+	fn drop(&mut self) {
+		self.count.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+impl Subscription {
+	/// Create new Subscription API RPC handler.
+	/// The subscriptions are driven by `executor` and registered in `active`.
+	pub fn new(
+		config: Arc<RwLock<Config>>,
+		rpc_client: Arc<RpcClient>,
+		executor: TaskExecutor,
+		active: Arc<ActiveSubscriptions>,
+	) -> Self {
+		Self {
+			config,
+			rpc_client,
+			executor,
+			active,
+		}
+	}
+}
+
+impl ActiveSubscriptions {
+	pub fn new() -> Self {
+		ActiveSubscriptions {
+			next_id: AtomicUsize::new(0),
+			subscriptions: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// (subscription name, shard_num) of the active subscriptions
+	pub fn list(&self) -> Vec<(&'static str, u16)> {
+		self.subscriptions
+			.lock()
+			.expect("qed")
+			.values()
+			.map(|x| (x.name, x.shard_num))
+			.collect()
+	}
+
+	fn next_id(&self) -> SubscriptionId {
+		SubscriptionId::Number(self.next_id.fetch_add(1, Ordering::SeqCst) as u64)
+	}
+
+	fn insert(&self, id: SubscriptionId, active: Active) {
+		self.subscriptions.lock().expect("qed").insert(id, active);
+	}
+
+	/// Stop the subscription, false if not active
+	fn cancel(&self, id: &SubscriptionId) -> bool {
+		match self.subscriptions.lock().expect("qed").remove(id) {
+			Some(active) => {
+				let _ = active.cancel.send(());
+				true
+			}
+			None => false,
+		}
+	}
+}
+
+impl SubscriptionApi for Subscription {
+	type Metadata = Metadata;
+
+	fn subscribe_new_heads(&self, meta: Metadata, subscriber: Subscriber<Value>, shard_num: u16) {
+		if let Err(e) = check_shard_num(shard_num, &self.config.read().expect("qed")) {
+			let _ = subscriber.reject(e.into());
+			return;
+		}
+
+		let max = self.config.read().expect("qed").max_subscriptions;
+		let slot = match Slot::acquire(meta.subscriptions(), max) {
+			Some(slot) => slot,
+			None => {
+				let e = errors::Error::from(errors::ErrorKind::TooManySubscriptions(max));
+				let _ = subscriber.reject(e.into());
+				return;
+			}
+		};
+
+		let id = self.active.next_id();
+		let sink = match subscriber.assign_id(id.clone()) {
+			Ok(sink) => sink,
+			Err(_) => return,
+		};
+
+		let rpc_client = self.rpc_client.clone();
+		let mut tracker = NewHeadsTracker::new();
+
+		let events = Interval::new(Instant::now(), NEW_HEADS_POLL_INTERVAL)
+			.map_err(|e| warn!("New heads timer error: {:?}", e))
+			.and_then(move |_| {
+				// a failed poll is skipped
+				get_best_header_future(rpc_client.clone(), shard_num)
+					.then(|x| -> Result<Option<ResultHeader>, ()> { Ok(x.ok().and_then(|x| x)) })
+			})
+			.filter_map(|x| x)
+			.map(move |header| stream::iter_ok::<_, ()>(tracker.on_header(header)))
+			.flatten()
+			.map(|event| Ok::<_, jsonrpc_core::Error>(event));
+
+		let task = sink
+			.sink_map_err(|e| warn!("New heads notification error: {:?}", e))
+			.send_all(events)
+			.map(|_| ());
+
+		// stopped when unsubscribed or disconnected
+		let (cancel, cancelled) = oneshot::channel();
+		self.active.insert(
+			id.clone(),
+			Active {
+				name: NEW_HEADS,
+				shard_num,
+				cancel,
+				_slot: slot,
+			},
+		);
+
+		if let Some(session) = meta.session() {
+			let active = self.active.clone();
+			let id = id.clone();
+			session.on_drop(move || {
+				active.cancel(&id);
+			});
+		}
+
+		let active = self.active.clone();
+		let task = task
+			.select(cancelled.map_err(|_| ()))
+			.then(move |_| -> Result<(), ()> {
+				active.cancel(&id);
+				Ok(())
+			});
+
+		self.executor.spawn(task);
+	}
+
+	fn unsubscribe_new_heads(
+		&self,
+		_meta: Option<Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		match self.active.cancel(&id) {
+			true => Ok(true),
+			false => Err(jsonrpc_core::Error::invalid_params(
+				"Invalid subscription id",
+			)),
+		}
+	}
+}
+
+fn get_best_header_future(
+	rpc_client: Arc<RpcClient>,
+	shard_num: u16,
+) -> Box<dyn Future<Item = Option<ResultHeader>, Error = jsonrpc_core::Error> + Send> {
+	let result = client::get_best_hash_future(rpc_client.clone(), shard_num);
+
+	let result = result.and_then(move |hash| {
+		let header = client::get_header_future(rpc_client, &hash, shard_num);
+		header.map(|header| match (header, hash) {
+			(Some(header), Some(hash)) => {
+				let mut header: ResultHeader = header.into();
+				header.block_hash = Some(hash);
+				Some(header)
+			}
+			_ => None,
+		})
+	});
+
+	Box::new(result)
+}
+
+/// Track the head of a new heads subscription to detect the reorgs
+struct NewHeadsTracker {
+	head: Option<(BlockNumber, Vec<u8>)>,
+}
+
+impl NewHeadsTracker {
+	fn new() -> Self {
+		NewHeadsTracker { head: None }
+	}
+
+	/// Events of the polled best header, a reorg event goes before the new head
+	fn on_header(&mut self, header: ResultHeader) -> Vec<Value> {
+		let hash = match &header.block_hash {
+			Some(hash) => hash.0.clone(),
+			None => return vec![],
+		};
+
+		let mut events = vec![];
+
+		if let Some((number, head_hash)) = &self.head {
+			if *head_hash == hash {
+				return events;
+			}
+
+			// the parent of a skipped block is unknown, only a rewind is detected then
+			let reorg = if header.number == number + 1 {
+				header.parent_hash != *head_hash
+			} else {
+				header.number <= *number
+			};
+
+			if reorg {
+				events.push(json!({
+					"type": "reorg",
+					"from": {
+						"number": number,
+						"hash": Hex(head_hash.clone()),
+					},
+					"to": {
+						"number": header.number,
+						"hash": Hex(hash.clone()),
+					},
+				}));
+			}
+		}
+
+		self.head = Some((header.number, hash));
+
+		events.push(json!({
+			"type": "head",
+			"header": header,
+		}));
+
+		events
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, RwLock};
+
+	use serde_json::json;
+	use tokio::runtime::Runtime;
+
+	use crate::config::Config;
+	use crate::rpc::client::RpcClient;
+	use crate::rpc::metadata::Metadata;
+	use crate::rpc::serde::Hex;
+	use crate::rpc::types::{BlockNumber, ResultHeader};
+
+	use super::{ActiveSubscriptions, NewHeadsTracker, Subscription, SubscriptionApi};
+
+	fn header(number: BlockNumber, hash: u8, parent_hash: u8) -> ResultHeader {
+		ResultHeader {
+			block_hash: Some(Hex(vec![hash; 32])),
+			extrinsics_root: vec![0u8; 32],
+			parent_hash: vec![parent_hash; 32],
+			state_root: vec![0u8; 32],
+			number,
+		}
+	}
+
+	fn types(events: &[serde_json::Value]) -> Vec<&str> {
+		events.iter().map(|x| x["type"].as_str().unwrap()).collect()
+	}
+
+	#[test]
+	fn test_new_heads_tracker() {
+		let mut tracker = NewHeadsTracker::new();
+
+		assert_eq!(types(&tracker.on_header(header(10, 10, 9))), vec!["head"]);
+		assert_eq!(types(&tracker.on_header(header(11, 11, 10))), vec!["head"]);
+
+		// unchanged
+		assert!(tracker.on_header(header(11, 11, 10)).is_empty());
+
+		// skipped blocks
+		assert_eq!(types(&tracker.on_header(header(13, 13, 12))), vec!["head"]);
+
+		// parent mismatch
+		let events = tracker.on_header(header(14, 0xa4, 0xa3));
+		assert_eq!(types(&events), vec!["reorg", "head"]);
+		assert_eq!(events[0]["from"]["number"], 13);
+		assert_eq!(events[0]["to"]["number"], 14);
+		assert_eq!(events[1]["header"]["number"], 14);
+
+		// the chain rewinds
+		let events = tracker.on_header(header(12, 0xb2, 11));
+		assert_eq!(types(&events), vec!["reorg", "head"]);
+		assert_eq!(
+			events[0],
+			json!({
+				"type": "reorg",
+				"from": {
+					"number": 14,
+					"hash": Hex(vec![0xa4u8; 32]),
+				},
+				"to": {
+					"number": 12,
+					"hash": Hex(vec![0xb2u8; 32]),
+				},
+			})
+		);
+
+		assert_eq!(
+			types(&tracker.on_header(header(13, 0xb3, 0xb2))),
+			vec!["head"]
+		);
+	}
+
+	#[test]
+	fn test_max_subscriptions() {
+		let config: Config = toml::from_str(
+			r#"
+			max_subscriptions = 2
+			[shards]
+			[shards.0]
+			rpc = ["http://127.0.0.1:1"]
+			"#,
+		)
+		.unwrap();
+		let rpc_client = Arc::new(RpcClient::new(config.clone()).unwrap());
+		let runtime = Runtime::new().unwrap();
+
+		let mut io = pubsub::PubSubHandler::default();
+		let active = Arc::new(ActiveSubscriptions::new());
+		io.extend_with(
+			Subscription::new(
+				Arc::new(RwLock::new(config)),
+				rpc_client,
+				runtime.executor(),
+				active,
+			)
+			.to_delegate(),
+		);
+
+		let (_receiver, meta) = Metadata::new_test();
+		let request = |method: &str, params: serde_json::Value, meta: &Metadata| {
+			let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+			let response = io
+				.handle_request_sync(&request.to_string(), meta.clone())
+				.unwrap();
+			serde_json::from_str::<serde_json::Value>(&response).unwrap()
+		};
+		let subscribe = |meta: &Metadata| request("chain_subscribeNewHeads", json!([0]), meta);
+
+		// up to the limit
+		let first = subscribe(&meta);
+		assert!(first["result"].is_number());
+		assert!(subscribe(&meta)["result"].is_number());
+
+		let response = subscribe(&meta);
+		assert_eq!(response["error"]["message"], "Too many subscriptions");
+		assert_eq!(response["error"]["data"], json!({ "max": 2 }));
+
+		// the limit is per connection
+		let (_other_receiver, other) = Metadata::new_test();
+		assert!(subscribe(&other)["result"].is_number());
+
+		// unsubscribing frees a slot
+		let response = request("chain_unsubscribeNewHeads", json!([first["result"]]), &meta);
+		assert_eq!(response["result"], true);
+		assert!(subscribe(&meta)["result"].is_number());
+
+		// the shard num of the shard count is rejected without taking a slot
+		let (_third_receiver, third) = Metadata::new_test();
+		let response = request("chain_subscribeNewHeads", json!([1]), &third);
+		assert_eq!(response["error"]["message"], "Invalid shard");
+		assert!(subscribe(&third)["result"].is_number());
+		assert!(subscribe(&third)["result"].is_number());
+	}
+}
@@ -0,0 +1,168 @@
+// Copyright (C) 2019 Yee Foundation.
+//
+// This file is part of YeeChain.
+//
+// YeeChain is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YeeChain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use futures::Future;
+use jsonrpc_core::{BoxFuture, Metadata, Params, RemoteProcedure, RpcMethod, Value};
+use tokio::timer::Timeout;
+
+use crate::config::Config;
+use crate::rpc::errors;
+
+/// Timeouts of the rpc methods in ms, 0 means no timeout.
+/// Read from the live config on each call, a reload takes effect on the next calls
+pub struct Timeouts {
+	config: Arc<RwLock<Config>>,
+}
+
+impl Timeouts {
+	pub fn new(config: Arc<RwLock<Config>>) -> Self {
+		Timeouts { config }
+	}
+
+	/// Bound the future of the method by its timeout
+	pub fn apply<M: Metadata>(
+		&self,
+		name: &str,
+		procedure: RemoteProcedure<M>,
+	) -> RemoteProcedure<M> {
+		match procedure {
+			RemoteProcedure::Method(method) => RemoteProcedure::Method(Arc::new(TimeoutMethod {
+				name: name.to_string(),
+				config: self.config.clone(),
+				method,
+			})),
+			procedure => procedure,
+		}
+	}
+}
+
+/// The method override or the default
+fn get_timeout_ms(config: &Config, method: &str) -> u64 {
+	config
+		.method_timeouts_ms
+		.get(method)
+		.cloned()
+		.unwrap_or(config.request_timeout_ms)
+}
+
+struct TimeoutMethod<M: Metadata> {
+	name: String,
+	config: Arc<RwLock<Config>>,
+	method: Arc<dyn RpcMethod<M>>,
+}
+
+impl<M: Metadata> RpcMethod<M> for TimeoutMethod<M> {
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Value> {
+		let name = self.name.clone();
+		let timeout_ms = get_timeout_ms(&self.config.read().expect("qed"), &name);
+		if timeout_ms == 0 {
+			return self.method.call(params, meta);
+		}
+
+		let result = Timeout::new(
+			self.method.call(params, meta),
+			Duration::from_millis(timeout_ms),
+		);
+
+		let result = result.map_err(move |e| {
+			if e.is_elapsed() {
+				return errors::Error::from(errors::ErrorKind::Timeout(name, timeout_ms)).into();
+			}
+			match e.into_inner() {
+				Some(e) => e,
+				None => errors::internal("Timer error"),
+			}
+		});
+
+		Box::new(result)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, RwLock};
+	use std::time::{Duration, Instant};
+
+	use futures::Future;
+	use jsonrpc_core::{MetaIoHandler, Params, RemoteProcedure, RpcMethod, Value};
+	use serde_json::json;
+	use tokio::runtime::Runtime;
+	use tokio::timer::Delay;
+
+	use crate::config::Config;
+
+	use super::Timeouts;
+
+	#[test]
+	fn test_timeouts() {
+		let config: Config = toml::from_str(
+			r#"
+			request_timeout_ms = 20
+			[method_timeouts_ms]
+			slow_scan = 1000
+			[shards]
+			"#,
+		)
+		.unwrap();
+		let config = Arc::new(RwLock::new(config));
+		let timeouts = Timeouts::new(config.clone());
+
+		// both methods take 100 ms
+		let methods = ["slow_scan", "slow_lookup"]
+			.iter()
+			.map(|name| {
+				let method: Arc<dyn RpcMethod<()>> = Arc::new(|_: Params, _: ()| {
+					Delay::new(Instant::now() + Duration::from_millis(100))
+						.map(|_| Value::Bool(true))
+						.map_err(|_| jsonrpc_core::Error::internal_error())
+				});
+				let procedure = timeouts.apply(name, RemoteProcedure::Method(method));
+				(name.to_string(), procedure)
+			})
+			.collect::<Vec<_>>();
+		let mut io = MetaIoHandler::<()>::default();
+		io.extend_with(methods);
+
+		let mut runtime = Runtime::new().unwrap();
+		let mut call = |method: &str| {
+			let request = json!({ "jsonrpc": "2.0", "method": method, "params": [], "id": 1 });
+			let response = runtime
+				.block_on(io.handle_request(&request.to_string(), ()))
+				.unwrap()
+				.unwrap();
+			serde_json::from_str::<Value>(&response).unwrap()
+		};
+
+		// the longer timeout of the method is honored
+		assert_eq!(call("slow_scan")["result"], true);
+
+		// the other methods time out by default
+		let response = call("slow_lookup");
+		assert_eq!(response["error"]["message"], "Request timeout");
+		assert_eq!(
+			response["error"]["data"],
+			json!({ "method": "slow_lookup", "timeout_ms": 20 })
+		);
+
+		// the reloaded timeouts
+		config.write().unwrap().request_timeout_ms = 0;
+		assert_eq!(call("slow_lookup")["result"], true);
+	}
+}
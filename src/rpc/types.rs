@@ -16,9 +16,10 @@
 // along with YeeChain.  If not, see <https://www.gnu.org/licenses/>.
 
 use parity_codec::{Compact, Decode};
-use parity_codec::Encode;
-use serde::{Deserialize, Serialize};
+use parity_codec::{Encode, Output};
+use runtime_primitives::traits::{BlakeTwo256, Hash as HashT};
 use serde::export::TryFrom;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use substrate_primitives::blake2_256;
 use substrate_primitives::storage::StorageKey;
@@ -36,7 +37,38 @@ pub type Hash = primitive_types::H256;
 
 pub type BlockNumber = u64;
 
-pub type Nonce = u64;
+/// Block number of the rpc params, a number or a `0x` prefixed hex string
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum NumberOrHex {
+	Number(BlockNumber),
+	Hex(Hex<BlockNumber>),
+}
+
+impl NumberOrHex {
+	pub fn number(&self) -> BlockNumber {
+		match self {
+			NumberOrHex::Number(number) => *number,
+			NumberOrHex::Hex(number) => number.0,
+		}
+	}
+}
+
+/// Serialized as both the integer and the hex
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Nonce {
+	pub nonce: u64,
+	pub nonce_hex: Hex<u64>,
+}
+
+impl From<u64> for Nonce {
+	fn from(t: u64) -> Self {
+		Nonce {
+			nonce: t,
+			nonce_hex: Hex(t),
+		}
+	}
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Balance(pub u128);
@@ -55,6 +87,29 @@ pub struct Header {
 
 	#[serde(with = "SerdeHex")]
 	pub number: BlockNumber,
+
+	#[serde(default)]
+	pub digest: Digest,
+}
+
+/// The logs are the encoded digest items
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Digest {
+	pub logs: Vec<Hex<Vec<u8>>>,
+}
+
+/// SCALE encoded as the node header
+impl Encode for Header {
+	fn encode_to<T: Output>(&self, dest: &mut T) {
+		dest.write(&self.parent_hash);
+		Compact(self.number).encode_to(dest);
+		dest.write(&self.state_root);
+		dest.write(&self.extrinsics_root);
+		Compact(self.digest.logs.len() as u32).encode_to(dest);
+		for log in &self.digest.logs {
+			dest.write(&log.0);
+		}
+	}
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -70,7 +125,49 @@ pub struct Block {
 	pub extrinsics: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Block {
+	/// SCALE encoded as the node block, the extrinsics are length prefixed
+	pub fn encode_scale(&self) -> errors::Result<Vec<u8>> {
+		let mut result = self.header.encode();
+		Compact(self.extrinsics.len() as u32).encode_to(&mut result);
+		for x in &self.extrinsics {
+			hex_decode(x)?.encode_to(&mut result);
+		}
+		Ok(result)
+	}
+
+	/// The ordered trie root of the extrinsics, as the `extrinsics_root` of the header
+	pub fn compute_extrinsics_root(&self) -> errors::Result<Vec<u8>> {
+		let extrinsics = self
+			.extrinsics
+			.iter()
+			.map(|x| hex_decode(x))
+			.collect::<errors::Result<Vec<_>>>()?;
+		let extrinsics = extrinsics.iter().map(|x| &x[..]).collect::<Vec<_>>();
+
+		let root = BlakeTwo256::enumerated_trie_root(&extrinsics);
+
+		Ok(root.as_ref().to_vec())
+	}
+}
+
+/// Encoding of the block and header results
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+	/// The decoded form
+	Json,
+	/// The hex of the SCALE bytes from the node
+	Scale,
+}
+
+impl Default for Encoding {
+	fn default() -> Self {
+		Encoding::Json
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ResultHeader {
 	pub block_hash: Option<Hex<Vec<u8>>>,
 
@@ -98,7 +195,7 @@ impl From<Header> for ResultHeader {
 	}
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct ResultBlock {
 	pub header: ResultHeader,
 	pub extrinsics: Vec<ResultTransaction>,
@@ -118,8 +215,8 @@ impl TryFrom<Block> for ResultBlock {
 				let mut length_prefix: Vec<u8> = Compact(x.len() as u32).encode();
 				length_prefix.append(&mut x);
 				let raw = length_prefix;
-				let x: Transaction =
-					Decode::decode(&mut &raw[..]).ok_or(errors::ErrorKind::ParseError)?;
+				let x: Transaction = Decode::decode(&mut &raw[..])
+					.ok_or(errors::ErrorKind::DecodeFailed("transaction"))?;
 				let mut x: ResultTransaction = x.into();
 
 				let hash = blake2_256(&raw);
@@ -160,6 +257,16 @@ impl TryFrom<ResultTransaction> for Value {
 	}
 }
 
+impl TryFrom<Nonce> for Value {
+	type Error = errors::Error;
+
+	fn try_from(x: Nonce) -> Result<Self, Self::Error> {
+		let x = serde_json::to_vec(&x).map_err(|_| errors::ErrorKind::ParseError)?;
+		let x = serde_json::from_slice(&x).map_err(|_| errors::ErrorKind::ParseError)?;
+		Ok(x)
+	}
+}
+
 impl TryFrom<Balance> for Value {
 	type Error = errors::Error;
 
@@ -170,7 +277,7 @@ impl TryFrom<Balance> for Value {
 	}
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct ResultSignature {
 	#[serde(with = "SerdeHex")]
 	pub sender: Vec<u8>,
@@ -182,7 +289,7 @@ pub struct ResultSignature {
 	pub era: ResultEra,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct ResultTransaction {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub raw: Option<Hex<Vec<u8>>>,
@@ -191,11 +298,14 @@ pub struct ResultTransaction {
 	pub call: Call,
 	pub index: Option<u32>,
 	pub success: Option<bool>,
+	/// Fee paid by the transfer of the extrinsic
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub fee: Option<u128>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub block_number: Option<BlockNumber>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub enum ResultEra {
 	Immortal,
 	Mortal(u64, u64),
@@ -234,6 +344,7 @@ impl From<Transaction> for ResultTransaction {
 			call: t.call,
 			index: None,
 			success: None,
+			fee: None,
 			block_number: None,
 		}
 	}
@@ -275,3 +386,95 @@ fn hex_decode(str: &str) -> errors::Result<Vec<u8>> {
 	let result = hex::decode(str).map_err(|_| errors::ErrorKind::ParseError)?;
 	Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+	use std::convert::TryInto;
+
+	use serde::export::TryFrom;
+	use serde_json::{json, Value};
+
+	use crate::rpc::errors;
+
+	use super::{Block, Nonce, NumberOrHex, ResultBlock};
+
+	#[test]
+	fn test_number_or_hex() {
+		let number: NumberOrHex = serde_json::from_str("400").unwrap();
+		assert_eq!(number.number(), 400);
+
+		let number: NumberOrHex = serde_json::from_str(r#""0x190""#).unwrap();
+		assert_eq!(number.number(), 400);
+
+		let number: NumberOrHex = serde_json::from_str(r#""0xffffffffffffffff""#).unwrap();
+		assert_eq!(number.number(), u64::max_value());
+
+		// out of range
+		assert!(serde_json::from_str::<NumberOrHex>(r#""0x010000000000000000""#).is_err());
+		assert!(serde_json::from_str::<NumberOrHex>("-1").is_err());
+
+		// malformed
+		assert!(serde_json::from_str::<NumberOrHex>(r#""0xzz""#).is_err());
+		assert!(serde_json::from_str::<NumberOrHex>(r#""400""#).is_err());
+		assert!(serde_json::from_str::<NumberOrHex>("4.5").is_err());
+	}
+
+	#[test]
+	fn test_nonce() {
+		let nonce: Value = Nonce::from(300).try_into().unwrap();
+		assert_eq!(nonce, json!({ "nonce": 300, "nonce_hex": "0x12c" }));
+
+		let nonce: Value = Nonce::from(0).try_into().unwrap();
+		assert_eq!(nonce, json!({ "nonce": 0, "nonce_hex": "0x0" }));
+	}
+
+	#[test]
+	fn test_result_block_decode_failed() {
+		let block: Block = serde_json::from_value(json!({
+			"header": {
+				"extrinsicsRoot": "0x00",
+				"parentHash": "0x00",
+				"stateRoot": "0x00",
+				"number": "0x41",
+			},
+			"extrinsics": ["0xff"],
+		}))
+		.unwrap();
+
+		match ResultBlock::try_from(block) {
+			Err(errors::Error(errors::ErrorKind::DecodeFailed("transaction"), _)) => (),
+			_ => panic!("expected DecodeFailed"),
+		}
+	}
+
+	#[test]
+	fn test_compute_extrinsics_root() {
+		let block = |extrinsics: Value| -> Block {
+			serde_json::from_value(json!({
+				"header": {
+					"extrinsicsRoot": "0x00",
+					"parentHash": "0x00",
+					"stateRoot": "0x00",
+					"number": "0x41",
+				},
+				"extrinsics": extrinsics,
+			}))
+			.unwrap()
+		};
+
+		// the root of the empty trie
+		assert_eq!(
+			hex::encode(block(json!([])).compute_extrinsics_root().unwrap()),
+			"03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314"
+		);
+
+		// ordered by the index
+		let root = |extrinsics: Value| block(extrinsics).compute_extrinsics_root().unwrap();
+		assert_ne!(
+			root(json!(["0x0401", "0x0402"])),
+			root(json!(["0x0402", "0x0401"]))
+		);
+
+		assert!(block(json!(["0xzz"])).compute_extrinsics_root().is_err());
+	}
+}